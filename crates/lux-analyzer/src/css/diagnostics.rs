@@ -0,0 +1,162 @@
+use lux_ast::analysis::{
+    AnalysisDiagnostic, AnalysisDiagnosticCode, AnalysisSeverity, AnalysisTables,
+};
+use lux_ast::common::Span;
+use lux_ast::css::selector::{CombinatorKind, ComplexSelector, SimpleSelector};
+use lux_ast::css::stylesheet::{CssBlockChild, CssRule};
+
+use super::selector::{is_relative_global, starts_with_global_block};
+
+/// Checks the `:global(...)` selectors of a complex selector, which must
+/// still produce valid CSS once the `:global(...)` wrapper is removed.
+pub(super) fn validate_global_selectors(
+    complex_selector: &ComplexSelector<'_>,
+    tables: &mut AnalysisTables,
+) {
+    let relatives = &complex_selector.children;
+    if let Some(index) = relatives.iter().position(is_relative_global)
+        && let Some(SimpleSelector::PseudoClassSelector(global)) =
+            relatives[index].selectors.first()
+        && global.args.is_some()
+        && index != 0
+        && index + 1 != relatives.len()
+        && !relatives[index + 1..].iter().all(is_relative_global)
+    {
+        push_error(
+            tables,
+            AnalysisDiagnosticCode::CssGlobalInvalidPlacement,
+            "`:global(...)` can be at the start or end of a selector sequence, but not in the middle",
+            global.span,
+        );
+    }
+
+    for relative_selector in relatives {
+        for (index, selector) in relative_selector.selectors.iter().enumerate() {
+            let SimpleSelector::PseudoClassSelector(pseudo_class) = selector else {
+                continue;
+            };
+            if pseudo_class.name != "global" {
+                continue;
+            }
+
+            let starts_with_type = pseudo_class
+                .args
+                .as_ref()
+                .and_then(|args| args.children.first())
+                .and_then(|complex| complex.children.first())
+                .and_then(|relative| relative.selectors.first())
+                .is_some_and(|selector| matches!(selector, SimpleSelector::TypeSelector(_)));
+            if starts_with_type && index != 0 {
+                push_error(
+                    tables,
+                    AnalysisDiagnosticCode::CssGlobalInvalidSelectorList,
+                    "`:global(...)` must not contain type or universal selectors when used in a compound selector",
+                    pseudo_class.span,
+                );
+            }
+
+            if let Some(SimpleSelector::TypeSelector(type_selector)) =
+                relative_selector.selectors.get(index + 1)
+            {
+                push_error(
+                    tables,
+                    AnalysisDiagnosticCode::CssTypeSelectorInvalidPlacement,
+                    "`:global(...)` must not be followed by a type selector",
+                    type_selector.span,
+                );
+            }
+        }
+    }
+}
+
+/// Checks rules using a `:global { ... }` block: the block must follow a
+/// descendant combinator, a lone `:global` cannot hold declarations, and a
+/// selector list cannot mix global blocks with other selectors.
+pub(super) fn validate_global_block(rule: &CssRule<'_>, tables: &mut AnalysisTables) {
+    let mut has_global_block = false;
+    let mut has_plain_selector = false;
+    let mut has_lone_global = false;
+
+    for complex_selector in &rule.prelude.children {
+        let Some(block_start) = complex_selector
+            .children
+            .iter()
+            .find(|relative| starts_with_global_block(relative))
+        else {
+            has_plain_selector = true;
+            continue;
+        };
+        has_global_block = true;
+
+        if let Some(combinator) = &block_start.combinator
+            && combinator.kind != CombinatorKind::Descendant
+        {
+            push_error(
+                tables,
+                AnalysisDiagnosticCode::CssGlobalBlockInvalidCombinator,
+                format!(
+                    "A `:global` selector cannot follow a `{}` combinator",
+                    combinator_name(combinator.kind)
+                ),
+                block_start.span,
+            );
+        }
+
+        let is_lone_global =
+            complex_selector.children.len() == 1 && block_start.selectors.len() == 1;
+        if !is_lone_global {
+            continue;
+        }
+
+        has_lone_global = true;
+        if let Some(CssBlockChild::Declaration(declaration)) = rule
+            .block
+            .children
+            .iter()
+            .find(|child| matches!(child, CssBlockChild::Declaration(_)))
+        {
+            push_error(
+                tables,
+                AnalysisDiagnosticCode::CssGlobalBlockInvalidDeclaration,
+                "A top-level `:global {...}` block can only contain rules, not declarations",
+                declaration.span,
+            );
+        }
+    }
+
+    // `:global, :global x { y {} }` would constrain `y` to `x` once the
+    // lone `:global` is dropped, so it is rejected like a mixed list.
+    let is_list = rule.prelude.children.len() > 1;
+    if has_global_block && (has_plain_selector || (is_list && has_lone_global)) {
+        push_error(
+            tables,
+            AnalysisDiagnosticCode::CssGlobalBlockInvalidList,
+            "A `:global` selector cannot be part of a selector list with entries that don't contain `:global`",
+            rule.prelude.span,
+        );
+    }
+}
+
+fn combinator_name(kind: CombinatorKind) -> &'static str {
+    match kind {
+        CombinatorKind::Descendant => " ",
+        CombinatorKind::Child => ">",
+        CombinatorKind::NextSibling => "+",
+        CombinatorKind::SubsequentSibling => "~",
+        CombinatorKind::Column => "||",
+    }
+}
+
+fn push_error(
+    tables: &mut AnalysisTables,
+    code: AnalysisDiagnosticCode,
+    message: impl Into<String>,
+    span: Span,
+) {
+    tables.diagnostics.push(AnalysisDiagnostic {
+        severity: AnalysisSeverity::Error,
+        code,
+        message: message.into(),
+        span,
+    });
+}
@@ -1,15 +1,27 @@
+mod diagnostics;
+mod prune;
 mod rule;
 mod selector;
+mod values;
 
 use lux_ast::analysis::AnalysisTables;
 use lux_ast::css::StyleSheet;
 use lux_ast::css::stylesheet::StyleSheetChild;
+use lux_ast::template::root::Fragment;
 
-pub(super) fn analyze_stylesheet<'a>(stylesheet: &StyleSheet<'a>, tables: &mut AnalysisTables) {
+pub use values::{PossibleValues, possible_attribute_values, possible_class_names};
+
+pub(super) fn analyze_stylesheet<'a>(
+    stylesheet: &StyleSheet<'a>,
+    fragment: &Fragment<'a>,
+    tables: &mut AnalysisTables,
+) {
     for child in &stylesheet.children {
         match child {
             StyleSheetChild::Rule(rule) => rule::analyze_rule(rule, None, tables),
             StyleSheetChild::Atrule(atrule) => rule::analyze_atrule(atrule, None, tables),
         }
     }
+
+    prune::mark_used_selectors(stylesheet, fragment, tables);
 }
@@ -0,0 +1,732 @@
+use lux_ast::analysis::{
+    AnalysisDiagnostic, AnalysisDiagnosticCode, AnalysisNodeKind, AnalysisSeverity, AnalysisTables,
+    RelativeSelectorAnalysis, SpanKey,
+};
+use lux_ast::css::StyleSheet;
+use lux_ast::css::selector::{
+    Combinator, CombinatorKind, ComplexSelector, PseudoClassSelector, RelativeSelector,
+    SelectorList, SimpleSelector,
+};
+use lux_ast::css::stylesheet::{CssAtrule, CssBlock, CssBlockChild, CssRule, StyleSheetChild};
+use lux_ast::template::attribute::AttributeNode;
+use lux_ast::template::root::{Fragment, FragmentNode};
+use oxc_ast::ast::Expression;
+
+use super::selector::mark_selector_list_used;
+use super::values::{possible_attribute_values, possible_class_names};
+
+/// Marks the complex selectors that may match an element of the template as
+/// used and warns about the others, which are left out of the compiled CSS.
+pub(super) fn mark_used_selectors(
+    stylesheet: &StyleSheet<'_>,
+    fragment: &Fragment<'_>,
+    tables: &mut AnalysisTables,
+) {
+    let mut pruner = SelectorPruner {
+        stylesheet,
+        template: TemplateElements::collect(fragment),
+        tables,
+    };
+    for child in &stylesheet.children {
+        match child {
+            StyleSheetChild::Rule(rule) => pruner.visit_rule(rule),
+            StyleSheetChild::Atrule(atrule) => pruner.visit_atrule(atrule),
+        }
+    }
+}
+
+struct SelectorPruner<'s, 't, 'a> {
+    stylesheet: &'s StyleSheet<'s>,
+    template: TemplateElements<'t, 'a>,
+    tables: &'s mut AnalysisTables,
+}
+
+impl SelectorPruner<'_, '_, '_> {
+    fn visit_rule(&mut self, rule: &CssRule<'_>) {
+        self.visit_selector_list(&rule.prelude);
+        self.visit_block(&rule.block);
+    }
+
+    fn visit_atrule(&mut self, atrule: &CssAtrule<'_>) {
+        let Some(block) = &atrule.block else {
+            return;
+        };
+        // Keyframe selectors such as `from` and `50%` select no elements.
+        if is_keyframes(atrule) {
+            for child in &block.children {
+                if let CssBlockChild::Rule(rule) = child {
+                    mark_selector_list_used(&rule.prelude, self.tables);
+                }
+            }
+            return;
+        }
+        self.visit_block(block);
+    }
+
+    fn visit_block(&mut self, block: &CssBlock<'_>) {
+        for child in &block.children {
+            match child {
+                CssBlockChild::Declaration(_) => {}
+                CssBlockChild::Rule(rule) => self.visit_rule(rule),
+                CssBlockChild::Atrule(atrule) => self.visit_atrule(atrule),
+            }
+        }
+    }
+
+    /// Checks each complex selector of the list and, for those that are
+    /// kept, the selector lists of their pseudo-classes other than
+    /// `:global(...)`, which are never pruned.
+    fn visit_selector_list(&mut self, selector_list: &SelectorList<'_>) {
+        for complex in &selector_list.children {
+            if !self.complex_is_used_or_maybe_used(complex) {
+                self.warn_unused(complex);
+                continue;
+            }
+
+            for relative in &complex.children {
+                for selector in &relative.selectors {
+                    if let SimpleSelector::PseudoClassSelector(pseudo_class) = selector
+                        && pseudo_class.name != "global"
+                        && let Some(args) = &pseudo_class.args
+                    {
+                        self.visit_selector_list(args);
+                    }
+                }
+            }
+        }
+    }
+
+    fn complex_is_used_or_maybe_used(&mut self, complex: &ComplexSelector<'_>) -> bool {
+        let key = SpanKey::new(AnalysisNodeKind::ComplexSelector, complex.span);
+        let complex_analysis = self
+            .tables
+            .complex_selectors
+            .get(&key)
+            .cloned()
+            .unwrap_or_default();
+        if complex_analysis.used || complex_analysis.is_global {
+            return true;
+        }
+
+        let might_match = self.template.complex_might_match(complex, self.tables);
+        if might_match {
+            self.tables.complex_selectors.entry(key).or_default().used = true;
+        }
+        might_match
+    }
+
+    fn warn_unused(&mut self, complex: &ComplexSelector<'_>) {
+        let content_start = self.stylesheet.content_start;
+        let text = self
+            .stylesheet
+            .content_styles
+            .get(
+                (complex.span.start - content_start) as usize
+                    ..(complex.span.end - content_start) as usize,
+            )
+            .unwrap_or_default();
+        self.tables.diagnostics.push(AnalysisDiagnostic {
+            severity: AnalysisSeverity::Warning,
+            code: AnalysisDiagnosticCode::CssUnusedSelector,
+            message: format!("Unused CSS selector \"{text}\""),
+            span: complex.span,
+        });
+    }
+}
+
+fn is_keyframes(atrule: &CssAtrule<'_>) -> bool {
+    atrule.name == "keyframes"
+        || (atrule.name.starts_with('-') && atrule.name.ends_with("-keyframes"))
+}
+
+/// Elements of the component template with the parent and sibling
+/// relationships needed to match selectors right-to-left, the way Svelte's
+/// `css-prune` does.
+struct TemplateElements<'t, 'a> {
+    elements: Vec<TemplateElement<'t, 'a>>,
+}
+
+struct TemplateElement<'t, 'a> {
+    /// `None` for `<svelte:element>` with a dynamic tag.
+    name: Option<&'t str>,
+    attributes: &'t [AttributeNode<'a>],
+    parent: ElementParent,
+    /// Elements that may directly precede this one (`+`).
+    previous: Vec<Sibling>,
+    /// Elements that may precede this one anywhere in the parent (`~`).
+    preceding: Vec<Sibling>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElementParent {
+    /// Top level of the component; ancestors are outside of it.
+    Root,
+    Element(usize),
+    /// Inside a snippet, which can be rendered under any element.
+    Boundary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sibling {
+    Element(usize),
+    /// A component, slot, `{@render}` or `{@html}` that may render anything.
+    Boundary,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SiblingState {
+    previous: Vec<Sibling>,
+    preceding: Vec<Sibling>,
+}
+
+impl SiblingState {
+    fn boundary() -> Self {
+        Self {
+            previous: vec![Sibling::Boundary],
+            preceding: vec![Sibling::Boundary],
+        }
+    }
+
+    fn merge(&mut self, other: &SiblingState) {
+        merge_siblings(&mut self.previous, &other.previous);
+        merge_siblings(&mut self.preceding, &other.preceding);
+    }
+
+    fn push_boundary(&mut self) {
+        merge_siblings(&mut self.previous, &[Sibling::Boundary]);
+        merge_siblings(&mut self.preceding, &[Sibling::Boundary]);
+    }
+}
+
+fn merge_siblings(target: &mut Vec<Sibling>, source: &[Sibling]) {
+    for sibling in source {
+        if !target.contains(sibling) {
+            target.push(*sibling);
+        }
+    }
+}
+
+impl<'t, 'a> TemplateElements<'t, 'a> {
+    fn collect(fragment: &'t Fragment<'a>) -> Self {
+        let mut elements = Self {
+            elements: Vec::new(),
+        };
+        elements.walk_fragment(fragment, ElementParent::Root, &mut SiblingState::default());
+        elements
+    }
+
+    fn walk_fragment(
+        &mut self,
+        fragment: &'t Fragment<'a>,
+        parent: ElementParent,
+        state: &mut SiblingState,
+    ) {
+        for node in &fragment.nodes {
+            self.walk_node(node, parent, state);
+        }
+    }
+
+    fn walk_branches(
+        &mut self,
+        branches: &[Option<&'t Fragment<'a>>],
+        parent: ElementParent,
+        state: &mut SiblingState,
+        may_render_nothing: bool,
+    ) {
+        let incoming = state.clone();
+        let mut outgoing = if may_render_nothing {
+            incoming.clone()
+        } else {
+            SiblingState::default()
+        };
+        for branch in branches {
+            let mut branch_state = incoming.clone();
+            if let Some(fragment) = branch {
+                self.walk_fragment(fragment, parent, &mut branch_state);
+            }
+            outgoing.merge(&branch_state);
+        }
+        *state = outgoing;
+    }
+
+    fn walk_element(
+        &mut self,
+        name: Option<&'t str>,
+        attributes: &'t [AttributeNode<'a>],
+        fragment: &'t Fragment<'a>,
+        parent: ElementParent,
+        state: &mut SiblingState,
+    ) {
+        let index = self.elements.len();
+        self.elements.push(TemplateElement {
+            name,
+            attributes,
+            parent,
+            previous: state.previous.clone(),
+            preceding: state.preceding.clone(),
+        });
+        self.walk_fragment(
+            fragment,
+            ElementParent::Element(index),
+            &mut SiblingState::default(),
+        );
+        state.previous = vec![Sibling::Element(index)];
+        merge_siblings(&mut state.preceding, &[Sibling::Element(index)]);
+    }
+
+    fn walk_node(
+        &mut self,
+        node: &'t FragmentNode<'a>,
+        parent: ElementParent,
+        state: &mut SiblingState,
+    ) {
+        match node {
+            FragmentNode::RegularElement(element) => self.walk_element(
+                Some(element.name),
+                &element.attributes,
+                &element.fragment,
+                parent,
+                state,
+            ),
+            FragmentNode::SvelteElement(element) => self.walk_element(
+                static_tag_name(&element.tag),
+                &element.attributes,
+                &element.fragment,
+                parent,
+                state,
+            ),
+            FragmentNode::Component(component) => {
+                self.walk_component_children(&component.fragment, parent);
+                state.push_boundary();
+            }
+            FragmentNode::SvelteComponent(component) => {
+                self.walk_component_children(&component.fragment, parent);
+                state.push_boundary();
+            }
+            FragmentNode::SvelteSelf(component) => {
+                self.walk_component_children(&component.fragment, parent);
+                state.push_boundary();
+            }
+            FragmentNode::SlotElement(slot) => {
+                self.walk_branches(&[Some(&slot.fragment)], parent, state, true);
+                state.push_boundary();
+            }
+            FragmentNode::RenderTag(_) | FragmentNode::HtmlTag(_) => state.push_boundary(),
+            FragmentNode::SnippetBlock(block) => self.walk_snippet_body(&block.body),
+            FragmentNode::IfBlock(block) => self.walk_branches(
+                &[Some(&block.consequent), block.alternate.as_ref()],
+                parent,
+                state,
+                block.alternate.is_none(),
+            ),
+            FragmentNode::EachBlock(block) => {
+                // Each iterations follow one another, so the body is walked a
+                // second time with its own trailing elements as siblings.
+                // Walking is deterministic, so the indices line up.
+                let start = self.elements.len();
+                let mut first_pass = state.clone();
+                self.walk_fragment(&block.body, parent, &mut first_pass);
+                self.elements.truncate(start);
+
+                let mut body_state = state.clone();
+                body_state.merge(&first_pass);
+                self.walk_fragment(&block.body, parent, &mut body_state);
+
+                let mut fallback_state = state.clone();
+                if let Some(fallback) = &block.fallback {
+                    self.walk_fragment(fallback, parent, &mut fallback_state);
+                }
+                state.merge(&body_state);
+                state.merge(&fallback_state);
+            }
+            FragmentNode::AwaitBlock(block) => self.walk_branches(
+                &[
+                    block.pending.as_ref(),
+                    block.then.as_ref(),
+                    block.catch.as_ref(),
+                ],
+                parent,
+                state,
+                true,
+            ),
+            FragmentNode::KeyBlock(block) => self.walk_fragment(&block.fragment, parent, state),
+            FragmentNode::SvelteBoundary(element) => {
+                self.walk_fragment(&element.fragment, parent, state)
+            }
+            FragmentNode::SvelteFragment(element) => {
+                self.walk_fragment(&element.fragment, parent, state)
+            }
+            FragmentNode::SvelteHead(head) => self.walk_fragment(
+                &head.fragment,
+                ElementParent::Root,
+                &mut SiblingState::default(),
+            ),
+            FragmentNode::Text(_)
+            | FragmentNode::ExpressionTag(_)
+            | FragmentNode::ConstTag(_)
+            | FragmentNode::DebugTag(_)
+            | FragmentNode::AttachTag(_)
+            | FragmentNode::Comment(_)
+            | FragmentNode::SvelteBody(_)
+            | FragmentNode::SvelteWindow(_)
+            | FragmentNode::SvelteDocument(_)
+            | FragmentNode::TitleElement(_)
+            | FragmentNode::SvelteOptionsRaw(_) => {}
+        }
+    }
+
+    /// Slotted content stays under the surrounding elements, but its siblings
+    /// come from the child component's template.
+    fn walk_component_children(&mut self, fragment: &'t Fragment<'a>, parent: ElementParent) {
+        self.walk_fragment(fragment, parent, &mut SiblingState::boundary());
+    }
+
+    fn walk_snippet_body(&mut self, fragment: &'t Fragment<'a>) {
+        self.walk_fragment(
+            fragment,
+            ElementParent::Boundary,
+            &mut SiblingState::boundary(),
+        );
+    }
+
+    /// Returns whether some element of the template may be matched by the
+    /// complex selector, following its combinators.
+    fn complex_might_match(
+        &self,
+        complex: &ComplexSelector<'_>,
+        analysis: &AnalysisTables,
+    ) -> bool {
+        let relatives = scoped_relatives(complex, analysis);
+        if relatives.is_empty() {
+            return true;
+        }
+
+        (0..self.elements.len()).any(|index| self.apply_selector(relatives, analysis, index, None))
+    }
+
+    /// Matches the selectors right-to-left from the element at `index`. With
+    /// an anchor, the leftmost selector must also stand in the relation of
+    /// its combinator to the anchor element, as for the arguments of
+    /// `:has(...)`.
+    fn apply_selector(
+        &self,
+        relatives: &[RelativeSelector<'_>],
+        analysis: &AnalysisTables,
+        index: usize,
+        anchor: Option<HasAnchor>,
+    ) -> bool {
+        let Some((relative, rest)) = relatives.split_last() else {
+            return false;
+        };
+
+        self.relative_might_apply(relative, analysis, index)
+            && self.apply_combinator(relative.combinator.as_ref(), rest, analysis, index, anchor)
+    }
+
+    fn relative_might_apply(
+        &self,
+        relative: &RelativeSelector<'_>,
+        analysis: &AnalysisTables,
+        index: usize,
+    ) -> bool {
+        if relative_is_global(relative, analysis) {
+            return true;
+        }
+
+        let element = &self.elements[index];
+        let outer_subject = has_outer_subject(relative);
+        relative.selectors.iter().all(|selector| match selector {
+            SimpleSelector::PseudoClassSelector(pseudo_class) => {
+                self.pseudo_class_might_apply(pseudo_class, analysis, index, outer_subject)
+            }
+            _ => simple_selector_targets_element(selector, element.name, element.attributes),
+        })
+    }
+
+    fn pseudo_class_might_apply(
+        &self,
+        pseudo_class: &PseudoClassSelector<'_>,
+        analysis: &AnalysisTables,
+        index: usize,
+        outer_subject: bool,
+    ) -> bool {
+        let Some(args) = &pseudo_class.args else {
+            return match pseudo_class.name {
+                "root" => outer_subject,
+                "global" | "host" => false,
+                _ => true,
+            };
+        };
+
+        match pseudo_class.name {
+            "has" => args.children.iter().any(|complex| {
+                self.has_argument_might_match(complex, analysis, index, outer_subject)
+            }),
+            "is" | "where" => args.children.iter().any(|complex| {
+                let relatives = scoped_relatives(complex, analysis);
+                // `a :is(b c)` may also match with `b` as an ancestor of
+                // `a`, which is not checked; such arguments are kept.
+                relatives.is_empty()
+                    || complex.children.len() > 1
+                    || self.apply_selector(relatives, analysis, index, None)
+            }),
+            // `:not(...)` cannot be disproved from the possible attribute
+            // values, and a `:global(...)` inside a compound selector leaves
+            // the rest of it to be matched.
+            _ => true,
+        }
+    }
+
+    /// Whether an element related to the one at `index` as the argument's
+    /// leading combinator requires (a descendant by default) matches it.
+    /// With `:root` or `:global(...)` the subject may lie outside of the
+    /// component, so the element itself is a candidate too.
+    fn has_argument_might_match(
+        &self,
+        complex: &ComplexSelector<'_>,
+        analysis: &AnalysisTables,
+        index: usize,
+        include_self: bool,
+    ) -> bool {
+        let relatives = scoped_relatives(complex, analysis);
+        if relatives.is_empty() {
+            return true;
+        }
+
+        let anchor = HasAnchor {
+            index,
+            include_self,
+        };
+        (0..self.elements.len())
+            .any(|candidate| self.apply_selector(relatives, analysis, candidate, Some(anchor)))
+    }
+
+    fn apply_combinator(
+        &self,
+        combinator: Option<&Combinator>,
+        rest: &[RelativeSelector<'_>],
+        analysis: &AnalysisTables,
+        index: usize,
+        anchor: Option<HasAnchor>,
+    ) -> bool {
+        if rest.is_empty() {
+            return anchor.is_none_or(|anchor| self.is_anchored(combinator, anchor, index));
+        }
+
+        match combinator.map_or(CombinatorKind::Descendant, |combinator| combinator.kind) {
+            CombinatorKind::Descendant | CombinatorKind::Child => {
+                let adjacent = matches!(
+                    combinator.map(|combinator| combinator.kind),
+                    Some(CombinatorKind::Child)
+                );
+                let mut parent = self.elements[index].parent;
+                loop {
+                    match parent {
+                        ElementParent::Boundary => return true,
+                        ElementParent::Root => {
+                            return rest
+                                .iter()
+                                .all(|relative| relative_is_global(relative, analysis));
+                        }
+                        ElementParent::Element(parent_index) => {
+                            if self.apply_selector(rest, analysis, parent_index, anchor) {
+                                return true;
+                            }
+                            if adjacent {
+                                return false;
+                            }
+                            parent = self.elements[parent_index].parent;
+                        }
+                    }
+                }
+            }
+            CombinatorKind::NextSibling | CombinatorKind::SubsequentSibling => {
+                let element = &self.elements[index];
+                let siblings = if matches!(
+                    combinator.map(|combinator| combinator.kind),
+                    Some(CombinatorKind::NextSibling)
+                ) {
+                    &element.previous
+                } else {
+                    &element.preceding
+                };
+                let sibling_matched = siblings.iter().any(|sibling| match sibling {
+                    Sibling::Boundary => true,
+                    Sibling::Element(sibling_index) => {
+                        self.apply_selector(rest, analysis, *sibling_index, anchor)
+                    }
+                });
+
+                sibling_matched
+                    || (element.parent == ElementParent::Root
+                        && rest
+                            .iter()
+                            .all(|relative| relative_is_global(relative, analysis)))
+            }
+            CombinatorKind::Column => true,
+        }
+    }
+
+    fn is_anchored(
+        &self,
+        combinator: Option<&Combinator>,
+        anchor: HasAnchor,
+        index: usize,
+    ) -> bool {
+        if anchor.include_self && index == anchor.index {
+            return true;
+        }
+
+        let element = &self.elements[index];
+        match combinator.map_or(CombinatorKind::Descendant, |combinator| combinator.kind) {
+            CombinatorKind::Child => {
+                matches!(
+                    element.parent,
+                    ElementParent::Element(parent) if parent == anchor.index
+                ) || element.parent == ElementParent::Boundary
+            }
+            CombinatorKind::Descendant => {
+                let mut parent = element.parent;
+                loop {
+                    match parent {
+                        ElementParent::Boundary => return true,
+                        ElementParent::Root => return false,
+                        ElementParent::Element(parent_index) if parent_index == anchor.index => {
+                            return true;
+                        }
+                        ElementParent::Element(parent_index) => {
+                            parent = self.elements[parent_index].parent;
+                        }
+                    }
+                }
+            }
+            CombinatorKind::NextSibling => {
+                element.previous.contains(&Sibling::Element(anchor.index))
+            }
+            CombinatorKind::SubsequentSibling => {
+                element.preceding.contains(&Sibling::Element(anchor.index))
+            }
+            CombinatorKind::Column => true,
+        }
+    }
+}
+
+/// The element a `:has(...)` argument is matched relative to.
+#[derive(Debug, Clone, Copy)]
+struct HasAnchor {
+    index: usize,
+    include_self: bool,
+}
+
+/// Trailing `:global(...)` selectors never match inside the component; only
+/// the scoped part of a complex selector has to.
+fn scoped_relatives<'s, 'a>(
+    complex: &'s ComplexSelector<'a>,
+    analysis: &AnalysisTables,
+) -> &'s [RelativeSelector<'a>] {
+    let end = complex
+        .children
+        .iter()
+        .rposition(|relative| !relative_analysis(relative, analysis).is_global)
+        .map_or(0, |index| index + 1);
+    &complex.children[..end]
+}
+
+fn relative_is_global(relative: &RelativeSelector<'_>, analysis: &AnalysisTables) -> bool {
+    let relative_analysis = relative_analysis(relative, analysis);
+    relative_analysis.is_global
+        || relative_analysis.is_global_like
+        // `&` stands for the parent rule, which is matched on its own.
+        || relative
+            .selectors
+            .iter()
+            .any(|selector| matches!(selector, SimpleSelector::NestingSelector(_)))
+}
+
+fn static_tag_name<'t>(tag: &'t Expression<'_>) -> Option<&'t str> {
+    match tag {
+        Expression::StringLiteral(literal) => Some(literal.value.as_str()),
+        _ => None,
+    }
+}
+
+fn simple_selector_targets_element(
+    selector: &SimpleSelector<'_>,
+    name: Option<&str>,
+    attributes: &[AttributeNode<'_>],
+) -> bool {
+    match selector {
+        SimpleSelector::TypeSelector(type_selector) => {
+            type_selector.name == "*"
+                || name.is_none_or(|name| type_selector.name.eq_ignore_ascii_case(name))
+        }
+        SimpleSelector::IdSelector(id_selector) => possible_attribute_values(attributes, "id")
+            .is_some_and(|values| values.contains(id_selector.name)),
+        SimpleSelector::ClassSelector(class_selector) => {
+            possible_class_names(attributes).contains(class_selector.name)
+        }
+        SimpleSelector::AttributeSelector(attribute_selector) => {
+            let Some(values) = possible_attribute_values(attributes, attribute_selector.name)
+            else {
+                return false;
+            };
+
+            match (attribute_selector.matcher, attribute_selector.value) {
+                (Some("="), Some(expected)) if attribute_selector.flags.is_none() => {
+                    values.contains(strip_attribute_selector_quotes(expected))
+                }
+                _ => true,
+            }
+        }
+        // Pseudo-classes are matched by `TemplateElements`.
+        SimpleSelector::PseudoClassSelector(_) => true,
+        SimpleSelector::PseudoElementSelector(_)
+        | SimpleSelector::Percentage(_)
+        | SimpleSelector::Nth(_)
+        | SimpleSelector::NestingSelector(_) => true,
+    }
+}
+
+/// Whether a compound selector with `:has(...)` may select an element outside
+/// of the component, through `:root` or `:global(...)`.
+fn has_outer_subject(relative: &RelativeSelector<'_>) -> bool {
+    let mut has_has = false;
+    let mut has_outer = false;
+    for selector in &relative.selectors {
+        if let SimpleSelector::PseudoClassSelector(pseudo_class) = selector {
+            match pseudo_class.name {
+                "has" => has_has = true,
+                "root" => has_outer = true,
+                "global" => has_outer |= pseudo_class.args.is_some(),
+                _ => {}
+            }
+        }
+    }
+    has_has && has_outer
+}
+
+fn relative_analysis(
+    relative: &RelativeSelector<'_>,
+    analysis: &AnalysisTables,
+) -> RelativeSelectorAnalysis {
+    analysis
+        .relative_selectors
+        .get(&SpanKey::new(
+            AnalysisNodeKind::RelativeSelector,
+            relative.span,
+        ))
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn strip_attribute_selector_quotes(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|rest| rest.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
@@ -1,6 +1,7 @@
 use lux_ast::analysis::{AnalysisNodeKind, AnalysisTables, CssRuleAnalysis, SpanKey};
 use lux_ast::css::stylesheet::{CssAtrule, CssBlock, CssBlockChild, CssRule};
 
+use super::diagnostics::validate_global_block;
 use super::selector::analyze_complex_selector;
 
 pub(super) fn analyze_rule<'a>(
@@ -21,6 +22,7 @@ pub(super) fn analyze_rule<'a>(
         rule_analysis.is_global_block |= has_global_block;
     }
 
+    validate_global_block(rule, tables);
     tables.css_rules.insert(rule_key, rule_analysis);
     analyze_block(&rule.block, Some(rule_key), tables);
 }
@@ -1,9 +1,11 @@
 use lux_ast::analysis::AnalysisTables;
 use lux_ast::css::selector::{ComplexSelector, SelectorList, SimpleSelector};
 
+use super::super::diagnostics::validate_global_selectors;
 use super::global::starts_with_global_block;
 use super::relative::{
     analyze_relative_selector, insert_complex_analysis, insert_relative_analysis,
+    mark_selector_list_used, mark_unscoped,
 };
 
 pub(super) fn analyze_complex_selector(
@@ -11,6 +13,7 @@ pub(super) fn analyze_complex_selector(
     tables: &mut AnalysisTables,
 ) -> (bool, bool) {
     analyze_nested_selector_lists(complex_selector, tables);
+    validate_global_selectors(complex_selector, tables);
 
     let mut has_global_block = false;
     let mut in_global_block = false;
@@ -45,6 +48,21 @@ fn analyze_nested_selector_lists(
                 continue;
             };
             analyze_selector_list(args, tables);
+            if pseudo_class.name == "not" {
+                analyze_not_arguments(args, tables);
+            }
+        }
+    }
+}
+
+/// `:not(...)` never needs its arguments to match an element. Arguments made
+/// of a single compound selector stay unscoped, since scoping them would let
+/// the rule apply to more elements rather than fewer.
+fn analyze_not_arguments(selector_list: &SelectorList<'_>, tables: &mut AnalysisTables) {
+    mark_selector_list_used(selector_list, tables);
+    for complex_selector in &selector_list.children {
+        if let [relative_selector] = complex_selector.children.as_slice() {
+            mark_unscoped(relative_selector, tables);
         }
     }
 }
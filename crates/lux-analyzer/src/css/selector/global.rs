@@ -1,13 +1,13 @@
 use lux_ast::css::selector::{RelativeSelector, SimpleSelector};
 
-pub(super) fn starts_with_global_block(relative_selector: &RelativeSelector<'_>) -> bool {
+pub(crate) fn starts_with_global_block(relative_selector: &RelativeSelector<'_>) -> bool {
     relative_selector
         .selectors
         .first()
         .is_some_and(is_global_block_selector)
 }
 
-pub(super) fn is_relative_global(relative_selector: &RelativeSelector<'_>) -> bool {
+pub(crate) fn is_relative_global(relative_selector: &RelativeSelector<'_>) -> bool {
     let Some(SimpleSelector::PseudoClassSelector(first)) = relative_selector.selectors.first()
     else {
         return false;
@@ -2,6 +2,9 @@ mod complex;
 mod global;
 mod relative;
 
+pub(super) use global::{is_relative_global, starts_with_global_block};
+pub(super) use relative::mark_selector_list_used;
+
 use lux_ast::analysis::AnalysisTables;
 use lux_ast::css::selector::ComplexSelector;
 
@@ -1,7 +1,7 @@
 use lux_ast::analysis::{
     AnalysisNodeKind, AnalysisTables, ComplexSelectorAnalysis, RelativeSelectorAnalysis, SpanKey,
 };
-use lux_ast::css::selector::{ComplexSelector, RelativeSelector, SimpleSelector};
+use lux_ast::css::selector::{ComplexSelector, RelativeSelector, SelectorList, SimpleSelector};
 
 use super::global::is_relative_global;
 
@@ -70,6 +70,13 @@ pub(super) fn insert_complex_analysis(
         });
 }
 
+pub(super) fn mark_unscoped(relative_selector: &RelativeSelector<'_>, tables: &mut AnalysisTables) {
+    let key = SpanKey::new(AnalysisNodeKind::RelativeSelector, relative_selector.span);
+    if let Some(analysis) = tables.relative_selectors.get_mut(&key) {
+        analysis.scoped = false;
+    }
+}
+
 fn mark_nested_complex_selectors_used(
     relative_selector: &RelativeSelector<'_>,
     tables: &mut AnalysisTables,
@@ -86,11 +93,16 @@ fn mark_nested_complex_selectors_used_in_selector(
     let SimpleSelector::PseudoClassSelector(pseudo_class) = selector else {
         return;
     };
-    let Some(args) = &pseudo_class.args else {
-        return;
-    };
+    if let Some(args) = &pseudo_class.args {
+        mark_selector_list_used(args, tables);
+    }
+}
 
-    for complex_selector in &args.children {
+pub(crate) fn mark_selector_list_used(
+    selector_list: &SelectorList<'_>,
+    tables: &mut AnalysisTables,
+) {
+    for complex_selector in &selector_list.children {
         let key = SpanKey::new(AnalysisNodeKind::ComplexSelector, complex_selector.span);
         tables.complex_selectors.entry(key).or_default().used = true;
 
@@ -0,0 +1,193 @@
+use lux_ast::template::attribute::{AttributeNode, AttributeValue};
+use lux_ast::template::tag::TextOrExpressionTag;
+use oxc_ast::ast::{
+    ArrayExpressionElement, Expression, LogicalOperator, ObjectPropertyKind, PropertyKey,
+};
+
+/// Values an attribute of a template element may take at runtime, as far as
+/// selector matching is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PossibleValues<'t> {
+    /// The value comes from an expression we cannot follow, or from a spread.
+    Any,
+    Known(Vec<&'t str>),
+}
+
+impl<'t> PossibleValues<'t> {
+    pub fn contains(&self, value: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Known(values) => values.contains(&value),
+        }
+    }
+
+    fn none() -> Self {
+        Self::Known(Vec::new())
+    }
+
+    fn union(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Known(mut values), Self::Known(other)) => {
+                for value in other {
+                    if !values.contains(&value) {
+                        values.push(value);
+                    }
+                }
+                Self::Known(values)
+            }
+            _ => Self::Any,
+        }
+    }
+}
+
+/// Class names an element may end up with: `class` attribute tokens
+/// (including those of `clsx`-style expressions), `class:` directives and
+/// anything at all when a spread is present.
+pub fn possible_class_names<'t>(attributes: &'t [AttributeNode<'_>]) -> PossibleValues<'t> {
+    attributes.iter().fold(
+        PossibleValues::none(),
+        |classes, attribute| match attribute {
+            AttributeNode::Attribute(attribute) if attribute.name == "class" => {
+                classes.union(possible_tokens(&attribute.value))
+            }
+            AttributeNode::ClassDirective(directive) => {
+                classes.union(PossibleValues::Known(vec![directive.name]))
+            }
+            AttributeNode::SpreadAttribute(_) => PossibleValues::Any,
+            _ => classes,
+        },
+    )
+}
+
+/// Possible values of the named attribute, or `None` when the element
+/// certainly does not have it.
+pub fn possible_attribute_values<'t>(
+    attributes: &'t [AttributeNode<'_>],
+    name: &str,
+) -> Option<PossibleValues<'t>> {
+    let mut values = None;
+    for attribute in attributes {
+        match attribute {
+            AttributeNode::Attribute(attribute) if attribute.name == name => {
+                values = Some(possible_value(&attribute.value));
+            }
+            AttributeNode::SpreadAttribute(_) => values = Some(PossibleValues::Any),
+            _ => {}
+        }
+    }
+    values
+}
+
+fn possible_value<'t>(value: &'t AttributeValue<'_>) -> PossibleValues<'t> {
+    match value {
+        AttributeValue::True => PossibleValues::Known(vec![""]),
+        AttributeValue::ExpressionTag(_) => PossibleValues::Any,
+        AttributeValue::Sequence(chunks) => match chunks.as_slice() {
+            [TextOrExpressionTag::Text(text)] => PossibleValues::Known(vec![text.raw]),
+            _ => PossibleValues::Any,
+        },
+    }
+}
+
+fn possible_tokens<'t>(value: &'t AttributeValue<'_>) -> PossibleValues<'t> {
+    match value {
+        AttributeValue::True => PossibleValues::none(),
+        AttributeValue::ExpressionTag(tag) => expression_class_names(&tag.expression),
+        AttributeValue::Sequence(chunks) => sequence_tokens(chunks),
+    }
+}
+
+/// Tokens of `class="a {b} c"`. An expression glued to surrounding text
+/// (`btn-{size}`) could produce any class name.
+fn sequence_tokens<'t>(chunks: &'t [TextOrExpressionTag<'_>]) -> PossibleValues<'t> {
+    let mut tokens = PossibleValues::none();
+    for (index, chunk) in chunks.iter().enumerate() {
+        match chunk {
+            TextOrExpressionTag::Text(text) => {
+                tokens = tokens.union(PossibleValues::Known(
+                    text.raw.split_ascii_whitespace().collect(),
+                ));
+            }
+            TextOrExpressionTag::ExpressionTag(tag) => {
+                let separated_before = index == 0
+                    || matches!(
+                        &chunks[index - 1],
+                        TextOrExpressionTag::Text(text) if text.raw.ends_with(|c: char| c.is_ascii_whitespace())
+                    );
+                let separated_after = index + 1 == chunks.len()
+                    || matches!(
+                        &chunks[index + 1],
+                        TextOrExpressionTag::Text(text) if text.raw.starts_with(|c: char| c.is_ascii_whitespace())
+                    );
+                if !separated_before || !separated_after {
+                    return PossibleValues::Any;
+                }
+                tokens = tokens.union(expression_class_names(&tag.expression));
+            }
+        }
+    }
+    tokens
+}
+
+/// Class names produced by an expression, following the shapes Svelte's
+/// `clsx` handling accepts: strings, conditionals, logical operators,
+/// arrays and objects with static keys.
+fn expression_class_names<'t>(expression: &'t Expression<'_>) -> PossibleValues<'t> {
+    match expression {
+        Expression::StringLiteral(literal) => {
+            PossibleValues::Known(literal.value.as_str().split_ascii_whitespace().collect())
+        }
+        Expression::TemplateLiteral(template) if template.expressions.is_empty() => template
+            .quasis
+            .first()
+            .map_or_else(PossibleValues::none, |quasi| {
+                PossibleValues::Known(quasi.value.raw.as_str().split_ascii_whitespace().collect())
+            }),
+        Expression::NullLiteral(_) | Expression::BooleanLiteral(_) => PossibleValues::none(),
+        Expression::Identifier(identifier) if identifier.name == "undefined" => {
+            PossibleValues::none()
+        }
+        Expression::ParenthesizedExpression(parenthesized) => {
+            expression_class_names(&parenthesized.expression)
+        }
+        Expression::ConditionalExpression(conditional) => {
+            expression_class_names(&conditional.consequent)
+                .union(expression_class_names(&conditional.alternate))
+        }
+        Expression::LogicalExpression(logical) => match logical.operator {
+            // A falsy left operand renders no class name.
+            LogicalOperator::And => expression_class_names(&logical.right),
+            LogicalOperator::Or | LogicalOperator::Coalesce => {
+                expression_class_names(&logical.left).union(expression_class_names(&logical.right))
+            }
+        },
+        Expression::ArrayExpression(array) => {
+            array
+                .elements
+                .iter()
+                .fold(PossibleValues::none(), |names, element| match element {
+                    ArrayExpressionElement::SpreadElement(_) => PossibleValues::Any,
+                    ArrayExpressionElement::Elision(_) => names,
+                    _ => names.union(expression_class_names(element.to_expression())),
+                })
+        }
+        Expression::ObjectExpression(object) => object.properties.iter().fold(
+            PossibleValues::none(),
+            |names, property| match property {
+                ObjectPropertyKind::ObjectProperty(property) if !property.computed => {
+                    match &property.key {
+                        PropertyKey::StaticIdentifier(identifier) => {
+                            names.union(PossibleValues::Known(vec![identifier.name.as_str()]))
+                        }
+                        PropertyKey::StringLiteral(literal) => names.union(PossibleValues::Known(
+                            literal.value.as_str().split_ascii_whitespace().collect(),
+                        )),
+                        _ => PossibleValues::Any,
+                    }
+                }
+                _ => PossibleValues::Any,
+            },
+        ),
+        _ => PossibleValues::Any,
+    }
+}
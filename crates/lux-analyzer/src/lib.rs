@@ -5,6 +5,8 @@ mod template;
 use lux_ast::analysis::AnalysisTables;
 use lux_ast::template::root::Root;
 
+pub use css::{PossibleValues, possible_attribute_values, possible_class_names};
+
 pub fn analyze(root: &Root) -> AnalysisTables {
     let mut tables = AnalysisTables::default();
 
@@ -12,7 +14,7 @@ pub fn analyze(root: &Root) -> AnalysisTables {
     template::analyze_template(root, &mut tables);
 
     if let Some(stylesheet) = &root.css {
-        css::analyze_stylesheet(stylesheet, &mut tables);
+        css::analyze_stylesheet(stylesheet, &root.fragment, &mut tables);
     }
 
     tables
@@ -254,7 +254,7 @@ fn add_rune_placement_diagnostics(
                 allowed_props_spans.contains(&(rune.span.start, rune.span.end))
             }
             "$bindable" => allowed_bindable_spans.contains(&(rune.span.start, rune.span.end)),
-            "$effect" | "$effect.pre" | "$effect.root" => {
+            "$effect" | "$effect.pre" => {
                 allowed_effect_spans.contains(&(rune.span.start, rune.span.end))
             }
             "$host" => target == ScriptTarget::Instance && is_custom_element,
@@ -935,6 +935,25 @@ impl<'a> Visit<'a> for DollarBindingDiagnosticCollector {
     }
 }
 
+impl InspectTraceDiagnosticCollector {
+    /// Statements of a function passed as an argument, like the callback of
+    /// `$effect(...)`, are not part of the enclosing expression statement.
+    fn enter_function_body(&mut self) -> (Option<InspectTraceExpressionStatementContext>, usize) {
+        (
+            self.current_expression_statement.take(),
+            std::mem::take(&mut self.expression_depth),
+        )
+    }
+
+    fn leave_function_body(
+        &mut self,
+        (statement, depth): (Option<InspectTraceExpressionStatementContext>, usize),
+    ) {
+        self.current_expression_statement = statement;
+        self.expression_depth = depth;
+    }
+}
+
 impl<'a> Visit<'a> for InspectTraceDiagnosticCollector {
     fn visit_function(&mut self, function: &Function<'a>, flags: ScopeFlags) {
         let first_statement_span = function
@@ -946,7 +965,9 @@ impl<'a> Visit<'a> for InspectTraceDiagnosticCollector {
             generator: function.generator,
             first_statement_span,
         });
+        let outer = self.enter_function_body();
         walk::walk_function(self, function, flags);
+        self.leave_function_body(outer);
         self.function_stack.pop();
     }
 
@@ -956,7 +977,9 @@ impl<'a> Visit<'a> for InspectTraceDiagnosticCollector {
             generator: false,
             first_statement_span,
         });
+        let outer = self.enter_function_body();
         walk::walk_arrow_function_expression(self, expression);
+        self.leave_function_body(outer);
         self.function_stack.pop();
     }
 
@@ -1055,7 +1078,7 @@ fn rune_invalid_placement_message(rune_name: &str) -> String {
         "$bindable" => {
             "`$bindable()` can only be used inside a top-level `$props()` declaration.".to_string()
         }
-        "$effect" | "$effect.pre" | "$effect.root" => {
+        "$effect" | "$effect.pre" => {
             format!("`{rune_name}()` can only be used as an expression statement.")
         }
         "$host" => {
@@ -1705,7 +1728,7 @@ impl<'a> Visit<'a> for EffectExpressionStatementCollector {
             && let Expression::CallExpression(call) =
                 strip_typescript_expression_wrappers(&expression_statement.expression)
             && let Some(name) = extract_rune_name(&call.callee)
-            && matches!(name.as_str(), "$effect" | "$effect.pre" | "$effect.root")
+            && matches!(name.as_str(), "$effect" | "$effect.pre")
         {
             self.spans.insert((call.span.start, call.span.end));
         }
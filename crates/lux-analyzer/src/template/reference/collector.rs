@@ -99,7 +99,9 @@ impl<'a> Visit<'a> for ExpressionReferenceCollector<'_, '_> {
 
     fn visit_call_expression(&mut self, it: &CallExpression<'a>) {
         if let Some(name) = extract_rune_name(&it.callee) {
-            if is_rune(&name) {
+            // The template is tracked and renders inside its boundary, so
+            // these two may be asked from it.
+            if is_rune(&name) && !matches!(name.as_str(), "$effect.tracking" | "$effect.pending") {
                 self.context.add_diagnostic(
                     AnalysisSeverity::Error,
                     AnalysisDiagnosticCode::TemplateRuneInvalidPlacement,
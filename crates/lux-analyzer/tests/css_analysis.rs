@@ -1,5 +1,5 @@
 use lux_analyzer::analyze;
-use lux_ast::analysis::{AnalysisNodeKind, SpanKey};
+use lux_ast::analysis::{AnalysisDiagnosticCode, AnalysisNodeKind, AnalysisSeverity, SpanKey};
 use lux_ast::css::selector::SimpleSelector;
 use lux_ast::css::stylesheet::StyleSheetChild;
 use lux_parser::parse;
@@ -169,6 +169,58 @@ fn analyze_marks_nested_not_selector_as_used_under_root() {
     assert!(nested_complex_analysis.used);
 }
 
+#[test]
+fn analyze_leaves_simple_not_arguments_unscoped() {
+    let allocator = Allocator::default();
+    let result = parse(
+        "<style>p:not(.x):not(.y .z) { color: red; }</style>",
+        &allocator,
+        false,
+    );
+    assert!(result.errors.is_empty());
+
+    let tables = analyze(&result.root);
+    let css = result.root.css.as_ref().expect("expected stylesheet");
+    let StyleSheetChild::Rule(rule) = &css.children[0] else {
+        panic!("expected rule");
+    };
+    let relative = &rule.prelude.children[0].children[0];
+    let not_arguments = relative
+        .selectors
+        .iter()
+        .filter_map(|selector| match selector {
+            SimpleSelector::PseudoClassSelector(pseudo_class) if pseudo_class.name == "not" => {
+                pseudo_class.args.as_ref()
+            }
+            _ => None,
+        })
+        .map(|args| &args.children[0])
+        .collect::<Vec<_>>();
+    assert_eq!(not_arguments.len(), 2);
+
+    for (complex, expect_scoped) in not_arguments.into_iter().zip([false, true]) {
+        let complex_analysis = tables
+            .complex_selectors
+            .get(&SpanKey::new(
+                AnalysisNodeKind::ComplexSelector,
+                complex.span,
+            ))
+            .expect("expected nested complex selector analysis");
+        assert!(complex_analysis.used);
+
+        for nested_relative in &complex.children {
+            let relative_analysis = tables
+                .relative_selectors
+                .get(&SpanKey::new(
+                    AnalysisNodeKind::RelativeSelector,
+                    nested_relative.span,
+                ))
+                .expect("expected nested relative analysis");
+            assert_eq!(relative_analysis.scoped, expect_scoped);
+        }
+    }
+}
+
 #[test]
 fn analyze_treats_global_has_selector_as_scoped() {
     let allocator = Allocator::default();
@@ -249,3 +301,123 @@ fn analyze_links_nested_rule_to_parent_rule() {
         .expect("expected nested rule analysis");
     assert_eq!(inner_analysis.parent_rule, Some(outer_key));
 }
+
+fn analyze_css_diagnostics(source: &str) -> Vec<(AnalysisDiagnosticCode, String)> {
+    let allocator = Allocator::default();
+    let result = parse(source, &allocator, false);
+    assert!(result.errors.is_empty());
+
+    let tables = analyze(&result.root);
+    tables
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == AnalysisSeverity::Error)
+        .map(|diagnostic| {
+            let span = diagnostic.span;
+            (
+                diagnostic.code,
+                source[span.start as usize..span.end as usize].to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn analyze_reports_invalid_global_selector_usage() {
+    let cases = [
+        (
+            "<style>.a :global(.b) .c { color: red; }</style>",
+            AnalysisDiagnosticCode::CssGlobalInvalidPlacement,
+            ":global(.b)",
+        ),
+        (
+            "<style>.a:global(div) { color: red; }</style>",
+            AnalysisDiagnosticCode::CssGlobalInvalidSelectorList,
+            ":global(div)",
+        ),
+        (
+            "<style>:global(.a)div { color: red; }</style>",
+            AnalysisDiagnosticCode::CssTypeSelectorInvalidPlacement,
+            "div",
+        ),
+        (
+            "<style>.a > :global .b { color: red; }</style>",
+            AnalysisDiagnosticCode::CssGlobalBlockInvalidCombinator,
+            "> :global",
+        ),
+        (
+            "<style>:global { color: red; }</style>",
+            AnalysisDiagnosticCode::CssGlobalBlockInvalidDeclaration,
+            "color: red",
+        ),
+        (
+            "<style>:global .a, .b { color: red; }</style>",
+            AnalysisDiagnosticCode::CssGlobalBlockInvalidList,
+            ":global .a, .b",
+        ),
+    ];
+
+    for (source, code, snippet) in cases {
+        let diagnostics = analyze_css_diagnostics(source);
+        assert!(
+            diagnostics
+                .iter()
+                .any(|(actual, text)| *actual == code && text.trim() == snippet),
+            "{source}: {diagnostics:?}"
+        );
+    }
+}
+
+#[test]
+fn analyze_accepts_valid_global_selector_usage() {
+    for source in [
+        "<style>.a :global(.b) :global(.c) { color: red; }</style>",
+        "<style>:global(div).a { color: red; }</style>",
+        "<style>.a :global .b { color: red; }</style>",
+        "<style>:global { .a { color: red; } }</style>",
+        "<style>:global(.a, .b) { color: red; }</style>",
+    ] {
+        let diagnostics = analyze_css_diagnostics(source);
+        assert!(diagnostics.is_empty(), "{source}: {diagnostics:?}");
+    }
+}
+
+#[test]
+fn analyze_warns_about_unused_selectors() {
+    let allocator = Allocator::default();
+    let source = "<div class=\"a\"><p></p></div><style>.a p, .b { color: red; } .a .c { color: blue; } div:is(.a, .d) { color: green; } @keyframes fade { from { opacity: 0; } } :global(.e) { color: gray; }</style>";
+    let result = parse(source, &allocator, false);
+    assert!(result.errors.is_empty());
+
+    let tables = analyze(&result.root);
+    let warnings = tables
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == AnalysisSeverity::Warning)
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.code, AnalysisDiagnosticCode::CssUnusedSelector);
+            diagnostic.message.as_str()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        warnings,
+        [
+            "Unused CSS selector \".b\"",
+            "Unused CSS selector \".a .c\"",
+            "Unused CSS selector \".d\"",
+        ]
+    );
+
+    let css = result.root.css.as_ref().expect("expected stylesheet");
+    let StyleSheetChild::Rule(rule) = &css.children[0] else {
+        panic!("expected rule");
+    };
+    let used = tables
+        .complex_selectors
+        .get(&SpanKey::new(
+            AnalysisNodeKind::ComplexSelector,
+            rule.prelude.children[0].span,
+        ))
+        .expect("expected complex selector analysis");
+    assert!(used.used);
+}
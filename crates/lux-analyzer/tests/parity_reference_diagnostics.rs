@@ -122,6 +122,13 @@ fn parity_against_reference_analyzer_diagnostics_smoke() {
             lux_code: AnalysisDiagnosticCode::BlockEmpty,
             severity: AnalysisSeverity::Warning,
         },
+        ParityCase {
+            name: "css_unused_selector",
+            source: "<div></div><style>.missing { color: red; }</style>",
+            reference_code: "css_unused_selector",
+            lux_code: AnalysisDiagnosticCode::CssUnusedSelector,
+            severity: AnalysisSeverity::Warning,
+        },
         ParityCase {
             name: "state_invalid_placement",
             source: "{$state(1)}",
@@ -311,6 +318,20 @@ fn parity_against_reference_analyzer_diagnostics_smoke() {
             lux_code: AnalysisDiagnosticCode::SvelteHeadIllegalAttribute,
             severity: AnalysisSeverity::Error,
         },
+        ParityCase {
+            name: "css_global_invalid_placement",
+            source: "<style>.a :global(.b) .c { color: red; }</style>",
+            reference_code: "css_global_invalid_placement",
+            lux_code: AnalysisDiagnosticCode::CssGlobalInvalidPlacement,
+            severity: AnalysisSeverity::Error,
+        },
+        ParityCase {
+            name: "css_global_block_invalid_declaration",
+            source: "<style>:global { color: red; }</style>",
+            reference_code: "css_global_block_invalid_declaration",
+            lux_code: AnalysisDiagnosticCode::CssGlobalBlockInvalidDeclaration,
+            severity: AnalysisSeverity::Error,
+        },
     ];
 
     for case in cases {
@@ -648,6 +669,30 @@ fn map_reference_error_code_to_lux(
             AnalysisDiagnosticCode::ScriptRuneInvalidArguments,
             AnalysisSeverity::Error,
         )),
+        "css_global_invalid_placement" => Some((
+            AnalysisDiagnosticCode::CssGlobalInvalidPlacement,
+            AnalysisSeverity::Error,
+        )),
+        "css_global_invalid_selector_list" => Some((
+            AnalysisDiagnosticCode::CssGlobalInvalidSelectorList,
+            AnalysisSeverity::Error,
+        )),
+        "css_global_block_invalid_combinator" => Some((
+            AnalysisDiagnosticCode::CssGlobalBlockInvalidCombinator,
+            AnalysisSeverity::Error,
+        )),
+        "css_global_block_invalid_declaration" => Some((
+            AnalysisDiagnosticCode::CssGlobalBlockInvalidDeclaration,
+            AnalysisSeverity::Error,
+        )),
+        "css_global_block_invalid_list" => Some((
+            AnalysisDiagnosticCode::CssGlobalBlockInvalidList,
+            AnalysisSeverity::Error,
+        )),
+        "css_type_selector_invalid_placement" => Some((
+            AnalysisDiagnosticCode::CssTypeSelectorInvalidPlacement,
+            AnalysisSeverity::Error,
+        )),
         _ => None,
     }
 }
@@ -248,6 +248,32 @@ fn analyze_reports_props_bindable_and_effect_placement() {
     );
 }
 
+#[test]
+fn analyze_accepts_inspect_trace_in_rune_callbacks() {
+    let source = r#"
+<script>
+  let count = $state(0);
+  const doubled = $derived.by(() => {
+    $inspect.trace();
+    return count * 2;
+  });
+  $effect(() => {
+    $inspect.trace("effect");
+    console.log(doubled);
+  });
+</script>
+"#;
+
+    let tables = analyze_source(source);
+    assert!(
+        !tables.diagnostics.iter().any(|diagnostic| {
+            diagnostic.code == AnalysisDiagnosticCode::InspectTraceInvalidPlacement
+        }),
+        "{:?}",
+        tables.diagnostics
+    );
+}
+
 #[test]
 fn analyze_reports_props_rune_invalid_placement_in_module_script() {
     let source = r#"
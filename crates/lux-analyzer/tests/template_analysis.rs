@@ -475,6 +475,15 @@ fn analyze_reports_template_rune_invalid_placement() {
     }));
 }
 
+#[test]
+fn analyze_allows_effect_tracking_and_pending_in_template() {
+    let tables = analyze_source("<p>{$effect.tracking()} {$effect.pending()}</p>");
+
+    assert!(!tables.diagnostics.iter().any(|diagnostic| {
+        diagnostic.code == AnalysisDiagnosticCode::TemplateRuneInvalidPlacement
+    }));
+}
+
 #[test]
 fn analyze_reports_svelte_meta_invalid_content() {
     let tables = analyze_source("<svelte:window><div /></svelte:window>");
@@ -90,6 +90,13 @@ pub enum AnalysisDiagnosticCode {
     SvelteMetaInvalidPlacement,
     SvelteMetaInvalidContent,
     SvelteMetaDuplicate,
+    CssGlobalInvalidPlacement,
+    CssGlobalInvalidSelectorList,
+    CssGlobalBlockInvalidCombinator,
+    CssGlobalBlockInvalidDeclaration,
+    CssGlobalBlockInvalidList,
+    CssTypeSelectorInvalidPlacement,
+    CssUnusedSelector,
 }
 
 #[derive(Debug, Clone)]
@@ -1,4 +1,5 @@
 pub mod selector;
 pub mod stylesheet;
+pub mod value;
 
 pub use stylesheet::StyleSheet;
@@ -1,5 +1,6 @@
 use crate::common::Span;
 use crate::css::selector::SelectorList;
+use crate::css::value::CssValue;
 use crate::template::attribute::Attribute;
 use crate::template::tag::Comment;
 
@@ -32,6 +33,7 @@ pub struct CssAtrule<'a> {
     pub span: Span,
     pub name: &'a str,
     pub prelude: &'a str,
+    pub parsed_prelude: CssValue<'a>,
     pub block: Option<CssBlock<'a>>,
 }
 
@@ -53,4 +55,5 @@ pub struct CssDeclaration<'a> {
     pub span: Span,
     pub property: &'a str,
     pub value: &'a str,
+    pub parsed_value: CssValue<'a>,
 }
@@ -0,0 +1,91 @@
+use crate::common::Span;
+
+/// Component values of a declaration value or an at-rule prelude, with
+/// whitespace and comments dropped. Every node keeps the span of its exact
+/// source text.
+#[derive(Debug)]
+pub struct CssValue<'a> {
+    pub span: Span,
+    pub children: Vec<CssComponentValue<'a>>,
+}
+
+#[derive(Debug)]
+pub enum CssComponentValue<'a> {
+    Ident(CssIdent<'a>),
+    Function(CssFunction<'a>),
+    String(CssString<'a>),
+    Number(CssNumber<'a>),
+    Url(CssUrl<'a>),
+    Hash(CssHash<'a>),
+    Block(CssSimpleBlock<'a>),
+    Comma(CssComma),
+    Delimiter(CssDelimiter<'a>),
+}
+
+#[derive(Debug)]
+pub struct CssIdent<'a> {
+    pub span: Span,
+    pub name: &'a str,
+}
+
+/// A function call such as `calc(...)` or `var(...)`; `url(...)` is a
+/// [`CssUrl`] instead.
+#[derive(Debug)]
+pub struct CssFunction<'a> {
+    pub span: Span,
+    pub name: &'a str,
+    pub children: Vec<CssComponentValue<'a>>,
+}
+
+#[derive(Debug)]
+pub struct CssString<'a> {
+    pub span: Span,
+    pub quote: char,
+    /// Text between the quotes, with escapes left as written.
+    pub value: &'a str,
+}
+
+#[derive(Debug)]
+pub struct CssNumber<'a> {
+    pub span: Span,
+    pub value: &'a str,
+    /// `%` or a dimension unit such as `px`.
+    pub unit: Option<&'a str>,
+}
+
+/// A quoted or unquoted `url(...)`.
+#[derive(Debug)]
+pub struct CssUrl<'a> {
+    pub span: Span,
+    pub value: &'a str,
+    /// Span of `value`, excluding quotes and surrounding whitespace.
+    pub value_span: Span,
+    pub quote: Option<char>,
+}
+
+#[derive(Debug)]
+pub struct CssHash<'a> {
+    pub span: Span,
+    /// Text after the `#`.
+    pub value: &'a str,
+}
+
+/// A `(...)` or `[...]` block, as in media features or grid line names.
+#[derive(Debug)]
+pub struct CssSimpleBlock<'a> {
+    pub span: Span,
+    pub open: char,
+    pub children: Vec<CssComponentValue<'a>>,
+}
+
+#[derive(Debug)]
+pub struct CssComma {
+    pub span: Span,
+}
+
+/// Any other single character, such as `/`, `+`, `*`, `!` or `:`.
+#[derive(Debug)]
+pub struct CssDelimiter<'a> {
+    pub span: Span,
+    pub value: &'a str,
+}
@@ -93,8 +93,9 @@ pub struct SvelteOptions<'a> {
     pub attributes: Vec<Attribute<'a>>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Namespace {
+    #[default]
     Html,
     Svg,
     Mathml,
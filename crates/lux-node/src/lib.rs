@@ -1,17 +1,23 @@
+use std::cell::RefCell;
 use std::path::Path;
 
 use lux_ast::analysis::{AnalysisSeverity, ScriptRuneKind};
 use lux_ast::common::Span;
 use lux_ast::template::root::{CssOption, CustomElementOptions, Namespace, Root, SvelteOptions};
-use lux_transformer::TransformTarget;
-use napi::{Error, Result, Status};
+use lux_transformer::{
+    CssAssetKind, CssAssetReference, CssHash, CssHashCallback, CssHashInput, CssUrlResolver,
+    TransformOptions, TransformTarget,
+};
+use lux_utils::hash::hash;
+use napi::bindgen_prelude::{Either, Function};
+use napi::{Env, Error, Result, Status};
 use napi_derive::napi;
 use oxc_allocator::Allocator;
 use serde_json::json;
 
-#[napi(object)]
+#[napi(object, object_to_js = false)]
 #[derive(Default)]
-pub struct CompileOptions {
+pub struct CompileOptions<'a> {
     pub ts: Option<bool>,
     pub generate: Option<String>,
     pub filename: Option<String>,
@@ -25,6 +31,25 @@ pub struct CompileOptions {
     pub output_filename: Option<String>,
     pub css_output_filename: Option<String>,
     pub modern_ast: Option<bool>,
+    pub streaming: Option<bool>,
+    pub minify_css: Option<bool>,
+    pub lower_css_nesting: Option<bool>,
+    /// Keeps `$inspect` and `$inspect.trace` in client output.
+    pub dev: Option<bool>,
+    /// Called with each `url()` and `@import` of the component CSS; a
+    /// returned string replaces the reference.
+    pub css_url_resolver: Option<Function<'a, CssAsset, Option<String>>>,
+    /// Names the CSS scope class, either with a template such as
+    /// `"[name]-[hash]"` or with a function like Svelte's `cssHash`.
+    pub css_hash: Option<Either<String, Function<'a, CssHashArgs<'a>, String>>>,
+    pub experimental: Option<ExperimentalOptions>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct ExperimentalOptions {
+    #[napi(js_name = "async")]
+    pub async_: Option<bool>,
 }
 
 #[napi(object)]
@@ -37,6 +62,24 @@ pub struct Diagnostic {
     pub end: u32,
 }
 
+#[napi(object)]
+#[derive(Clone)]
+pub struct CssAsset {
+    pub kind: String,
+    pub value: String,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[napi(object, object_from_js = false)]
+pub struct CssHashArgs<'a> {
+    /// The hash function used for the default `svelte-[hash]` class.
+    pub hash: Function<'a, String, String>,
+    pub css: String,
+    pub name: String,
+    pub filename: Option<String>,
+}
+
 #[napi(object)]
 pub struct RuntimeModule {
     pub specifier: String,
@@ -51,6 +94,7 @@ pub struct CompileOutput {
     pub css_map: Option<String>,
     pub css_hash: Option<String>,
     pub css_scope: Option<String>,
+    pub css_assets: Vec<CssAsset>,
     pub runtime_modules: Vec<RuntimeModule>,
     pub errors: Vec<Diagnostic>,
     pub warnings: Vec<Diagnostic>,
@@ -60,13 +104,17 @@ pub struct CompileOutput {
 }
 
 #[napi(js_name = "compile")]
-pub fn compile_js(source: String, options: Option<CompileOptions>) -> CompileOutput {
-    compile_internal(&source, options.as_ref())
+pub fn compile_js(env: Env, source: String, options: Option<CompileOptions>) -> CompileOutput {
+    compile_with_js_callbacks(&env, &source, options.as_ref())
 }
 
 #[napi(js_name = "compileStrict")]
-pub fn compile_strict_js(source: String, options: Option<CompileOptions>) -> Result<CompileOutput> {
-    let output = compile_internal(&source, options.as_ref());
+pub fn compile_strict_js(
+    env: Env,
+    source: String,
+    options: Option<CompileOptions>,
+) -> Result<CompileOutput> {
+    let output = compile_with_js_callbacks(&env, &source, options.as_ref());
     if output.errors.is_empty() {
         return Ok(output);
     }
@@ -81,7 +129,84 @@ pub fn compile_strict_js(source: String, options: Option<CompileOptions>) -> Res
     ))
 }
 
-fn compile_internal(source: &str, options: Option<&CompileOptions>) -> CompileOutput {
+/// Compiles with the JavaScript callbacks of the options, whose failures are
+/// reported as errors of the output.
+fn compile_with_js_callbacks(
+    env: &Env,
+    source: &str,
+    options: Option<&CompileOptions>,
+) -> CompileOutput {
+    let callback_errors = RefCell::new(Vec::new());
+    let report_error = |code: &str, error: Error, span: Span| {
+        callback_errors.borrow_mut().push(Diagnostic {
+            phase: "transform".to_string(),
+            severity: "error".to_string(),
+            code: Some(code.to_string()),
+            message: error.reason.clone(),
+            start: span.start,
+            end: span.end,
+        });
+    };
+
+    let css_url_resolver = options.and_then(|o| o.css_url_resolver.as_ref());
+    let resolve_css_url = |reference: &CssAssetReference| {
+        let resolver = css_url_resolver?;
+        resolver.call(css_asset(reference)).unwrap_or_else(|error| {
+            report_error("css_url_resolver_failed", error, reference.span);
+            None
+        })
+    };
+
+    let css_hash = match options.and_then(|o| o.css_hash.as_ref()) {
+        Some(Either::B(css_hash)) => Some(css_hash),
+        _ => None,
+    };
+    let name_css_scope = |input: &CssHashInput<'_>| {
+        let default_scope = || format!("svelte-{}", input.default_hash);
+        let Some(css_hash) = css_hash else {
+            return default_scope();
+        };
+        env.create_function_from_closure("hash", |ctx| {
+            ctx.get::<String>(0).map(|input| hash(&input))
+        })
+        .and_then(|hash| {
+            css_hash.call(CssHashArgs {
+                hash,
+                css: input.css.to_string(),
+                name: input.name.to_string(),
+                filename: input.filename.map(ToString::to_string),
+            })
+        })
+        .unwrap_or_else(|error| {
+            report_error("css_hash_failed", error, Span::new(0, 0));
+            default_scope()
+        })
+    };
+
+    let mut output = compile_internal(
+        source,
+        options,
+        CompileCallbacks {
+            css_url_resolver: css_url_resolver.map(|_| CssUrlResolver(&resolve_css_url)),
+            css_hash: css_hash.map(|_| CssHashCallback(&name_css_scope)),
+        },
+    );
+    output.errors.extend(callback_errors.into_inner());
+    output
+}
+
+/// Rust implementations of the JavaScript callbacks of `CompileOptions`.
+#[derive(Default)]
+struct CompileCallbacks<'c> {
+    css_url_resolver: Option<CssUrlResolver<'c>>,
+    css_hash: Option<CssHashCallback<'c>>,
+}
+
+fn compile_internal(
+    source: &str,
+    options: Option<&CompileOptions>,
+    callbacks: CompileCallbacks<'_>,
+) -> CompileOutput {
     let generate_target = options
         .and_then(|o| o.generate.as_deref())
         .unwrap_or("server");
@@ -163,6 +288,7 @@ fn compile_internal(source: &str, options: Option<&CompileOptions>) -> CompileOu
                 css_map: None,
                 css_hash: None,
                 css_scope: None,
+                css_assets: Vec::new(),
                 runtime_modules: Vec::new(),
                 errors,
                 warnings,
@@ -174,11 +300,27 @@ fn compile_internal(source: &str, options: Option<&CompileOptions>) -> CompileOu
     };
 
     let source_filename = options.and_then(|o| o.filename.as_deref());
-    let transform = lux_transformer::transform_for_target_with_filename(
+    let experimental_async = options
+        .and_then(|o| o.experimental.as_ref())
+        .and_then(|experimental| experimental.async_)
+        .unwrap_or(false);
+    let transform = lux_transformer::transform_with_options(
         &parse_result.root,
         &analysis,
-        transform_target,
-        source_filename,
+        &TransformOptions {
+            target: transform_target,
+            filename: source_filename,
+            experimental_async,
+            streaming: options.and_then(|o| o.streaming).unwrap_or(false),
+            minify_css: options.and_then(|o| o.minify_css).unwrap_or(false),
+            lower_css_nesting: options.and_then(|o| o.lower_css_nesting).unwrap_or(false),
+            dev: options.and_then(|o| o.dev).unwrap_or(false),
+            css_url_resolver: callbacks.css_url_resolver,
+            css_hash: match options.and_then(|o| o.css_hash.as_ref()) {
+                Some(Either::A(template)) => Some(CssHash::Template(template)),
+                _ => callbacks.css_hash.map(CssHash::Callback),
+            },
+        },
     );
     let runtime_modules = transform
         .runtime_modules
@@ -210,6 +352,7 @@ fn compile_internal(source: &str, options: Option<&CompileOptions>) -> CompileOu
         css_map,
         css_hash: transform.css_hash,
         css_scope: transform.css_scope,
+        css_assets: transform.css_assets.iter().map(css_asset).collect(),
         runtime_modules,
         errors,
         warnings,
@@ -219,6 +362,19 @@ fn compile_internal(source: &str, options: Option<&CompileOptions>) -> CompileOu
     }
 }
 
+fn css_asset(reference: &CssAssetReference) -> CssAsset {
+    CssAsset {
+        kind: match reference.kind {
+            CssAssetKind::Url => "url",
+            CssAssetKind::Import => "import",
+        }
+        .to_string(),
+        value: reference.value.clone(),
+        start: reference.span.start,
+        end: reference.span.end,
+    }
+}
+
 fn apply_compile_options_to_root(root: &mut Root<'_>, options: Option<&CompileOptions>) {
     let Some(options) = options else {
         return;
@@ -417,11 +573,14 @@ fn fragment_node_kind(node: &lux_ast::template::root::FragmentNode<'_>) -> &'sta
 
 #[cfg(test)]
 mod tests {
-    use super::{CompileOptions, compile_internal};
+    use lux_transformer::{CssAssetReference, CssUrlResolver};
+    use napi::bindgen_prelude::Either;
+
+    use super::{CompileCallbacks, CompileOptions, ExperimentalOptions, compile_internal};
 
     #[test]
     fn compile_collects_parse_errors() {
-        let output = compile_internal("{#if x}<div>", None);
+        let output = compile_internal("{#if x}<div>", None, CompileCallbacks::default());
         assert!(!output.errors.is_empty());
         assert_eq!(output.errors[0].phase, "parse");
     }
@@ -435,6 +594,7 @@ mod tests {
                 generate: None,
                 ..CompileOptions::default()
             }),
+            CompileCallbacks::default(),
         );
         assert!(output.errors.is_empty());
         assert_eq!(output.runtime_modules.len(), 1);
@@ -450,6 +610,7 @@ mod tests {
                 generate: None,
                 ..CompileOptions::default()
             }),
+            CompileCallbacks::default(),
         );
         assert!(
             output.errors.is_empty(),
@@ -467,6 +628,7 @@ mod tests {
                 generate: Some("client".to_string()),
                 ..CompileOptions::default()
             }),
+            CompileCallbacks::default(),
         );
         assert!(output.errors.is_empty());
         assert!(
@@ -493,6 +655,7 @@ mod tests {
                 generate: Some("edge".to_string()),
                 ..CompileOptions::default()
             }),
+            CompileCallbacks::default(),
         );
         assert!(
             output
@@ -511,6 +674,7 @@ mod tests {
                 custom_element: Some(true),
                 ..CompileOptions::default()
             }),
+            CompileCallbacks::default(),
         );
         assert!(
             output
@@ -531,6 +695,7 @@ mod tests {
                 css_output_filename: Some("App.css".to_string()),
                 ..CompileOptions::default()
             }),
+            CompileCallbacks::default(),
         );
 
         let js_map = output.js_map.expect("expected js map");
@@ -540,4 +705,126 @@ mod tests {
         let css_map = output.css_map.expect("expected css map");
         assert!(css_map.contains("\"file\":\"App.css\""));
     }
+
+    #[test]
+    fn compile_forwards_experimental_async_option() {
+        let output = compile_internal(
+            "{#await value then resolved}<p>{resolved}</p>{/await}",
+            Some(&CompileOptions {
+                experimental: Some(ExperimentalOptions { async_: Some(true) }),
+                ..CompileOptions::default()
+            }),
+            CompileCallbacks::default(),
+        );
+
+        assert!(output.errors.is_empty());
+        assert!(output.js.contains("$$renderer.child(async ($$renderer) =>"));
+        assert!(output.js.contains("const __lux_await_value = await "));
+    }
+
+    #[test]
+    fn compile_keeps_inspect_in_dev_mode() {
+        let source = "<script>let count = $state(0); $inspect(count);</script><p>{count}</p>";
+        let dev = compile_internal(
+            source,
+            Some(&CompileOptions {
+                generate: Some("client".to_string()),
+                dev: Some(true),
+                ..CompileOptions::default()
+            }),
+            CompileCallbacks::default(),
+        );
+        assert!(dev.errors.is_empty());
+        assert!(
+            dev.js.contains("__lux_inspect(__lux_render_state, "),
+            "{}",
+            dev.js
+        );
+
+        let production = compile_internal(
+            source,
+            Some(&CompileOptions {
+                generate: Some("client".to_string()),
+                ..CompileOptions::default()
+            }),
+            CompileCallbacks::default(),
+        );
+        assert!(
+            !production.js.contains("__lux_inspect("),
+            "{}",
+            production.js
+        );
+    }
+
+    #[test]
+    fn compile_minifies_external_and_injected_css() {
+        let output = compile_internal(
+            "<svelte:options css=\"injected\" /><p>x</p><style>p { color: #FFFFFF; /* note */ margin: 0px  auto; } p { padding: 0; }</style>",
+            Some(&CompileOptions {
+                minify_css: Some(true),
+                ..CompileOptions::default()
+            }),
+            CompileCallbacks::default(),
+        );
+
+        assert!(output.errors.is_empty());
+        let css = output.css.expect("expected css output");
+        assert!(
+            css.ends_with("{color:#fff;margin:0 auto;padding:0;}"),
+            "{css}"
+        );
+        assert!(
+            output
+                .js
+                .contains("{color:#fff;margin:0 auto;padding:0;}</style>")
+        );
+    }
+
+    #[test]
+    fn compile_reports_and_resolves_css_assets() {
+        let resolve = |reference: &CssAssetReference| Some(format!("/static/{}", reference.value));
+        let output = compile_internal(
+            "<p>x</p><style>@import url(reset.css); p { background: url('bg.png'); }</style>",
+            None,
+            CompileCallbacks {
+                css_url_resolver: Some(CssUrlResolver(&resolve)),
+                ..CompileCallbacks::default()
+            },
+        );
+
+        assert!(output.errors.is_empty());
+        let assets = output
+            .css_assets
+            .iter()
+            .map(|asset| (asset.kind.as_str(), asset.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(assets, [("import", "reset.css"), ("url", "bg.png")]);
+        let css = output.css.expect("expected css output");
+        assert!(css.contains("@import url(\"/static/reset.css\");"), "{css}");
+        assert!(css.contains("background: url('/static/bg.png');"), "{css}");
+    }
+
+    #[test]
+    fn compile_names_css_scope_from_template() {
+        let output = compile_internal(
+            "<p>x</p><style>p { color: red; }</style>",
+            Some(&CompileOptions {
+                filename: Some("src/Card.svelte".to_string()),
+                css_hash: Some(Either::A("[name]-[hash]".to_string())),
+                ..CompileOptions::default()
+            }),
+            CompileCallbacks::default(),
+        );
+
+        assert!(output.errors.is_empty());
+        let scope = output.css_scope.expect("expected css scope");
+        let hash = output.css_hash.expect("expected css hash");
+        assert_eq!(scope, format!("Card-{hash}"));
+        assert!(
+            output
+                .css
+                .expect("expected css output")
+                .contains(&format!("p.{scope}"))
+        );
+    }
 }
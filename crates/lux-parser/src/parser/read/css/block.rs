@@ -63,9 +63,7 @@ fn parse_declaration<'a>(p: &mut CssParser<'a>) -> Result<CssDeclaration<'a>> {
 
     p.skip_whitespace();
     p.eat_required(":")?;
-    p.skip_whitespace();
-
-    let value = p.read_css_value();
+    let (value, parsed_value) = p.read_css_value_with_components();
 
     if value.is_empty() && !property.starts_with("--") {
         return Err(ContextError::new());
@@ -81,5 +79,6 @@ fn parse_declaration<'a>(p: &mut CssParser<'a>) -> Result<CssDeclaration<'a>> {
         span: p.span(start, end),
         property,
         value,
+        parsed_value,
     })
 }
@@ -1,6 +1,35 @@
+use lux_ast::common::Span;
+use lux_ast::css::value::{
+    CssComma, CssComponentValue, CssDelimiter, CssFunction, CssHash, CssIdent, CssNumber,
+    CssSimpleBlock, CssString, CssUrl, CssValue,
+};
+
 use super::CssParser;
 
 impl<'a> CssParser<'a> {
+    /// Reads a declaration value or at-rule prelude, returning the raw text
+    /// together with its component values.
+    pub fn read_css_value_with_components(&mut self) -> (&'a str, CssValue<'a>) {
+        self.skip_whitespace();
+        let start = self.index;
+        let raw = self.read_css_value();
+        let end = start + raw.len();
+
+        let mut tokenizer = ValueTokenizer {
+            parser: self,
+            index: start,
+            end,
+        };
+        let children = tokenizer.read_components(None);
+        (
+            raw,
+            CssValue {
+                span: self.span(start, end),
+                children,
+            },
+        )
+    }
+
     pub fn read_css_value(&mut self) -> &'a str {
         let start = self.index;
         let bytes = self.source.as_bytes();
@@ -86,3 +115,268 @@ impl<'a> CssParser<'a> {
         self.source[start..self.index].trim()
     }
 }
+
+struct ValueTokenizer<'p, 'a> {
+    parser: &'p CssParser<'a>,
+    index: usize,
+    end: usize,
+}
+
+impl<'a> ValueTokenizer<'_, 'a> {
+    fn read_components(&mut self, close: Option<u8>) -> Vec<CssComponentValue<'a>> {
+        let mut children = Vec::new();
+        loop {
+            self.skip_whitespace_and_comments();
+            let Some(b) = self.peek(0) else {
+                break;
+            };
+            if Some(b) == close {
+                break;
+            }
+
+            let start = self.index;
+            let child = match b {
+                b'"' | b'\'' => CssComponentValue::String(self.read_string()),
+                b'(' | b'[' => {
+                    self.index += 1;
+                    let children = self.read_components(Some(if b == b'(' { b')' } else { b']' }));
+                    self.index = (self.index + 1).min(self.end);
+                    CssComponentValue::Block(CssSimpleBlock {
+                        span: self.span(start),
+                        open: b as char,
+                        children,
+                    })
+                }
+                b',' => {
+                    self.index += 1;
+                    CssComponentValue::Comma(CssComma {
+                        span: self.span(start),
+                    })
+                }
+                b'#' if self.peek(1).is_some_and(|b| b == b'\\' || is_name_byte(b)) => {
+                    self.index += 1;
+                    let value = self.read_name();
+                    CssComponentValue::Hash(CssHash {
+                        span: self.span(start),
+                        value,
+                    })
+                }
+                _ if self.starts_number() => CssComponentValue::Number(self.read_number()),
+                _ if self.starts_identifier() => self.read_ident_like(),
+                _ => {
+                    let len = self.source()[self.index..]
+                        .chars()
+                        .next()
+                        .map_or(1, char::len_utf8);
+                    self.index += len;
+                    CssComponentValue::Delimiter(CssDelimiter {
+                        span: self.span(start),
+                        value: &self.source()[start..self.index],
+                    })
+                }
+            };
+            children.push(child);
+        }
+        children
+    }
+
+    fn read_string(&mut self) -> CssString<'a> {
+        let start = self.index;
+        let quote = self.source().as_bytes()[start];
+        self.index += 1;
+        let value_start = self.index;
+        while let Some(b) = self.peek(0) {
+            if b == b'\\' {
+                self.index = (self.index + 2).min(self.end);
+                continue;
+            }
+            if b == quote {
+                break;
+            }
+            self.index += 1;
+        }
+        let value = &self.source()[value_start..self.index];
+        self.index = (self.index + 1).min(self.end);
+        CssString {
+            span: self.span(start),
+            quote: quote as char,
+            value,
+        }
+    }
+
+    fn read_number(&mut self) -> CssNumber<'a> {
+        let start = self.index;
+        if matches!(self.peek(0), Some(b'+' | b'-')) {
+            self.index += 1;
+        }
+        self.skip_digits();
+        if self.peek(0) == Some(b'.') && self.peek(1).is_some_and(|b| b.is_ascii_digit()) {
+            self.index += 1;
+            self.skip_digits();
+        }
+        if matches!(self.peek(0), Some(b'e' | b'E')) {
+            let digit_offset = if matches!(self.peek(1), Some(b'+' | b'-')) {
+                2
+            } else {
+                1
+            };
+            if self.peek(digit_offset).is_some_and(|b| b.is_ascii_digit()) {
+                self.index += digit_offset;
+                self.skip_digits();
+            }
+        }
+        let value = &self.source()[start..self.index];
+
+        let unit = if self.peek(0) == Some(b'%') {
+            self.index += 1;
+            Some("%")
+        } else if self.starts_identifier() {
+            Some(self.read_name())
+        } else {
+            None
+        };
+
+        CssNumber {
+            span: self.span(start),
+            value,
+            unit,
+        }
+    }
+
+    fn read_ident_like(&mut self) -> CssComponentValue<'a> {
+        let start = self.index;
+        let name = self.read_name();
+        if self.peek(0) != Some(b'(') {
+            return CssComponentValue::Ident(CssIdent {
+                span: self.span(start),
+                name,
+            });
+        }
+
+        self.index += 1;
+        if name.eq_ignore_ascii_case("url") {
+            return CssComponentValue::Url(self.read_url(start));
+        }
+
+        let children = self.read_components(Some(b')'));
+        self.index = (self.index + 1).min(self.end);
+        CssComponentValue::Function(CssFunction {
+            span: self.span(start),
+            name,
+            children,
+        })
+    }
+
+    fn read_url(&mut self, start: usize) -> CssUrl<'a> {
+        self.skip_whitespace_and_comments();
+        let (value, value_span, quote) = if matches!(self.peek(0), Some(b'"' | b'\'')) {
+            let string = self.read_string();
+            let value_start = string.span.start + 1;
+            let value_end = value_start + string.value.len() as u32;
+            (
+                string.value,
+                Span::new(value_start, value_end),
+                Some(string.quote),
+            )
+        } else {
+            let value_start = self.index;
+            while let Some(b) = self.peek(0) {
+                if b == b')' {
+                    break;
+                }
+                self.index += if b == b'\\' { 2 } else { 1 };
+            }
+            self.index = self.index.min(self.end);
+            let value = self.source()[value_start..self.index].trim_end();
+            (
+                value,
+                self.parser.span(value_start, value_start + value.len()),
+                None,
+            )
+        };
+
+        while self.peek(0).is_some_and(|b| b != b')') {
+            self.index += 1;
+        }
+        self.index = (self.index + 1).min(self.end);
+        CssUrl {
+            span: self.span(start),
+            value,
+            value_span,
+            quote,
+        }
+    }
+
+    fn read_name(&mut self) -> &'a str {
+        let start = self.index;
+        while let Some(b) = self.peek(0) {
+            if b == b'\\' {
+                self.index = (self.index + 2).min(self.end);
+            } else if is_name_byte(b) {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+        &self.source()[start..self.index]
+    }
+
+    fn starts_number(&self) -> bool {
+        let digit_at = |offset| self.peek(offset).is_some_and(|b: u8| b.is_ascii_digit());
+        let starts_unsigned =
+            |offset| digit_at(offset) || (self.peek(offset) == Some(b'.') && digit_at(offset + 1));
+        match self.peek(0) {
+            Some(b'+' | b'-') => starts_unsigned(1),
+            _ => starts_unsigned(0),
+        }
+    }
+
+    fn starts_identifier(&self) -> bool {
+        match self.peek(0) {
+            Some(b'-') => self.peek(1) == Some(b'-') || self.starts_identifier_char(1),
+            _ => self.starts_identifier_char(0),
+        }
+    }
+
+    fn starts_identifier_char(&self, offset: usize) -> bool {
+        self.peek(offset)
+            .is_some_and(|b| b == b'\\' || b == b'_' || b >= 0x80 || b.is_ascii_alphabetic())
+    }
+
+    fn skip_digits(&mut self) {
+        while self.peek(0).is_some_and(|b| b.is_ascii_digit()) {
+            self.index += 1;
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            while self.peek(0).is_some_and(|b| b.is_ascii_whitespace()) {
+                self.index += 1;
+            }
+            if !self.source()[self.index..self.end].starts_with("/*") {
+                break;
+            }
+            self.index = self.source()[self.index + 2..self.end]
+                .find("*/")
+                .map_or(self.end, |offset| self.index + 2 + offset + 2);
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        let index = self.index + offset;
+        (index < self.end).then(|| self.source().as_bytes()[index])
+    }
+
+    fn source(&self) -> &'a str {
+        self.parser.source
+    }
+
+    fn span(&self, start: usize) -> Span {
+        self.parser.span(start, self.index)
+    }
+}
+
+fn is_name_byte(b: u8) -> bool {
+    b == b'-' || b == b'_' || b >= 0x80 || b.is_ascii_alphanumeric()
+}
@@ -22,7 +22,7 @@ pub fn parse_css_atrule<'a>(p: &mut CssParser<'a>) -> Result<CssAtrule<'a>> {
     p.eat_required("@")?;
 
     let name = p.read_identifier()?;
-    let prelude = p.read_css_value();
+    let (prelude, parsed_prelude) = p.read_css_value_with_components();
 
     let block = if p.matches("{") {
         Some(parse_css_block(p)?)
@@ -35,6 +35,7 @@ pub fn parse_css_atrule<'a>(p: &mut CssParser<'a>) -> Result<CssAtrule<'a>> {
         span: p.span(start, p.index),
         name,
         prelude,
+        parsed_prelude,
         block,
     })
 }
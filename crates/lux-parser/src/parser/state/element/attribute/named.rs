@@ -18,7 +18,10 @@ pub fn parse_named_attribute<'a>(input: &mut Input<'a>) -> Result<AttributeNode<
 
     let name: &str = take_while(1.., is_attr_name_char).parse_next(input)?;
 
-    if let Some(colon_pos) = name.find(':') {
+    if let Some(colon_pos) = name
+        .find(':')
+        .filter(|pos| is_directive_prefix(&name[..*pos]))
+    {
         let prefix = &name[..colon_pos];
         let directive_name = &name[colon_pos + 1..];
 
@@ -58,6 +61,13 @@ pub fn parse_named_attribute<'a>(input: &mut Input<'a>) -> Result<AttributeNode<
     }))
 }
 
+fn is_directive_prefix(prefix: &str) -> bool {
+    matches!(
+        prefix,
+        "bind" | "on" | "class" | "style" | "use" | "let" | "animate" | "in" | "out" | "transition"
+    )
+}
+
 fn split_modifiers(name: &str) -> (&str, Vec<&str>) {
     if let Some(pipe_pos) = name.find('|') {
         let base = &name[..pipe_pos];
@@ -1,5 +1,7 @@
 mod common;
 use common::parse_nodes;
+use lux_ast::template::attribute::AttributeNode;
+use lux_ast::template::root::FragmentNode;
 use lux_parser::parse;
 use oxc_allocator::Allocator;
 
@@ -48,3 +50,27 @@ fn test_parses_textarea_boolean_and_expression_attributes() {
     assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
     assert_eq!(parsed.root.fragment.nodes.len(), 4);
 }
+
+#[test]
+fn test_parses_namespaced_attributes_as_regular_attributes() {
+    let source = "<svg><use xlink:href=\"#icon\" xml:lang=\"en\" /></svg>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let FragmentNode::RegularElement(svg) = &parsed.root.fragment.nodes[0] else {
+        panic!("expected svg element");
+    };
+    let FragmentNode::RegularElement(use_element) = &svg.fragment.nodes[0] else {
+        panic!("expected use element");
+    };
+    let names = use_element
+        .attributes
+        .iter()
+        .filter_map(|attribute| match attribute {
+            AttributeNode::Attribute(attribute) => Some(attribute.name),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["xlink:href", "xml:lang"]);
+}
@@ -1,4 +1,5 @@
-use lux_ast::css::stylesheet::StyleSheetChild;
+use lux_ast::css::stylesheet::{CssBlockChild, StyleSheetChild};
+use lux_ast::css::value::CssComponentValue;
 use lux_parser::error::ErrorKind;
 use lux_parser::parse;
 use oxc_allocator::Allocator;
@@ -95,3 +96,86 @@ fn style_parses_escaped_class_identifier() {
     assert_eq!(css.children.len(), 1);
     assert!(matches!(css.children[0], StyleSheetChild::Rule(_)));
 }
+
+#[test]
+fn style_parses_declaration_value_components_with_spans() {
+    let allocator = Allocator::default();
+    let source = "<style>.a { background: url( \"img.png\" ) no-repeat, calc(100% - 2.5px) #fff /* c */ !important; }</style>";
+    let result = parse(source, &allocator, false);
+
+    assert!(result.errors.is_empty());
+    let css = result.root.css.as_ref().expect("expected stylesheet");
+    let StyleSheetChild::Rule(rule) = &css.children[0] else {
+        panic!("expected rule");
+    };
+    let CssBlockChild::Declaration(declaration) = &rule.block.children[0] else {
+        panic!("expected declaration");
+    };
+    let text = |span: lux_ast::common::Span| &source[span.start as usize..span.end as usize];
+    assert_eq!(text(declaration.parsed_value.span), declaration.value);
+
+    let children = &declaration.parsed_value.children;
+    assert_eq!(children.len(), 7);
+    let CssComponentValue::Url(url) = &children[0] else {
+        panic!("expected url, got {:?}", children[0]);
+    };
+    assert_eq!(url.value, "img.png");
+    assert_eq!(url.quote, Some('"'));
+    assert_eq!(text(url.value_span), "img.png");
+    assert_eq!(text(url.span), "url( \"img.png\" )");
+    assert!(matches!(&children[1], CssComponentValue::Ident(ident) if ident.name == "no-repeat"));
+    assert!(matches!(children[2], CssComponentValue::Comma(_)));
+
+    let CssComponentValue::Function(calc) = &children[3] else {
+        panic!("expected function, got {:?}", children[3]);
+    };
+    assert_eq!(calc.name, "calc");
+    assert_eq!(text(calc.span), "calc(100% - 2.5px)");
+    assert!(matches!(
+        &calc.children[..],
+        [
+            CssComponentValue::Number(percent),
+            CssComponentValue::Delimiter(minus),
+            CssComponentValue::Number(length),
+        ] if percent.value == "100"
+            && percent.unit == Some("%")
+            && minus.value == "-"
+            && length.value == "2.5"
+            && length.unit == Some("px")
+    ));
+
+    assert!(matches!(&children[4], CssComponentValue::Hash(hash) if hash.value == "fff"));
+    assert!(matches!(&children[5], CssComponentValue::Delimiter(bang) if bang.value == "!"));
+    assert!(matches!(&children[6], CssComponentValue::Ident(ident) if ident.name == "important"));
+}
+
+#[test]
+fn style_parses_atrule_prelude_components() {
+    let allocator = Allocator::default();
+    let source = "<style>@media screen and (min-width: 600px) { .a { color: blue; } }</style>";
+    let result = parse(source, &allocator, false);
+
+    assert!(result.errors.is_empty());
+    let css = result.root.css.as_ref().expect("expected stylesheet");
+    let StyleSheetChild::Atrule(atrule) = &css.children[0] else {
+        panic!("expected atrule");
+    };
+    let children = &atrule.parsed_prelude.children;
+    assert_eq!(children.len(), 3);
+    let CssComponentValue::Block(block) = &children[2] else {
+        panic!("expected block, got {:?}", children[2]);
+    };
+    assert_eq!(block.open, '(');
+    assert_eq!(
+        &source[block.span.start as usize..block.span.end as usize],
+        "(min-width: 600px)"
+    );
+    assert!(matches!(
+        &block.children[..],
+        [
+            CssComponentValue::Ident(feature),
+            CssComponentValue::Delimiter(colon),
+            CssComponentValue::Number(width),
+        ] if feature.name == "min-width" && colon.value == ":" && width.unit == Some("px")
+    ));
+}
@@ -0,0 +1,234 @@
+use std::borrow::Cow;
+
+use lux_ast::analysis::AnalysisTables;
+use lux_ast::css::StyleSheet;
+use lux_ast::css::stylesheet::{CssAtrule, CssBlock, CssBlockChild, CssRule, StyleSheetChild};
+use lux_ast::css::value::{CssComponentValue, CssValue};
+use rustc_hash::FxHashMap;
+
+use super::selector::complex_is_used_or_maybe_used;
+use crate::{CssAssetKind, CssAssetReference, CssUrlResolver};
+
+/// Rewritten asset references, keyed by the start of their span.
+pub(super) type ResolvedAssetUrls = FxHashMap<u32, String>;
+
+/// Every `url(...)` and `@import` that ends up in the output, in source
+/// order. The rules that are pruned are skipped.
+pub(crate) fn collect_asset_references(
+    stylesheet: &StyleSheet<'_>,
+    analysis: &AnalysisTables,
+) -> Vec<CssAssetReference> {
+    let mut references = Vec::new();
+    for child in &stylesheet.children {
+        match child {
+            StyleSheetChild::Rule(rule) => collect_rule(rule, analysis, &mut references),
+            StyleSheetChild::Atrule(atrule) => collect_atrule(atrule, analysis, &mut references),
+        }
+    }
+    references
+}
+
+/// Calls the resolver once for each reference that ends up in the output,
+/// so that the external and embedded stylesheets can share the results.
+pub(super) fn resolve_asset_urls(
+    stylesheet: &StyleSheet<'_>,
+    analysis: &AnalysisTables,
+    resolver: Option<CssUrlResolver<'_>>,
+) -> ResolvedAssetUrls {
+    let mut resolved = ResolvedAssetUrls::default();
+    let Some(resolver) = resolver else {
+        return resolved;
+    };
+
+    for reference in collect_asset_references(stylesheet, analysis) {
+        if let Some(url) = (resolver.0)(&reference) {
+            resolved.insert(reference.span.start, url);
+        }
+    }
+    resolved
+}
+
+fn collect_rule(
+    rule: &CssRule<'_>,
+    analysis: &AnalysisTables,
+    references: &mut Vec<CssAssetReference>,
+) {
+    let is_rendered = rule
+        .prelude
+        .children
+        .iter()
+        .any(|complex| complex_is_used_or_maybe_used(complex, analysis));
+    if is_rendered {
+        collect_block(&rule.block, analysis, references);
+    }
+}
+
+fn collect_atrule(
+    atrule: &CssAtrule<'_>,
+    analysis: &AnalysisTables,
+    references: &mut Vec<CssAssetReference>,
+) {
+    visit_value(
+        &atrule.parsed_prelude,
+        is_import(atrule),
+        &mut |reference, _| {
+            references.push(reference);
+        },
+    );
+    if let Some(block) = &atrule.block {
+        collect_block(block, analysis, references);
+    }
+}
+
+fn collect_block(
+    block: &CssBlock<'_>,
+    analysis: &AnalysisTables,
+    references: &mut Vec<CssAssetReference>,
+) {
+    for child in &block.children {
+        match child {
+            CssBlockChild::Declaration(declaration) => {
+                visit_value(&declaration.parsed_value, false, &mut |reference, _| {
+                    references.push(reference);
+                });
+            }
+            CssBlockChild::Rule(rule) => collect_rule(rule, analysis, references),
+            CssBlockChild::Atrule(atrule) => collect_atrule(atrule, analysis, references),
+        }
+    }
+}
+
+/// Replaces the references of a declaration value or at-rule prelude that
+/// were resolved, returning the raw text with rewritten references.
+pub(super) fn rewrite_asset_references<'r>(
+    raw: &'r str,
+    value: &CssValue<'_>,
+    is_import: bool,
+    resolved: &ResolvedAssetUrls,
+) -> Cow<'r, str> {
+    if resolved.is_empty() {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut replacements = Vec::new();
+    visit_value(value, is_import, &mut |reference, target| {
+        if let Some(url) = resolved.get(&reference.span.start) {
+            replacements.push((target, url.as_str()));
+        }
+    });
+    if replacements.is_empty() {
+        return Cow::Borrowed(raw);
+    }
+
+    let base = value.span.start;
+    let mut output = String::with_capacity(raw.len());
+    let mut cursor = 0;
+    for (target, resolved) in replacements {
+        let (start, end, text) = match target {
+            RewriteTarget::QuotedValue { start, end, quote } => {
+                (start, end, escape_string(resolved, quote))
+            }
+            RewriteTarget::UnquotedUrl { start, end } => (
+                start,
+                end,
+                format!("url(\"{}\")", escape_string(resolved, '"')),
+            ),
+        };
+        let start = (start - base) as usize;
+        let end = (end - base) as usize;
+        output.push_str(&raw[cursor..start]);
+        output.push_str(&text);
+        cursor = end;
+    }
+    output.push_str(&raw[cursor..]);
+    Cow::Owned(output)
+}
+
+pub(super) fn is_import(atrule: &CssAtrule<'_>) -> bool {
+    atrule.name.eq_ignore_ascii_case("import")
+}
+
+/// Source range to replace when a reference is rewritten: the text between
+/// the quotes, or the whole `url(...)` when it is unquoted so that the new
+/// value can be quoted.
+#[derive(Clone, Copy)]
+enum RewriteTarget {
+    QuotedValue { start: u32, end: u32, quote: char },
+    UnquotedUrl { start: u32, end: u32 },
+}
+
+fn visit_value(
+    value: &CssValue<'_>,
+    is_import: bool,
+    visit: &mut impl FnMut(CssAssetReference, RewriteTarget),
+) {
+    if is_import && let Some(CssComponentValue::String(string)) = value.children.first() {
+        let start = string.span.start + 1;
+        visit(
+            CssAssetReference {
+                kind: CssAssetKind::Import,
+                value: string.value.to_string(),
+                span: string.span,
+            },
+            RewriteTarget::QuotedValue {
+                start,
+                end: start + string.value.len() as u32,
+                quote: string.quote,
+            },
+        );
+    }
+    visit_components(&value.children, is_import, visit);
+}
+
+fn visit_components(
+    components: &[CssComponentValue<'_>],
+    is_import: bool,
+    visit: &mut impl FnMut(CssAssetReference, RewriteTarget),
+) {
+    for (index, component) in components.iter().enumerate() {
+        match component {
+            CssComponentValue::Url(url) => {
+                let target = if let Some(quote) = url.quote {
+                    RewriteTarget::QuotedValue {
+                        start: url.value_span.start,
+                        end: url.value_span.end,
+                        quote,
+                    }
+                } else {
+                    RewriteTarget::UnquotedUrl {
+                        start: url.span.start,
+                        end: url.span.end,
+                    }
+                };
+                visit(
+                    CssAssetReference {
+                        kind: if is_import && index == 0 {
+                            CssAssetKind::Import
+                        } else {
+                            CssAssetKind::Url
+                        },
+                        value: url.value.to_string(),
+                        span: url.span,
+                    },
+                    target,
+                );
+            }
+            CssComponentValue::Function(function) => {
+                visit_components(&function.children, false, visit);
+            }
+            CssComponentValue::Block(block) => visit_components(&block.children, false, visit),
+            _ => {}
+        }
+    }
+}
+
+fn escape_string(value: &str, quote: char) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '\\' || c == quote {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
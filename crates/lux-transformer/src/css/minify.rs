@@ -0,0 +1,190 @@
+const LENGTH_UNITS: &[&str] = &[
+    "px", "em", "rem", "ex", "ch", "vw", "vh", "vmin", "vmax", "cm", "mm", "q", "in", "pt", "pc",
+];
+
+/// Minifies a declaration value: drops comments, collapses whitespace,
+/// removes spaces around commas and parentheses, shortens hex colors and
+/// drops the unit of zero lengths outside of functions.
+///
+/// Custom property values are only trimmed since their tokens may be
+/// substituted anywhere, including inside `calc()`.
+pub(super) fn minify_value(property: &str, value: &str) -> String {
+    if property.starts_with("--") {
+        return value.trim().to_string();
+    }
+
+    // A unitless zero is read as a flex factor rather than a basis.
+    let shorten_zero_lengths = !property.eq_ignore_ascii_case("flex");
+    let mut minifier = ValueMinifier {
+        output: String::with_capacity(value.len()),
+        word: String::new(),
+        depth: 0,
+        pending_space: false,
+        shorten_zero_lengths,
+    };
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                minifier.flush_word();
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                minifier.pending_space = true;
+            }
+            '"' | '\'' => {
+                minifier.flush_word();
+                minifier.push_separated(c);
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    minifier.output.push(next);
+                    if escaped {
+                        escaped = false;
+                    } else if next == '\\' {
+                        escaped = true;
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            ',' => {
+                minifier.flush_word();
+                minifier.pending_space = false;
+                minifier.output.push(',');
+                skip_whitespace(&mut chars);
+            }
+            '(' if minifier.word.eq_ignore_ascii_case("url") => {
+                minifier.flush_word();
+                minifier.output.push('(');
+                skip_whitespace(&mut chars);
+                if chars.peek().is_some_and(|c| *c == '"' || *c == '\'') {
+                    minifier.depth += 1;
+                    continue;
+                }
+                let mut url = String::new();
+                for c in chars.by_ref() {
+                    if c == ')' {
+                        break;
+                    }
+                    url.push(c);
+                }
+                minifier.output.push_str(url.trim_end());
+                minifier.output.push(')');
+            }
+            '(' => {
+                minifier.flush_word();
+                minifier.push_separated('(');
+                minifier.depth += 1;
+                skip_whitespace(&mut chars);
+            }
+            ')' => {
+                minifier.flush_word();
+                minifier.pending_space = false;
+                minifier.output.push(')');
+                minifier.depth = minifier.depth.saturating_sub(1);
+            }
+            c if c.is_whitespace() => {
+                minifier.flush_word();
+                minifier.pending_space = true;
+            }
+            c => minifier.word.push(c),
+        }
+    }
+
+    minifier.flush_word();
+    minifier.output
+}
+
+/// Collapses whitespace runs in an at-rule prelude.
+pub(super) fn minify_prelude(prelude: &str) -> String {
+    prelude.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+struct ValueMinifier {
+    output: String,
+    word: String,
+    depth: usize,
+    pending_space: bool,
+    shorten_zero_lengths: bool,
+}
+
+impl ValueMinifier {
+    fn flush_word(&mut self) {
+        if self.word.is_empty() {
+            return;
+        }
+        let word = std::mem::take(&mut self.word);
+        let word = if let Some(color) = shorten_hex_color(&word) {
+            color
+        } else if self.depth == 0 && self.shorten_zero_lengths && is_zero_length(&word) {
+            "0".to_string()
+        } else {
+            word
+        };
+        self.push_separated_str(&word);
+    }
+
+    fn push_separated(&mut self, c: char) {
+        self.push_pending_space();
+        self.output.push(c);
+    }
+
+    fn push_separated_str(&mut self, text: &str) {
+        self.push_pending_space();
+        self.output.push_str(text);
+    }
+
+    fn push_pending_space(&mut self) {
+        if self.pending_space && !self.output.is_empty() && !self.output.ends_with('(') {
+            self.output.push(' ');
+        }
+        self.pending_space = false;
+    }
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// `#AABBCC` → `#abc`, `#aabbccdd` → `#abcd`; other hex colors are only
+/// lowercased.
+fn shorten_hex_color(word: &str) -> Option<String> {
+    let digits = word.strip_prefix('#')?;
+    if !matches!(digits.len(), 3 | 4 | 6 | 8) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let digits = digits.to_ascii_lowercase();
+    let bytes = digits.as_bytes();
+    if matches!(bytes.len(), 6 | 8) && bytes.chunks(2).all(|pair| pair[0] == pair[1]) {
+        let short = bytes
+            .chunks(2)
+            .map(|pair| pair[0] as char)
+            .collect::<String>();
+        return Some(format!("#{short}"));
+    }
+    Some(format!("#{digits}"))
+}
+
+fn is_zero_length(word: &str) -> bool {
+    let number_end = word
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(word.len());
+    let (number, unit) = word.split_at(number_end);
+    let digits = number.trim_start_matches(['-', '+']);
+    !digits.is_empty()
+        && digits.bytes().any(|b| b.is_ascii_digit())
+        && digits.bytes().all(|b| b == b'0' || b == b'.')
+        && digits.bytes().filter(|b| *b == b'.').count() <= 1
+        && LENGTH_UNITS
+            .iter()
+            .any(|length_unit| unit.eq_ignore_ascii_case(length_unit))
+}
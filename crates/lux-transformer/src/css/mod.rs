@@ -1,42 +1,58 @@
+mod assets;
+mod minify;
+mod nesting;
+mod scope;
 mod selector;
 mod stylesheet;
 
 use lux_ast::analysis::AnalysisTables;
 use lux_ast::css::StyleSheet;
-use lux_ast::template::root::Fragment;
+
+pub(crate) use self::assets::collect_asset_references;
+use self::assets::resolve_asset_urls;
+pub(crate) use self::scope::css_scope;
+use self::stylesheet::StylesheetRenderer;
+use crate::TransformOptions;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum CssOutputFormat {
     External,
     Embedded,
+    Minified,
 }
 
-pub(super) fn render_stylesheet(
-    stylesheet: &StyleSheet<'_>,
-    analysis: &AnalysisTables,
-    scope_class: &str,
-    fragment: &Fragment<'_>,
-) -> String {
-    stylesheet::render(
-        stylesheet,
-        analysis,
-        scope_class,
-        fragment,
-        CssOutputFormat::External,
-    )
+/// The stylesheet as emitted in `css` and as injected into the head.
+pub(super) struct RenderedStylesheet {
+    pub external: String,
+    pub embedded: String,
 }
 
-pub(super) fn render_stylesheet_embedded(
+/// Renders both forms of the stylesheet, resolving its asset references
+/// once for the two of them.
+pub(super) fn render_stylesheet(
     stylesheet: &StyleSheet<'_>,
     analysis: &AnalysisTables,
     scope_class: &str,
-    fragment: &Fragment<'_>,
-) -> String {
-    stylesheet::render(
-        stylesheet,
-        analysis,
-        scope_class,
-        fragment,
-        CssOutputFormat::Embedded,
-    )
+    options: &TransformOptions<'_>,
+) -> RenderedStylesheet {
+    let resolved_urls = resolve_asset_urls(stylesheet, analysis, options.css_url_resolver);
+    let render = |format| {
+        StylesheetRenderer {
+            analysis,
+            scope_class,
+            format: if options.minify_css {
+                CssOutputFormat::Minified
+            } else {
+                format
+            },
+            lower_nesting: options.lower_css_nesting,
+            resolved_urls: &resolved_urls,
+        }
+        .render(stylesheet)
+    };
+
+    RenderedStylesheet {
+        external: render(CssOutputFormat::External),
+        embedded: render(CssOutputFormat::Embedded),
+    }
 }
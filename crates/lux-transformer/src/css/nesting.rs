@@ -0,0 +1,114 @@
+/// Resolves the selectors of a nested rule against those of its parent rule.
+///
+/// Each `&` is replaced with a parent selector, wrapped in `:is(...)` where
+/// pasting it in would change what it matches; a selector without `&` is a
+/// descendant of (or, with a leading combinator, relative to) the parent.
+/// Several parent selectors multiply out into every combination.
+pub(super) fn resolve_nested_selectors(parents: &[String], children: &[String]) -> Vec<String> {
+    if parents.is_empty() {
+        return children.to_vec();
+    }
+
+    let mut resolved = Vec::with_capacity(parents.len() * children.len());
+    for child in children {
+        for parent in parents {
+            let selector = if has_nesting_selector(child) {
+                replace_nesting_selector(child, parent)
+            } else if child.starts_with(' ') {
+                format!("{parent}{child}")
+            } else {
+                format!("{parent} {child}")
+            };
+            if !resolved.contains(&selector) {
+                resolved.push(selector);
+            }
+        }
+    }
+    resolved
+}
+
+/// At-rules whose blocks may contain declarations of the enclosing rule and
+/// are therefore hoisted around it when nesting is lowered.
+pub(super) fn is_conditional_group_atrule(name: &str) -> bool {
+    matches!(
+        name,
+        "media" | "supports" | "container" | "layer" | "scope" | "starting-style" | "document"
+    )
+}
+
+fn has_nesting_selector(selector: &str) -> bool {
+    let mut found = false;
+    visit_unquoted(selector, |c| found |= c == '&');
+    found
+}
+
+/// A parent selector is pasted in as it is at the start of the selector, as
+/// in `&.x` or `& .y`, and where it is a single compound selector that can
+/// join the compound holding `&`. Elsewhere, as in `.y &` or `.x&` with a
+/// parent of `.a .b`, it becomes `:is(.a .b)`.
+fn replace_nesting_selector(selector: &str, parent: &str) -> String {
+    let parent_is_compound = is_compound_selector(parent);
+    let parent_starts_with_type = parent.starts_with(|c: char| c.is_ascii_alphabetic() || c == '*');
+    let mut output = String::with_capacity(selector.len() + parent.len());
+    let mut in_quote = None;
+    let mut previous = None;
+    for c in selector.chars() {
+        match in_quote {
+            Some(quote) => {
+                output.push(c);
+                if c == quote {
+                    in_quote = None;
+                }
+            }
+            None if c == '"' || c == '\'' => {
+                in_quote = Some(c);
+                output.push(c);
+            }
+            None if c == '&' => {
+                let starts_compound = previous.is_none_or(|previous: char| {
+                    previous.is_whitespace() || matches!(previous, '>' | '+' | '~' | '(' | ',')
+                });
+                if output.is_empty()
+                    || (parent_is_compound && (starts_compound || !parent_starts_with_type))
+                {
+                    output.push_str(parent);
+                } else {
+                    output.push_str(":is(");
+                    output.push_str(parent);
+                    output.push(')');
+                }
+            }
+            None => output.push(c),
+        }
+        previous = Some(c);
+    }
+    output
+}
+
+/// Whether the selector has no combinators outside of parentheses and
+/// attribute selectors.
+fn is_compound_selector(selector: &str) -> bool {
+    let mut depth = 0usize;
+    let mut compound = true;
+    visit_unquoted(selector, |c| match c {
+        '(' | '[' => depth += 1,
+        ')' | ']' => depth = depth.saturating_sub(1),
+        c if depth == 0 && (c.is_whitespace() || matches!(c, '>' | '+' | '~')) => {
+            compound = false;
+        }
+        _ => {}
+    });
+    compound
+}
+
+fn visit_unquoted(selector: &str, mut visit: impl FnMut(char)) {
+    let mut in_quote = None;
+    for c in selector.chars() {
+        match in_quote {
+            Some(quote) if c == quote => in_quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => in_quote = Some(c),
+            None => visit(c),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use lux_ast::css::StyleSheet;
+use lux_utils::hash::hash;
+
+use crate::{CssHash, CssHashInput, TransformOptions};
+
+/// Returns the hash of the component styles and the scope class built from
+/// it, which is `svelte-[hash]` unless `options.css_hash` says otherwise.
+pub(crate) fn css_scope(
+    stylesheet: &StyleSheet<'_>,
+    options: &TransformOptions<'_>,
+) -> (String, String) {
+    let css_hash = hash(&css_hash_input(stylesheet.content_styles, options.filename));
+    let css_scope = match options.css_hash {
+        None => format!("svelte-{css_hash}"),
+        Some(CssHash::Template(template)) => template
+            .replace("[name]", &component_name(options.filename))
+            .replace("[hash]", &css_hash),
+        Some(CssHash::Callback(callback)) => (callback.0)(&CssHashInput {
+            css: stylesheet.content_styles,
+            name: &component_name(options.filename),
+            filename: options.filename,
+            default_hash: &css_hash,
+        }),
+    };
+    (css_hash, css_scope)
+}
+
+fn css_hash_input(css: &str, filename: Option<&str>) -> String {
+    match filename {
+        Some("(unknown)") | None => css.to_string(),
+        Some(filename) => filename.replace('\\', "/"),
+    }
+}
+
+/// The component name Svelte derives from a filename: the capitalized
+/// basename without `.svelte`, or the directory name for `index.svelte`.
+fn component_name(filename: Option<&str>) -> String {
+    let filename = filename
+        .filter(|filename| *filename != "(unknown)")
+        .unwrap_or("Component");
+    let mut parts = filename.split(['/', '\\']).collect::<Vec<_>>();
+    let basename = parts.pop().unwrap_or_default();
+    let mut name = basename.replacen(".svelte", "", 1);
+    if name == "index"
+        && let Some(last_dir) = parts.last()
+        && !last_dir.is_empty()
+        && *last_dir != "src"
+    {
+        name = last_dir.to_string();
+    }
+
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
@@ -4,32 +4,49 @@ use lux_ast::analysis::{
 use lux_ast::css::selector::{
     Combinator, CombinatorKind, ComplexSelector, RelativeSelector, SelectorList, SimpleSelector,
 };
-use lux_ast::template::attribute::{AttributeNode, AttributeValue};
-use lux_ast::template::root::{Fragment, FragmentNode};
-use lux_ast::template::tag::TextOrExpressionTag;
 
 pub(super) fn render_selector_list(
     selector_list: &SelectorList<'_>,
     analysis: &AnalysisTables,
     scope_class: &str,
-    fragment: &Fragment<'_>,
 ) -> String {
-    render_selector_list_with_scope(selector_list, analysis, scope_class, fragment, true)
+    render_selector_list_with_scope(selector_list, analysis, scope_class, Some(false))
 }
 
+/// Renders each used complex selector of a list separately, for callers that
+/// combine them with the selectors of a parent rule.
+pub(super) fn render_selector_items(
+    selector_list: &SelectorList<'_>,
+    analysis: &AnalysisTables,
+    scope_class: &str,
+) -> Vec<String> {
+    selector_list
+        .children
+        .iter()
+        .filter(|complex| complex_is_used_or_maybe_used(complex, analysis))
+        .map(|complex| render_complex_selector(complex, analysis, scope_class, Some(false)))
+        .filter(|selector| !selector.is_empty())
+        .collect()
+}
+
+/// `specificity_bumped` is `None` when scoping is disabled, and otherwise
+/// whether an enclosing selector already carries the scope class, in which
+/// case nested selectors use `:where(...)` so as not to add specificity.
 fn render_selector_list_with_scope(
     selector_list: &SelectorList<'_>,
     analysis: &AnalysisTables,
     scope_class: &str,
-    fragment: &Fragment<'_>,
-    scope_enabled: bool,
+    specificity_bumped: Option<bool>,
 ) -> String {
     let rendered = selector_list
         .children
         .iter()
         .map(|complex| {
-            complex_is_used_or_maybe_used(complex, analysis, fragment).then(|| {
-                render_complex_selector(complex, analysis, scope_class, fragment, scope_enabled)
+            // Nothing inside `:global(...)` is pruned.
+            let keep =
+                specificity_bumped.is_none() || complex_is_used_or_maybe_used(complex, analysis);
+            keep.then(|| {
+                render_complex_selector(complex, analysis, scope_class, specificity_bumped)
             })
         })
         .collect::<Vec<_>>();
@@ -58,22 +75,24 @@ fn render_complex_selector(
     complex: &ComplexSelector<'_>,
     analysis: &AnalysisTables,
     scope_class: &str,
-    fragment: &Fragment<'_>,
-    scope_enabled: bool,
+    specificity_bumped: Option<bool>,
 ) -> String {
+    // The scope modifiers of this selector are chosen before those of the
+    // selector lists nested in its pseudo-classes, so that only the first
+    // scoped compound selector of the whole selector adds specificity.
+    let mut bumped = specificity_bumped;
+    let modifiers = complex
+        .children
+        .iter()
+        .map(|relative| scope_modifier(relative, analysis, scope_class, &mut bumped))
+        .collect::<Vec<_>>();
+
     let mut output = String::new();
     let mut has_relative = false;
-    let mut specificity_bumped = false;
-
-    for relative in &complex.children {
-        let relative_output = render_relative_selector(
-            relative,
-            analysis,
-            scope_class,
-            fragment,
-            scope_enabled,
-            &mut specificity_bumped,
-        );
+
+    for (relative, modifier) in complex.children.iter().zip(modifiers) {
+        let relative_output =
+            render_relative_selector(relative, analysis, scope_class, modifier.as_deref(), bumped);
         if relative_output.is_empty() {
             continue;
         }
@@ -91,33 +110,41 @@ fn render_complex_selector(
     output
 }
 
+fn scope_modifier(
+    relative: &RelativeSelector<'_>,
+    analysis: &AnalysisTables,
+    scope_class: &str,
+    specificity_bumped: &mut Option<bool>,
+) -> Option<String> {
+    let bumped = specificity_bumped.as_mut()?;
+    if !relative_analysis(relative, analysis).scoped || !can_apply_scope(relative) {
+        return None;
+    }
+
+    let modifier = if *bumped {
+        format!(":where(.{scope_class})")
+    } else {
+        format!(".{scope_class}")
+    };
+    *bumped = true;
+    Some(modifier)
+}
+
 fn render_relative_selector(
     relative: &RelativeSelector<'_>,
     analysis: &AnalysisTables,
     scope_class: &str,
-    fragment: &Fragment<'_>,
-    scope_enabled: bool,
-    specificity_bumped: &mut bool,
+    scope_modifier: Option<&str>,
+    specificity_bumped: Option<bool>,
 ) -> String {
-    let relative_analysis = relative_analysis(relative, analysis);
-
     let mut rendered = relative
         .selectors
         .iter()
-        .map(|selector| {
-            render_simple_selector(selector, analysis, scope_class, fragment, scope_enabled)
-        })
+        .map(|selector| render_simple_selector(selector, analysis, scope_class, specificity_bumped))
         .collect::<Vec<_>>();
 
-    if scope_enabled && relative_analysis.scoped && can_apply_scope(relative) {
-        let scope_modifier = if *specificity_bumped {
-            format!(":where(.{scope_class})")
-        } else {
-            format!(".{scope_class}")
-        };
-        *specificity_bumped = true;
-
-        apply_scope_modifier(&mut rendered, &scope_modifier);
+    if let Some(modifier) = scope_modifier {
+        apply_scope_modifier(&mut rendered, modifier);
     }
 
     rendered
@@ -135,6 +162,12 @@ fn can_apply_scope(relative: &RelativeSelector<'_>) -> bool {
         return false;
     }
 
+    // `:root:has(...)` and `:global(...):has(...)` select an element outside
+    // of the component; only the selectors inside `:has(...)` are scoped.
+    if has_outer_subject(relative) {
+        return false;
+    }
+
     if relative.selectors.len() == 1 {
         return !matches!(
             &relative.selectors[0],
@@ -146,6 +179,24 @@ fn can_apply_scope(relative: &RelativeSelector<'_>) -> bool {
     true
 }
 
+/// Whether a compound selector with `:has(...)` may select an element outside
+/// of the component, through `:root` or `:global(...)`.
+fn has_outer_subject(relative: &RelativeSelector<'_>) -> bool {
+    let mut has_has = false;
+    let mut has_outer = false;
+    for selector in &relative.selectors {
+        if let SimpleSelector::PseudoClassSelector(pseudo_class) = selector {
+            match pseudo_class.name {
+                "has" => has_has = true,
+                "root" => has_outer = true,
+                "global" => has_outer |= pseudo_class.args.is_some(),
+                _ => {}
+            }
+        }
+    }
+    has_has && has_outer
+}
+
 fn apply_scope_modifier(rendered: &mut [RenderedSimpleSelector], modifier: &str) {
     if rendered.is_empty() {
         return;
@@ -187,8 +238,7 @@ fn render_simple_selector(
     selector: &SimpleSelector<'_>,
     analysis: &AnalysisTables,
     scope_class: &str,
-    fragment: &Fragment<'_>,
-    scope_enabled: bool,
+    specificity_bumped: Option<bool>,
 ) -> RenderedSimpleSelector {
     match selector {
         SimpleSelector::TypeSelector(type_selector) => RenderedSimpleSelector {
@@ -240,13 +290,7 @@ fn render_simple_selector(
                 if let Some(args) = &pseudo_class.args {
                     return RenderedSimpleSelector {
                         kind: RenderedSelectorKind::Other,
-                        text: render_selector_list_with_scope(
-                            args,
-                            analysis,
-                            scope_class,
-                            fragment,
-                            false,
-                        ),
+                        text: render_selector_list_with_scope(args, analysis, scope_class, None),
                     };
                 }
 
@@ -258,14 +302,16 @@ fn render_simple_selector(
 
             let mut text = format!(":{}", pseudo_class.name);
             if let Some(args) = &pseudo_class.args {
-                text.push('(');
-                text.push_str(&render_selector_list_with_scope(
+                // A leading combinator, as in `:has(> p)`, needs no space
+                // after the parenthesis.
+                let args = render_selector_list_with_scope(
                     args,
                     analysis,
                     scope_class,
-                    fragment,
-                    scope_enabled,
-                ));
+                    specificity_bumped,
+                );
+                text.push('(');
+                text.push_str(args.trim_start());
                 text.push(')');
             }
 
@@ -293,10 +339,22 @@ fn render_simple_selector(
     }
 }
 
-fn complex_is_used_or_maybe_used(
+fn render_combinator(combinator: Option<&Combinator>) -> String {
+    match combinator.map(|combinator| combinator.kind) {
+        Some(CombinatorKind::Descendant) => " ".to_string(),
+        Some(CombinatorKind::Child) => " > ".to_string(),
+        Some(CombinatorKind::NextSibling) => " + ".to_string(),
+        Some(CombinatorKind::SubsequentSibling) => " ~ ".to_string(),
+        Some(CombinatorKind::Column) => " || ".to_string(),
+        None => " ".to_string(),
+    }
+}
+
+/// Complex selectors that cannot match any element of the template are left
+/// out; the analyzer has warned about them.
+pub(super) fn complex_is_used_or_maybe_used(
     complex: &ComplexSelector<'_>,
     analysis: &AnalysisTables,
-    fragment: &Fragment<'_>,
 ) -> bool {
     let complex_analysis = analysis
         .complex_selectors
@@ -309,203 +367,7 @@ fn complex_is_used_or_maybe_used(
             is_global: false,
             used: false,
         });
-    if complex_analysis.used || complex_analysis.is_global {
-        return true;
-    }
-    if fragment.dynamic || complex.children.len() != 1 {
-        return true;
-    }
-
-    fragment_contains_matching_element(fragment, &complex.children[0], analysis)
-}
-
-fn fragment_contains_matching_element(
-    fragment: &Fragment<'_>,
-    relative: &RelativeSelector<'_>,
-    analysis: &AnalysisTables,
-) -> bool {
-    fragment
-        .nodes
-        .iter()
-        .any(|node| node_contains_matching_element(node, relative, analysis))
-}
-
-fn node_contains_matching_element(
-    node: &FragmentNode<'_>,
-    relative: &RelativeSelector<'_>,
-    analysis: &AnalysisTables,
-) -> bool {
-    match node {
-        FragmentNode::RegularElement(element) => {
-            relative_targets_element(relative, analysis, element.name, &element.attributes)
-                || fragment_contains_matching_element(&element.fragment, relative, analysis)
-        }
-        FragmentNode::TitleElement(element) => {
-            fragment_contains_matching_element(&element.fragment, relative, analysis)
-        }
-        FragmentNode::SvelteFragment(element) => {
-            fragment_contains_matching_element(&element.fragment, relative, analysis)
-        }
-        FragmentNode::SvelteBody(element) => {
-            fragment_contains_matching_element(&element.fragment, relative, analysis)
-        }
-        FragmentNode::SvelteWindow(element) => {
-            fragment_contains_matching_element(&element.fragment, relative, analysis)
-        }
-        FragmentNode::SvelteDocument(element) => {
-            fragment_contains_matching_element(&element.fragment, relative, analysis)
-        }
-        FragmentNode::SvelteBoundary(element) => {
-            fragment_contains_matching_element(&element.fragment, relative, analysis)
-        }
-        FragmentNode::KeyBlock(block) => {
-            fragment_contains_matching_element(&block.fragment, relative, analysis)
-        }
-        _ => false,
-    }
-}
-
-fn relative_targets_element(
-    relative: &RelativeSelector<'_>,
-    analysis: &AnalysisTables,
-    name: &str,
-    attributes: &[AttributeNode<'_>],
-) -> bool {
-    relative
-        .selectors
-        .iter()
-        .all(|selector| simple_selector_targets_element(selector, analysis, name, attributes))
-}
-
-fn simple_selector_targets_element(
-    selector: &SimpleSelector<'_>,
-    analysis: &AnalysisTables,
-    name: &str,
-    attributes: &[AttributeNode<'_>],
-) -> bool {
-    match selector {
-        SimpleSelector::TypeSelector(type_selector) => {
-            type_selector.name == "*" || type_selector.name.eq_ignore_ascii_case(name)
-        }
-        SimpleSelector::IdSelector(id_selector) => {
-            static_attribute_value(attributes, "id").is_some_and(|value| value == id_selector.name)
-        }
-        SimpleSelector::ClassSelector(class_selector) => {
-            attributes.iter().any(|attribute| match attribute {
-                AttributeNode::Attribute(attribute) if attribute.name == "class" => {
-                    static_class_tokens(&attribute.value)
-                        .iter()
-                        .any(|token| *token == class_selector.name)
-                }
-                AttributeNode::ClassDirective(directive) => directive.name == class_selector.name,
-                _ => false,
-            })
-        }
-        SimpleSelector::AttributeSelector(attribute_selector) => {
-            let Some(value) = static_attribute_value(attributes, attribute_selector.name) else {
-                return attributes.iter().any(|attribute| {
-                    matches!(attribute, AttributeNode::Attribute(attribute) if attribute.name == attribute_selector.name)
-                });
-            };
-
-            attribute_selector
-                .value
-                .is_none_or(|expected| value == strip_attribute_selector_quotes(expected))
-        }
-        SimpleSelector::PseudoClassSelector(pseudo_class) => match pseudo_class.name {
-            "global" | "root" | "host" => false,
-            "is" | "where" => pseudo_class
-                .args
-                .as_ref()
-                .is_none_or(|args| selector_list_matches_element(args, analysis, name, attributes)),
-            "not" => pseudo_class.args.as_ref().is_none_or(|args| {
-                !selector_list_matches_element(args, analysis, name, attributes)
-            }),
-            _ => true,
-        },
-        SimpleSelector::PseudoElementSelector(_)
-        | SimpleSelector::Percentage(_)
-        | SimpleSelector::Nth(_)
-        | SimpleSelector::NestingSelector(_) => true,
-    }
-}
-
-fn selector_list_matches_element(
-    selector_list: &SelectorList<'_>,
-    analysis: &AnalysisTables,
-    name: &str,
-    attributes: &[AttributeNode<'_>],
-) -> bool {
-    selector_list.children.iter().any(|complex| {
-        complex.children.len() == 1
-            && relative_targets_element(&complex.children[0], analysis, name, attributes)
-    })
-}
-
-fn static_attribute_value<'a>(attributes: &'a [AttributeNode<'a>], name: &str) -> Option<&'a str> {
-    attributes.iter().find_map(|attribute| {
-        let AttributeNode::Attribute(attribute) = attribute else {
-            return None;
-        };
-        if attribute.name != name {
-            return None;
-        }
-        match &attribute.value {
-            AttributeValue::True => Some(""),
-            AttributeValue::ExpressionTag(_) => None,
-            AttributeValue::Sequence(chunks) => static_text_chunks(chunks),
-        }
-    })
-}
-
-fn static_class_tokens<'a>(value: &'a AttributeValue<'a>) -> Vec<&'a str> {
-    match value {
-        AttributeValue::True | AttributeValue::ExpressionTag(_) => Vec::new(),
-        AttributeValue::Sequence(chunks) => static_text_chunks(chunks)
-            .map(|text| text.split_whitespace().collect::<Vec<_>>())
-            .unwrap_or_default(),
-    }
-}
-
-fn static_text_chunks<'a>(chunks: &'a [TextOrExpressionTag<'a>]) -> Option<&'a str> {
-    if chunks
-        .iter()
-        .any(|chunk| matches!(chunk, TextOrExpressionTag::ExpressionTag(_)))
-    {
-        return None;
-    }
-
-    if chunks.len() != 1 {
-        return None;
-    }
-
-    match &chunks[0] {
-        TextOrExpressionTag::Text(text) => Some(text.raw),
-        TextOrExpressionTag::ExpressionTag(_) => None,
-    }
-}
-
-fn strip_attribute_selector_quotes(value: &str) -> &str {
-    value
-        .strip_prefix('"')
-        .and_then(|rest| rest.strip_suffix('"'))
-        .or_else(|| {
-            value
-                .strip_prefix('\'')
-                .and_then(|rest| rest.strip_suffix('\''))
-        })
-        .unwrap_or(value)
-}
-
-fn render_combinator(combinator: Option<&Combinator>) -> String {
-    match combinator.map(|combinator| combinator.kind) {
-        Some(CombinatorKind::Descendant) => " ".to_string(),
-        Some(CombinatorKind::Child) => " > ".to_string(),
-        Some(CombinatorKind::NextSibling) => " + ".to_string(),
-        Some(CombinatorKind::SubsequentSibling) => " ~ ".to_string(),
-        Some(CombinatorKind::Column) => " || ".to_string(),
-        None => " ".to_string(),
-    }
+    complex_analysis.used || complex_analysis.is_global
 }
 
 fn relative_analysis<'a>(
@@ -3,141 +3,335 @@ use lux_ast::css::StyleSheet;
 use lux_ast::css::stylesheet::{
     CssAtrule, CssBlock, CssBlockChild, CssDeclaration, CssRule, StyleSheetChild,
 };
-use lux_ast::template::root::Fragment;
 
 use super::CssOutputFormat;
-use super::selector::render_selector_list;
-
-pub(super) fn render(
-    stylesheet: &StyleSheet<'_>,
-    analysis: &AnalysisTables,
-    scope_class: &str,
-    fragment: &Fragment<'_>,
-    format: CssOutputFormat,
-) -> String {
-    stylesheet
-        .children
-        .iter()
-        .filter_map(|child| render_child(child, analysis, scope_class, fragment, format))
-        .collect::<Vec<_>>()
-        .join(match format {
-            CssOutputFormat::External => "\n",
-            CssOutputFormat::Embedded => "",
-        })
+use super::assets::{ResolvedAssetUrls, is_import, rewrite_asset_references};
+use super::minify::{minify_prelude, minify_value};
+use super::nesting::{is_conditional_group_atrule, resolve_nested_selectors};
+use super::selector::{render_selector_items, render_selector_list};
+
+pub(super) struct StylesheetRenderer<'r> {
+    pub analysis: &'r AnalysisTables,
+    pub scope_class: &'r str,
+    pub format: CssOutputFormat,
+    /// Flattens nested rules into plain selectors for browsers without
+    /// native CSS nesting.
+    pub lower_nesting: bool,
+    pub resolved_urls: &'r ResolvedAssetUrls,
 }
 
-fn render_child(
-    child: &StyleSheetChild<'_>,
-    analysis: &AnalysisTables,
-    scope_class: &str,
-    fragment: &Fragment<'_>,
-    format: CssOutputFormat,
-) -> Option<String> {
-    match child {
-        StyleSheetChild::Rule(rule) => render_rule(rule, analysis, scope_class, fragment, format),
-        StyleSheetChild::Atrule(atrule) => {
-            render_atrule(atrule, analysis, scope_class, fragment, format)
+impl StylesheetRenderer<'_> {
+    pub(super) fn render(&self, stylesheet: &StyleSheet<'_>) -> String {
+        let mut rendered = Vec::new();
+        for child in &stylesheet.children {
+            match child {
+                StyleSheetChild::Rule(rule) if self.lower_nesting => {
+                    self.push_lowered_rule(&mut rendered, rule, &[]);
+                }
+                StyleSheetChild::Atrule(atrule) if self.lower_nesting => {
+                    self.push_lowered_atrule(&mut rendered, atrule, &[]);
+                }
+                StyleSheetChild::Rule(rule) => {
+                    if let Some(rule) = self.render_rule(rule) {
+                        self.push_rendered(&mut rendered, rule);
+                    }
+                }
+                StyleSheetChild::Atrule(atrule) => {
+                    if let Some(atrule) = self.render_atrule(atrule) {
+                        self.push_rendered(&mut rendered, atrule);
+                    }
+                }
+            }
         }
+
+        self.join(rendered, "\n")
     }
-}
 
-fn render_rule(
-    rule: &CssRule<'_>,
-    analysis: &AnalysisTables,
-    scope_class: &str,
-    fragment: &Fragment<'_>,
-    format: CssOutputFormat,
-) -> Option<String> {
-    let selector = render_selector_list(&rule.prelude, analysis, scope_class, fragment);
-    if selector.is_empty() {
-        return None;
-    }
-    let block = render_block(&rule.block, analysis, scope_class, fragment, format);
-    Some(format!("{selector} {block}"))
-}
+    fn join(&self, rendered: Vec<RenderedChild>, external_separator: &str) -> String {
+        rendered
+            .into_iter()
+            .map(|child| child.into_string(self.format))
+            .filter(|child| !child.is_empty())
+            .collect::<Vec<_>>()
+            .join(match self.format {
+                CssOutputFormat::External => external_separator,
+                CssOutputFormat::Embedded | CssOutputFormat::Minified => "",
+            })
+    }
 
-fn render_atrule(
-    atrule: &CssAtrule<'_>,
-    analysis: &AnalysisTables,
-    scope_class: &str,
-    fragment: &Fragment<'_>,
-    format: CssOutputFormat,
-) -> Option<String> {
-    if let Some(block) = &atrule.block {
-        if atrule.prelude.is_empty() {
-            Some(format!(
-                "@{}{}",
-                atrule.name,
-                render_block(block, analysis, scope_class, fragment, format)
-            ))
-        } else {
-            Some(format!(
-                "@{} {}{}",
-                atrule.name,
-                atrule.prelude,
-                render_block(block, analysis, scope_class, fragment, format)
-            ))
-        }
-    } else if atrule.prelude.is_empty() {
-        Some(format!("@{};", atrule.name))
-    } else {
-        Some(format!("@{} {};", atrule.name, atrule.prelude))
+    fn push_rendered(&self, rendered: &mut Vec<RenderedChild>, child: RenderedChild) {
+        if self.format != CssOutputFormat::Minified {
+            rendered.push(child);
+            return;
+        }
+
+        match child {
+            RenderedChild::Rule { body, .. } if body.is_empty() => {}
+            RenderedChild::Other(text) if text.is_empty() => {}
+            RenderedChild::Rule { selector, body } => match rendered.last_mut() {
+                Some(RenderedChild::Rule {
+                    selector: previous_selector,
+                    body: previous_body,
+                }) if *previous_selector == selector => previous_body.push_str(&body),
+                _ => rendered.push(RenderedChild::Rule { selector, body }),
+            },
+            other => rendered.push(other),
+        }
     }
-}
 
-fn render_block(
-    block: &CssBlock<'_>,
-    analysis: &AnalysisTables,
-    scope_class: &str,
-    fragment: &Fragment<'_>,
-    format: CssOutputFormat,
-) -> String {
-    if block.children.is_empty() {
-        return "{}".to_string();
+    fn render_rule(&self, rule: &CssRule<'_>) -> Option<RenderedChild> {
+        let selector = render_selector_list(&rule.prelude, self.analysis, self.scope_class);
+        if selector.is_empty() {
+            return None;
+        }
+        let body = self.render_block_body(&rule.block);
+        Some(RenderedChild::Rule { selector, body })
     }
 
-    let body = block
-        .children
-        .iter()
-        .map(|child| render_block_child(child, analysis, scope_class, fragment, format))
-        .filter(|child| !child.is_empty())
-        .collect::<Vec<_>>()
-        .join(match format {
-            CssOutputFormat::External => " ",
-            CssOutputFormat::Embedded => "",
-        });
+    fn render_atrule(&self, atrule: &CssAtrule<'_>) -> Option<RenderedChild> {
+        let prelude = self.render_prelude(atrule);
+        let Some(block) = &atrule.block else {
+            return Some(RenderedChild::Other(render_statement_atrule(
+                atrule, &prelude,
+            )));
+        };
 
-    if body.is_empty() {
-        return "{}".to_string();
+        let body = self.render_block_body(block);
+        if self.format == CssOutputFormat::Minified && body.is_empty() {
+            return None;
+        }
+        Some(RenderedChild::Other(render_block_atrule(
+            atrule,
+            &prelude,
+            wrap_block(body, self.format),
+        )))
     }
 
-    match format {
-        CssOutputFormat::External => format!("{{ {body} }}"),
-        CssOutputFormat::Embedded => format!("{{{body}}}"),
+    fn render_prelude(&self, atrule: &CssAtrule<'_>) -> String {
+        let prelude = rewrite_asset_references(
+            atrule.prelude,
+            &atrule.parsed_prelude,
+            is_import(atrule),
+            self.resolved_urls,
+        );
+        match self.format {
+            CssOutputFormat::Minified => minify_prelude(&prelude),
+            _ => prelude.into_owned(),
+        }
     }
-}
 
-fn render_block_child(
-    child: &CssBlockChild<'_>,
-    analysis: &AnalysisTables,
-    scope_class: &str,
-    fragment: &Fragment<'_>,
-    format: CssOutputFormat,
-) -> String {
-    match child {
-        CssBlockChild::Declaration(declaration) => render_declaration(declaration, format),
-        CssBlockChild::Rule(rule) => {
-            render_rule(rule, analysis, scope_class, fragment, format).unwrap_or_default()
+    fn render_block_body(&self, block: &CssBlock<'_>) -> String {
+        let mut rendered = Vec::new();
+        for child in &block.children {
+            let child = match child {
+                CssBlockChild::Declaration(declaration) => {
+                    Some(RenderedChild::Other(self.render_declaration(declaration)))
+                }
+                CssBlockChild::Rule(rule) => self.render_rule(rule),
+                CssBlockChild::Atrule(atrule) => self.render_atrule(atrule),
+            };
+            if let Some(child) = child {
+                self.push_rendered(&mut rendered, child);
+            }
         }
-        CssBlockChild::Atrule(atrule) => {
-            render_atrule(atrule, analysis, scope_class, fragment, format).unwrap_or_default()
+
+        self.join(rendered, " ")
+    }
+
+    fn render_declaration(&self, declaration: &CssDeclaration<'_>) -> String {
+        let value = rewrite_asset_references(
+            declaration.value,
+            &declaration.parsed_value,
+            false,
+            self.resolved_urls,
+        );
+        match self.format {
+            CssOutputFormat::External => format!("{}: {value};", declaration.property),
+            CssOutputFormat::Embedded => format!("{}:{value};", declaration.property),
+            CssOutputFormat::Minified => format!(
+                "{}:{};",
+                declaration.property,
+                minify_value(declaration.property, &value)
+            ),
         }
     }
+
+    /// Pushes a rule and, after it, each of its nested rules as top-level
+    /// rules. Declarations following a nested rule start a new rule with the
+    /// same selector so that source order is preserved.
+    fn push_lowered_rule(
+        &self,
+        rendered: &mut Vec<RenderedChild>,
+        rule: &CssRule<'_>,
+        parents: &[String],
+    ) {
+        let selectors = resolve_nested_selectors(
+            parents,
+            &render_selector_items(&rule.prelude, self.analysis, self.scope_class),
+        );
+        if selectors.is_empty() {
+            return;
+        }
+
+        let selector = selectors.join(", ");
+        if rule.block.children.is_empty() {
+            let child = RenderedChild::Rule {
+                selector,
+                body: String::new(),
+            };
+            self.push_rendered(rendered, child);
+            return;
+        }
+
+        let mut declarations = Vec::new();
+        for child in &rule.block.children {
+            match child {
+                CssBlockChild::Declaration(declaration) => {
+                    declarations.push(self.render_declaration(declaration));
+                }
+                CssBlockChild::Atrule(atrule) if atrule.block.is_none() => {
+                    declarations.push(render_statement_atrule(
+                        atrule,
+                        &self.render_prelude(atrule),
+                    ));
+                }
+                CssBlockChild::Rule(nested) => {
+                    self.flush_declarations(rendered, &selector, &mut declarations);
+                    self.push_lowered_rule(rendered, nested, &selectors);
+                }
+                CssBlockChild::Atrule(atrule) => {
+                    self.flush_declarations(rendered, &selector, &mut declarations);
+                    self.push_lowered_atrule(rendered, atrule, &selectors);
+                }
+            }
+        }
+        self.flush_declarations(rendered, &selector, &mut declarations);
+    }
+
+    /// Conditional group rules are hoisted around the enclosing rule, whose
+    /// selectors then apply to the declarations and rules inside them.
+    fn push_lowered_atrule(
+        &self,
+        rendered: &mut Vec<RenderedChild>,
+        atrule: &CssAtrule<'_>,
+        parents: &[String],
+    ) {
+        let Some(block) = &atrule.block else {
+            if let Some(atrule) = self.render_atrule(atrule) {
+                self.push_rendered(rendered, atrule);
+            }
+            return;
+        };
+        if !is_conditional_group_atrule(atrule.name) {
+            if let Some(atrule) = self.render_atrule(atrule) {
+                self.push_rendered(rendered, atrule);
+            }
+            return;
+        }
+
+        let selector = parents.join(", ");
+        let mut inner = Vec::new();
+        let mut declarations = Vec::new();
+        for child in &block.children {
+            match child {
+                CssBlockChild::Declaration(declaration) => {
+                    declarations.push(self.render_declaration(declaration));
+                }
+                CssBlockChild::Rule(rule) => {
+                    self.flush_declarations(&mut inner, &selector, &mut declarations);
+                    self.push_lowered_rule(&mut inner, rule, parents);
+                }
+                CssBlockChild::Atrule(nested) => {
+                    self.flush_declarations(&mut inner, &selector, &mut declarations);
+                    self.push_lowered_atrule(&mut inner, nested, parents);
+                }
+            }
+        }
+        self.flush_declarations(&mut inner, &selector, &mut declarations);
+
+        let body = self.join(inner, " ");
+        if self.format == CssOutputFormat::Minified && body.is_empty() {
+            return;
+        }
+        let prelude = self.render_prelude(atrule);
+        self.push_rendered(
+            rendered,
+            RenderedChild::Other(render_block_atrule(
+                atrule,
+                &prelude,
+                wrap_block(body, self.format),
+            )),
+        );
+    }
+
+    fn flush_declarations(
+        &self,
+        rendered: &mut Vec<RenderedChild>,
+        selector: &str,
+        declarations: &mut Vec<String>,
+    ) {
+        if declarations.is_empty() {
+            return;
+        }
+        let declarations = std::mem::take(declarations);
+        if selector.is_empty() {
+            // Declarations directly inside a top-level at-rule stay as they are.
+            rendered.extend(declarations.into_iter().map(RenderedChild::Other));
+            return;
+        }
+        let child = RenderedChild::Rule {
+            selector: selector.to_string(),
+            body: declarations.join(match self.format {
+                CssOutputFormat::External => " ",
+                CssOutputFormat::Embedded | CssOutputFormat::Minified => "",
+            }),
+        };
+        self.push_rendered(rendered, child);
+    }
+}
+
+/// A rendered stylesheet or block child. Rules keep their selector apart
+/// from their body so that minified output can merge adjacent rules.
+enum RenderedChild {
+    Rule { selector: String, body: String },
+    Other(String),
 }
 
-fn render_declaration(declaration: &CssDeclaration<'_>, format: CssOutputFormat) -> String {
+impl RenderedChild {
+    fn into_string(self, format: CssOutputFormat) -> String {
+        match self {
+            Self::Rule { selector, body } => match format {
+                CssOutputFormat::Minified => format!("{selector}{}", wrap_block(body, format)),
+                _ => format!("{selector} {}", wrap_block(body, format)),
+            },
+            Self::Other(text) => text,
+        }
+    }
+}
+
+fn render_statement_atrule(atrule: &CssAtrule<'_>, prelude: &str) -> String {
+    if prelude.is_empty() {
+        format!("@{};", atrule.name)
+    } else {
+        format!("@{} {prelude};", atrule.name)
+    }
+}
+
+fn render_block_atrule(atrule: &CssAtrule<'_>, prelude: &str, block: String) -> String {
+    if prelude.is_empty() {
+        format!("@{}{block}", atrule.name)
+    } else {
+        format!("@{} {prelude}{block}", atrule.name)
+    }
+}
+
+fn wrap_block(body: String, format: CssOutputFormat) -> String {
+    if body.is_empty() {
+        return "{}".to_string();
+    }
+
     match format {
-        CssOutputFormat::External => format!("{}: {};", declaration.property, declaration.value),
-        CssOutputFormat::Embedded => format!("{}:{};", declaration.property, declaration.value),
+        CssOutputFormat::External => format!("{{ {body} }}"),
+        CssOutputFormat::Embedded | CssOutputFormat::Minified => format!("{{{body}}}"),
     }
 }
@@ -31,6 +31,9 @@ pub(super) const LUX_CLEANUP_MOUNT: &str = "__lux_cleanup_mount";
 pub(super) const LUX_BEGIN_RENDER: &str = "__lux_begin_render";
 pub(super) const LUX_END_RENDER: &str = "__lux_end_render";
 pub(crate) const LUX_RENDER_COMPONENT: &str = "__lux_render_component";
+pub(crate) const LUX_CSS_PROPS: &str = "__lux_css_props";
+pub(crate) const LUX_BOUNDARY: &str = "__lux_boundary";
+pub(crate) const LUX_BLOCK: &str = "__lux_block";
 pub(super) const LUX_EVENT_ATTR: &str = "__lux_event_attr";
 pub(super) const LUX_EVENT_TARGET_ATTR: &str = "__lux_event_target_attr";
 pub(super) const LUX_MOUNT_EVENTS: &str = "__lux_mount_events";
@@ -40,13 +43,32 @@ pub(super) const LUX_BIND_TARGET_ATTR: &str = "__lux_bind_target_attr";
 pub(super) const LUX_MOUNT_BINDINGS: &str = "__lux_mount_bindings";
 pub(super) const LUX_USE_ATTR: &str = "__lux_use_attr";
 pub(super) const LUX_MOUNT_ACTIONS: &str = "__lux_mount_actions";
+pub(super) const LUX_ATTACH_ATTR: &str = "__lux_attach_attr";
+pub(crate) const LUX_ATTACHMENT_KEY: &str = "__lux_attachment_key";
 pub(super) const LUX_TRANSITION_ATTR: &str = "__lux_transition_attr";
 pub(super) const LUX_MOUNT_TRANSITIONS: &str = "__lux_mount_transitions";
 pub(super) const LUX_ANIMATE_ATTR: &str = "__lux_animate_attr";
 pub(super) const LUX_MOUNT_ANIMATIONS: &str = "__lux_mount_animations";
+pub(super) const LUX_CREATE_CUSTOM_ELEMENT: &str = "__lux_create_custom_element";
+pub(super) const LUX_STATE: &str = "__lux_state";
+pub(super) const LUX_PROPS: &str = "__lux_props";
+pub(super) const LUX_SET: &str = "__lux_set";
+pub(super) const LUX_EFFECT: &str = "__lux_effect";
+pub(super) const LUX_EFFECT_PRE: &str = "__lux_effect_pre";
+pub(super) const LUX_EFFECT_ROOT: &str = "__lux_effect_root";
+pub(crate) const LUX_EFFECT_TRACKING: &str = "__lux_effect_tracking";
+pub(crate) const LUX_EFFECT_PENDING: &str = "__lux_effect_pending";
+pub(super) const LUX_READ: &str = "__lux_read";
+pub(super) const LUX_TRACK: &str = "__lux_track";
+pub(super) const LUX_PROXY: &str = "__lux_proxy";
+pub(super) const LUX_SNAPSHOT: &str = "__lux_snapshot";
+pub(super) const LUX_OBSERVE: &str = "__lux_observe";
+pub(super) const LUX_INVALIDATE: &str = "__lux_invalidate";
+pub(super) const LUX_INSPECT: &str = "__lux_inspect";
+pub(super) const LUX_INSPECT_TRACE: &str = "__lux_inspect_trace";
 
-pub(super) const LUX_RUNTIME_SERVER_IMPORT_SOURCE: &str = "import { stringify as __lux_stringify, escape as __lux_escape, escape_attr as __lux_escape_attr, attr as __lux_attr, class_attr as __lux_class_attr, style_attr as __lux_style_attr, attributes as __lux_attributes, is_boolean_attr as __lux_is_boolean_attr, props_id as __lux_props_id_runtime, finalize_head as __lux_finalize_head, rest_props as __lux_rest_props, store_get as __lux_store_get, unsubscribe_stores as __lux_unsubscribe_stores, mount_html as __lux_mount_html, mount_head as __lux_mount_head, is_mount_target as __lux_is_mount_target, cleanup_mount as __lux_cleanup_mount, begin_render as __lux_begin_render, end_render as __lux_end_render, render_component as __lux_render_component, event_attr as __lux_event_attr, event_target_attr as __lux_event_target_attr, mount_events as __lux_mount_events, once as __lux_once, bind_attr as __lux_bind_attr, bind_target_attr as __lux_bind_target_attr, mount_bindings as __lux_mount_bindings, use_attr as __lux_use_attr, mount_actions as __lux_mount_actions, transition_attr as __lux_transition_attr, mount_transitions as __lux_mount_transitions, animate_attr as __lux_animate_attr, mount_animations as __lux_mount_animations } from \"lux/runtime/server\";";
-pub(super) const LUX_RUNTIME_CLIENT_IMPORT_SOURCE: &str = "import { stringify as __lux_stringify, escape as __lux_escape, escape_attr as __lux_escape_attr, attr as __lux_attr, class_attr as __lux_class_attr, style_attr as __lux_style_attr, attributes as __lux_attributes, is_boolean_attr as __lux_is_boolean_attr, props_id as __lux_props_id, rest_props as __lux_rest_props, mount_html as __lux_mount_html, mount_head as __lux_mount_head, is_mount_target as __lux_is_mount_target, cleanup_mount as __lux_cleanup_mount, begin_render as __lux_begin_render, end_render as __lux_end_render, render_component as __lux_render_component, event_attr as __lux_event_attr, event_target_attr as __lux_event_target_attr, mount_events as __lux_mount_events, once as __lux_once, bind_attr as __lux_bind_attr, bind_target_attr as __lux_bind_target_attr, mount_bindings as __lux_mount_bindings, use_attr as __lux_use_attr, mount_actions as __lux_mount_actions, transition_attr as __lux_transition_attr, mount_transitions as __lux_mount_transitions, animate_attr as __lux_animate_attr, mount_animations as __lux_mount_animations } from \"lux/runtime/client\";";
+pub(super) const LUX_RUNTIME_SERVER_IMPORT_SOURCE: &str = "import { stringify as __lux_stringify, escape as __lux_escape, escape_attr as __lux_escape_attr, attr as __lux_attr, class_attr as __lux_class_attr, style_attr as __lux_style_attr, attributes as __lux_attributes, is_boolean_attr as __lux_is_boolean_attr, props_id as __lux_props_id_runtime, finalize_head as __lux_finalize_head, rest_props as __lux_rest_props, store_get as __lux_store_get, unsubscribe_stores as __lux_unsubscribe_stores, mount_html as __lux_mount_html, mount_head as __lux_mount_head, is_mount_target as __lux_is_mount_target, cleanup_mount as __lux_cleanup_mount, begin_render as __lux_begin_render, end_render as __lux_end_render, render_component as __lux_render_component, css_props as __lux_css_props, boundary as __lux_boundary, event_attr as __lux_event_attr, event_target_attr as __lux_event_target_attr, mount_events as __lux_mount_events, once as __lux_once, bind_attr as __lux_bind_attr, bind_target_attr as __lux_bind_target_attr, mount_bindings as __lux_mount_bindings, use_attr as __lux_use_attr, mount_actions as __lux_mount_actions, attach_attr as __lux_attach_attr, attachment_key as __lux_attachment_key, transition_attr as __lux_transition_attr, mount_transitions as __lux_mount_transitions, animate_attr as __lux_animate_attr, mount_animations as __lux_mount_animations } from \"lux/runtime/server\";";
+pub(super) const LUX_RUNTIME_CLIENT_IMPORT_SOURCE: &str = "import { stringify as __lux_stringify, escape as __lux_escape, escape_attr as __lux_escape_attr, attr as __lux_attr, class_attr as __lux_class_attr, style_attr as __lux_style_attr, attributes as __lux_attributes, is_boolean_attr as __lux_is_boolean_attr, props_id as __lux_props_id, rest_props as __lux_rest_props, mount_html as __lux_mount_html, mount_head as __lux_mount_head, is_mount_target as __lux_is_mount_target, cleanup_mount as __lux_cleanup_mount, begin_render as __lux_begin_render, end_render as __lux_end_render, render_component as __lux_render_component, css_props as __lux_css_props, boundary as __lux_boundary, block as __lux_block, event_attr as __lux_event_attr, event_target_attr as __lux_event_target_attr, mount_events as __lux_mount_events, once as __lux_once, bind_attr as __lux_bind_attr, bind_target_attr as __lux_bind_target_attr, mount_bindings as __lux_mount_bindings, use_attr as __lux_use_attr, mount_actions as __lux_mount_actions, attach_attr as __lux_attach_attr, attachment_key as __lux_attachment_key, transition_attr as __lux_transition_attr, mount_transitions as __lux_mount_transitions, animate_attr as __lux_animate_attr, mount_animations as __lux_mount_animations, create_custom_element as __lux_create_custom_element, state as __lux_state, props as __lux_props, set as __lux_set, effect as __lux_effect, effect_pre as __lux_effect_pre, effect_root as __lux_effect_root, effect_tracking as __lux_effect_tracking, effect_pending as __lux_effect_pending, read as __lux_read, track as __lux_track, proxy as __lux_proxy, snapshot as __lux_snapshot, observe as __lux_observe, invalidate as __lux_invalidate, inspect as __lux_inspect, inspect_trace as __lux_inspect_trace } from \"lux/runtime/client\";";
 
 pub(super) fn push_const<'a>(
     ast: AstBuilder<'a>,
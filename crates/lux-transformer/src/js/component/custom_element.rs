@@ -0,0 +1,99 @@
+use lux_ast::template::root::{CustomElementOptions, CustomElementShadow};
+use oxc_allocator::CloneIn;
+use oxc_ast::{
+    AstBuilder, NONE,
+    ast::{Expression, PropertyKind},
+};
+use oxc_span::SPAN;
+
+use super::consts::{LUX_CREATE_CUSTOM_ELEMENT, LUX_CSS, optional_string_expr};
+
+/// Wraps the client component function in the runtime custom element factory.
+///
+/// The factory returns the component itself, so the default export keeps its
+/// mount signature while `component.element` carries the generated class.
+pub(super) fn wrap_custom_element_component<'a>(
+    ast: AstBuilder<'a>,
+    component: Expression<'a>,
+    options: &CustomElementOptions<'_>,
+    prop_names: &[String],
+) -> Expression<'a> {
+    ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident(LUX_CREATE_CUSTOM_ELEMENT)),
+        NONE,
+        ast.vec_from_array([
+            component.into(),
+            custom_element_options_expression(ast, options, prop_names).into(),
+        ]),
+        false,
+    )
+}
+
+fn custom_element_options_expression<'a>(
+    ast: AstBuilder<'a>,
+    options: &CustomElementOptions<'_>,
+    prop_names: &[String],
+) -> Expression<'a> {
+    let mut properties = ast.vec_with_capacity(6);
+    properties.push(init_property(
+        ast,
+        "tag",
+        optional_string_expr(ast, options.tag),
+    ));
+
+    let props = options.props.as_ref().map_or_else(
+        || ast.expression_object(SPAN, ast.vec()),
+        |props| Expression::ObjectExpression(ast.alloc(props.clone_in(ast.allocator))),
+    );
+    properties.push(init_property(ast, "props", props));
+
+    let mut names = ast.vec_with_capacity(prop_names.len());
+    for name in prop_names {
+        names.push(
+            ast.expression_string_literal(SPAN, ast.atom(name.as_str()), None)
+                .into(),
+        );
+    }
+    properties.push(init_property(
+        ast,
+        "names",
+        ast.expression_array(SPAN, names),
+    ));
+
+    let shadow = match &options.shadow {
+        None | Some(CustomElementShadow::Open) => optional_string_expr(ast, Some("open")),
+        Some(CustomElementShadow::None) => optional_string_expr(ast, Some("none")),
+        Some(CustomElementShadow::Object(object)) => {
+            Expression::ObjectExpression(ast.alloc(object.clone_in(ast.allocator)))
+        }
+    };
+    properties.push(init_property(ast, "shadow", shadow));
+    properties.push(init_property(
+        ast,
+        "css",
+        ast.expression_identifier(SPAN, ast.ident(LUX_CSS)),
+    ));
+
+    if let Some(extend) = &options.extend {
+        properties.push(init_property(ast, "extend", extend.clone_in(ast.allocator)));
+    }
+
+    ast.expression_object(SPAN, properties)
+}
+
+fn init_property<'a>(
+    ast: AstBuilder<'a>,
+    key: &str,
+    value: Expression<'a>,
+) -> oxc_ast::ast::ObjectPropertyKind<'a> {
+    ast.object_property_kind_object_property(
+        SPAN,
+        PropertyKind::Init,
+        ast.property_key_static_identifier(SPAN, ast.ident(key)),
+        value,
+        false,
+        false,
+        false,
+    )
+}
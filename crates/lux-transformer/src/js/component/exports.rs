@@ -1,3 +1,4 @@
+use lux_ast::template::root::{CustomElementOptions, Namespace};
 use oxc_allocator::CloneIn;
 use oxc_ast::{
     AstBuilder, NONE,
@@ -9,13 +10,15 @@ use oxc_ast::{
 };
 use oxc_span::SPAN;
 
+use super::super::template::namespace_name;
 use super::consts::{
     LUX_BEGIN_RENDER, LUX_CLEANUP_MOUNT, LUX_CSS, LUX_CSS_HASH, LUX_CSS_SCOPE, LUX_END_RENDER,
-    LUX_HAS_DYNAMIC, LUX_IS_MOUNT_TARGET, LUX_MOUNT_ACTIONS, LUX_MOUNT_ANIMATIONS,
-    LUX_MOUNT_BINDINGS, LUX_MOUNT_EVENTS, LUX_MOUNT_HEAD, LUX_MOUNT_HTML, LUX_MOUNT_TRANSITIONS,
-    LUX_PROPS_ID, LUX_PROPS_ID_RUNTIME, LUX_FINALIZE_HEAD, LUX_RENDER_COMPONENT, LUX_TEMPLATE,
-    LUX_UNSUBSCRIBE_STORES,
+    LUX_FINALIZE_HEAD, LUX_HAS_DYNAMIC, LUX_IS_MOUNT_TARGET, LUX_MOUNT_ACTIONS,
+    LUX_MOUNT_ANIMATIONS, LUX_MOUNT_BINDINGS, LUX_MOUNT_EVENTS, LUX_MOUNT_HEAD, LUX_MOUNT_HTML,
+    LUX_MOUNT_TRANSITIONS, LUX_PROPS, LUX_PROPS_ID, LUX_PROPS_ID_RUNTIME, LUX_RENDER_COMPONENT,
+    LUX_TEMPLATE, LUX_UNSUBSCRIBE_STORES,
 };
+use super::custom_element::wrap_custom_element_component;
 
 pub(super) fn named_export_statement(ast: AstBuilder) -> Statement {
     let mut specifiers = ast.vec_with_capacity(5);
@@ -42,7 +45,8 @@ pub(super) fn default_export_statements<'a>(
     render_expression: Expression<'a>,
     render_setup_statements: oxc_allocator::Vec<'a, Statement<'a>>,
     head_expression: Option<Expression<'a>>,
-    _head_setup_statements: Option<oxc_allocator::Vec<'a, Statement<'a>>>,
+    async_rendering: bool,
+    stream: Option<ServerStream<'a>>,
 ) -> oxc_allocator::Vec<'a, Statement<'a>> {
     let mut statements = ast.vec_with_capacity(2);
     if needs_runtime_helpers {
@@ -51,6 +55,7 @@ pub(super) fn default_export_statements<'a>(
             render_expression,
             render_setup_statements,
             head_expression,
+            async_rendering,
         ));
     } else {
         statements.push(server_static_component_export_statement(
@@ -63,36 +68,78 @@ pub(super) fn default_export_statements<'a>(
     statements.push(server_component_metadata_statement(
         ast,
         needs_runtime_helpers,
+        async_rendering,
+        stream,
     ));
     statements
 }
 
+/// Parts of the `stream($$renderer, props)` method emitted for streaming SSR.
+/// The setup and head are rebuilt because the method has its own scope.
+pub(super) struct ServerStream<'a> {
+    pub body_statements: oxc_allocator::Vec<'a, Statement<'a>>,
+    pub setup_statements: oxc_allocator::Vec<'a, Statement<'a>>,
+    pub head_expression: Option<Expression<'a>>,
+}
+
 fn server_component_export_statement<'a>(
     ast: AstBuilder<'a>,
     render_expression: Expression<'a>,
     render_setup_statements: oxc_allocator::Vec<'a, Statement<'a>>,
     head_expression: Option<Expression<'a>>,
+    async_rendering: bool,
 ) -> Statement<'a> {
     let params = ast.alloc_formal_parameters(
         SPAN,
         FormalParameterKind::FormalParameter,
         ast.vec_from_array([
-            ast.formal_parameter(
-                SPAN,
-                ast.vec(),
-                ast.binding_pattern_binding_identifier(SPAN, ast.ident("$$renderer")),
-                NONE,
-                NONE,
-                false,
-                None,
-                false,
-                false,
-            ),
+            renderer_formal_parameter(ast),
             default_props_formal_parameter(ast),
         ]),
         NONE,
     );
+    let mut statements = server_render_statements(
+        ast,
+        ServerRenderBody::Html(render_expression),
+        render_setup_statements,
+        head_expression,
+    );
+    if async_rendering {
+        statements = ast.vec1(async_child_render_statement(ast, statements));
+    }
 
+    ast.module_declaration_export_default_declaration(
+        SPAN,
+        ast.export_default_declaration_kind_function_declaration(
+            SPAN,
+            FunctionType::FunctionDeclaration,
+            Some(ast.binding_identifier(SPAN, ast.ident("__lux_component"))),
+            false,
+            false,
+            false,
+            NONE,
+            NONE,
+            params,
+            NONE,
+            Some(ast.alloc_function_body(SPAN, ast.vec(), statements)),
+        ),
+    )
+    .into()
+}
+
+/// Rendered markup of a server component: either one html string pushed at
+/// the end, or statements that push chunks to `$$renderer` as they finish.
+enum ServerRenderBody<'a> {
+    Html(Expression<'a>),
+    Stream(oxc_allocator::Vec<'a, Statement<'a>>),
+}
+
+fn server_render_statements<'a>(
+    ast: AstBuilder<'a>,
+    body: ServerRenderBody<'a>,
+    render_setup_statements: oxc_allocator::Vec<'a, Statement<'a>>,
+    head_expression: Option<Expression<'a>>,
+) -> oxc_allocator::Vec<'a, Statement<'a>> {
     let head_html_decl = ast.variable_declarator(
         SPAN,
         oxc_ast::ast::VariableDeclarationKind::Const,
@@ -104,14 +151,6 @@ fn server_component_export_statement<'a>(
         ),
         false,
     );
-    let html_decl = ast.variable_declarator(
-        SPAN,
-        oxc_ast::ast::VariableDeclarationKind::Const,
-        ast.binding_pattern_binding_identifier(SPAN, ast.ident("__lux_html")),
-        NONE,
-        Some(render_expression),
-        false,
-    );
     let combined_head_decl = ast.variable_declarator(
         SPAN,
         oxc_ast::ast::VariableDeclarationKind::Const,
@@ -159,7 +198,24 @@ fn server_component_export_statement<'a>(
         render_state_declarator(ast),
     ));
     statements.push(const_declaration_statement(ast, head_html_decl));
-    statements.push(const_declaration_statement(ast, html_decl));
+    let push_html = match body {
+        ServerRenderBody::Html(render_expression) => {
+            let html_decl = ast.variable_declarator(
+                SPAN,
+                oxc_ast::ast::VariableDeclarationKind::Const,
+                ast.binding_pattern_binding_identifier(SPAN, ast.ident("__lux_html")),
+                NONE,
+                Some(render_expression),
+                false,
+            );
+            statements.push(const_declaration_statement(ast, html_decl));
+            true
+        }
+        ServerRenderBody::Stream(stream_statements) => {
+            statements.extend(stream_statements);
+            false
+        }
+    };
     statements.push(const_declaration_statement(
         ast,
         render_result_declarator(ast),
@@ -167,6 +223,9 @@ fn server_component_export_statement<'a>(
     statements.push(unsubscribe_stores_statement(ast));
     statements.push(const_declaration_statement(ast, combined_head_decl));
     statements.push(server_head_mount_statement(ast));
+    if !push_html {
+        return statements;
+    }
     statements.push(
         ast.statement_expression(
             SPAN,
@@ -188,39 +247,58 @@ fn server_component_export_statement<'a>(
             ),
         ),
     );
-
-    ast.module_declaration_export_default_declaration(
-        SPAN,
-        ast.export_default_declaration_kind_function_declaration(
-            SPAN,
-            FunctionType::FunctionDeclaration,
-            Some(ast.binding_identifier(SPAN, ast.ident("__lux_component"))),
-            false,
-            false,
-            false,
-            NONE,
-            NONE,
-            params,
-            NONE,
-            Some(ast.alloc_function_body(SPAN, ast.vec(), statements)),
-        ),
-    )
-    .into()
+    statements
 }
 
 fn server_component_metadata_statement<'a>(
     ast: AstBuilder<'a>,
     include_runtime_methods: bool,
+    async_rendering: bool,
+    stream: Option<ServerStream<'a>>,
 ) -> Statement<'a> {
-    let mut properties = ast.vec_with_capacity(if include_runtime_methods { 7 } else { 5 });
+    let mut properties = ast.vec_with_capacity(if include_runtime_methods { 9 } else { 5 });
     properties.push(named_property(ast, "template", LUX_TEMPLATE));
     properties.push(named_property(ast, "css", LUX_CSS));
     properties.push(named_property(ast, "cssHash", LUX_CSS_HASH));
     properties.push(named_property(ast, "cssScope", LUX_CSS_SCOPE));
     properties.push(named_property(ast, "hasDynamic", LUX_HAS_DYNAMIC));
     if include_runtime_methods {
-        properties.push(function_property(ast, "render", "__lux_render", false));
-        properties.push(function_property(ast, "head", "__lux_head", true));
+        if async_rendering {
+            properties.push(ast.object_property_kind_object_property(
+                SPAN,
+                PropertyKind::Init,
+                ast.property_key_static_identifier(SPAN, ast.ident("async")),
+                ast.expression_boolean_literal(SPAN, true),
+                false,
+                false,
+                false,
+            ));
+        }
+        properties.push(function_property(
+            ast,
+            "render",
+            "__lux_render",
+            false,
+            false,
+        ));
+        properties.push(function_property(
+            ast,
+            "head",
+            "__lux_head",
+            true,
+            async_rendering,
+        ));
+        if let Some(stream) = stream {
+            properties.push(ast.object_property_kind_object_property(
+                SPAN,
+                PropertyKind::Init,
+                ast.property_key_static_identifier(SPAN, ast.ident("stream")),
+                stream_function_expression(ast, stream),
+                false,
+                false,
+                false,
+            ));
+        }
     }
 
     let object_expression = ast.expression_object(SPAN, properties);
@@ -339,6 +417,8 @@ pub(super) fn client_default_export_statement<'a>(
     render_expression: Expression<'a>,
     render_setup_statements: oxc_allocator::Vec<'a, Statement<'a>>,
     head_expression: Option<Expression<'a>>,
+    namespace: Namespace,
+    custom_element: Option<(&CustomElementOptions<'_>, &[String])>,
 ) -> Statement<'a> {
     let params = ast.alloc_formal_parameters(
         SPAN,
@@ -406,12 +486,25 @@ pub(super) fn client_default_export_statement<'a>(
         coalesced_props,
         coalesced_anchor_props,
     );
+    // Props read through the writes of the component to props that are not
+    // bound by its parent.
+    let local_props = ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident(LUX_PROPS)),
+        NONE,
+        ast.vec_from_array([
+            ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+                .into(),
+            selected_props.into(),
+        ]),
+        false,
+    );
     let props_decl = ast.variable_declarator(
         SPAN,
         oxc_ast::ast::VariableDeclarationKind::Const,
         ast.binding_pattern_binding_identifier(SPAN, ast.ident("_props")),
         NONE,
-        Some(selected_props),
+        Some(local_props),
         false,
     );
 
@@ -435,6 +528,44 @@ pub(super) fn client_default_export_statement<'a>(
     );
     let init_self = ast.expression_logical(SPAN, self_missing, LogicalOperator::And, self_assign);
 
+    // Boundaries remember their failures per mount anchor and remount the
+    // component when reset.
+    let render_anchor = ast.expression_conditional(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident("__lux_mount_mode")),
+        anchor_ident.clone_in(ast.allocator),
+        ast.expression_null_literal(SPAN),
+    );
+    let remount = ast.expression_arrow_function(
+        SPAN,
+        true,
+        false,
+        NONE,
+        ast.alloc_formal_parameters(
+            SPAN,
+            FormalParameterKind::ArrowFormalParameters,
+            ast.vec(),
+            NONE,
+        ),
+        NONE,
+        ast.alloc_function_body(
+            SPAN,
+            ast.vec(),
+            ast.vec1(ast.statement_expression(
+                SPAN,
+                ast.expression_call(
+                    SPAN,
+                    ast.expression_identifier(SPAN, ast.ident("__lux_component")),
+                    NONE,
+                    ast.vec_from_array([
+                        anchor_ident.clone_in(ast.allocator).into(),
+                        ast.expression_identifier(SPAN, ast.ident("$$props")).into(),
+                    ]),
+                    false,
+                ),
+            )),
+        ),
+    );
     let render_state_decl = ast.variable_declarator(
         SPAN,
         oxc_ast::ast::VariableDeclarationKind::Const,
@@ -444,7 +575,7 @@ pub(super) fn client_default_export_statement<'a>(
             SPAN,
             ast.expression_identifier(SPAN, ast.ident(LUX_BEGIN_RENDER)),
             NONE,
-            ast.vec(),
+            ast.vec_from_array([render_anchor.into(), remount.into()]),
             false,
         )),
         false,
@@ -566,15 +697,22 @@ pub(super) fn client_default_export_statement<'a>(
         ),
         false,
     );
+    let mut mount_arguments = ast.vec_from_array([
+        anchor_ident.clone_in(ast.allocator).into(),
+        ast.expression_identifier(SPAN, ast.ident("__lux_html"))
+            .into(),
+    ]);
+    if namespace != Namespace::Html {
+        mount_arguments.push(
+            ast.expression_string_literal(SPAN, ast.atom(namespace_name(namespace)), None)
+                .into(),
+        );
+    }
     let mount_call = ast.expression_call(
         SPAN,
         ast.expression_identifier(SPAN, ast.ident(LUX_MOUNT_HTML)),
         NONE,
-        ast.vec_from_array([
-            anchor_ident.clone_in(ast.allocator).into(),
-            ast.expression_identifier(SPAN, ast.ident("__lux_html"))
-                .into(),
-        ]),
+        mount_arguments,
         false,
     );
     let mount_head_call = head_html_decl.as_ref().map(|_| {
@@ -590,13 +728,6 @@ pub(super) fn client_default_export_statement<'a>(
             false,
         )
     });
-    let cleanup_call = ast.expression_call(
-        SPAN,
-        ast.expression_identifier(SPAN, ast.ident(LUX_CLEANUP_MOUNT)),
-        NONE,
-        ast.vec1(anchor_ident.clone_in(ast.allocator).into()),
-        false,
-    );
     let cleanup_return_call = ast.expression_call(
         SPAN,
         ast.expression_identifier(SPAN, ast.ident(LUX_CLEANUP_MOUNT)),
@@ -676,8 +807,9 @@ pub(super) fn client_default_export_statement<'a>(
         ]),
         false,
     );
-    let mut mount_sequence = ast.vec_with_capacity(if mount_head_call.is_some() { 8 } else { 7 });
-    mount_sequence.push(cleanup_call);
+    // Mounting again reconciles with the markup already in place instead of
+    // clearing it first.
+    let mut mount_sequence = ast.vec_with_capacity(if mount_head_call.is_some() { 7 } else { 6 });
     mount_sequence.push(mount_call);
     if let Some(mount_head_call) = mount_head_call {
         mount_sequence.push(mount_head_call);
@@ -706,26 +838,27 @@ pub(super) fn client_default_export_statement<'a>(
         )
         .into(),
     );
+    // The render state holds the instance whose state the script reads.
     statements.push(
         ast.declaration_variable(
             SPAN,
             oxc_ast::ast::VariableDeclarationKind::Const,
-            ast.vec1(props_decl),
+            ast.vec1(render_state_decl),
             false,
         )
         .into(),
     );
-    statements.push(ast.statement_expression(SPAN, init_self));
-    statements.extend(render_setup_statements);
     statements.push(
         ast.declaration_variable(
             SPAN,
             oxc_ast::ast::VariableDeclarationKind::Const,
-            ast.vec1(render_state_decl),
+            ast.vec1(props_decl),
             false,
         )
         .into(),
     );
+    statements.push(ast.statement_expression(SPAN, init_self));
+    statements.extend(render_setup_statements);
     statements.push(
         ast.declaration_variable(
             SPAN,
@@ -816,10 +949,16 @@ pub(super) fn client_default_export_statement<'a>(
         NONE,
         Some(function_body),
     );
+    let component_expression = match custom_element {
+        Some((options, prop_names)) => {
+            wrap_custom_element_component(ast, function_expression, options, prop_names)
+        }
+        None => function_expression,
+    };
 
     ast.module_declaration_export_default_declaration(
         SPAN,
-        ExportDefaultDeclarationKind::from(function_expression),
+        ExportDefaultDeclarationKind::from(component_expression),
     )
     .into()
 }
@@ -858,12 +997,13 @@ fn function_property<'a>(
     key: &str,
     function_name: &str,
     return_head: bool,
+    async_rendering: bool,
 ) -> oxc_ast::ast::ObjectPropertyKind<'a> {
     ast.object_property_kind_object_property(
         SPAN,
         PropertyKind::Init,
         ast.property_key_static_identifier(SPAN, ast.ident(key)),
-        compatibility_function_expression(ast, function_name, return_head),
+        compatibility_function_expression(ast, function_name, return_head, async_rendering),
         false,
         false,
         false,
@@ -874,6 +1014,7 @@ fn compatibility_function_expression<'a>(
     ast: AstBuilder<'a>,
     function_name: &str,
     return_head: bool,
+    async_rendering: bool,
 ) -> Expression<'a> {
     let params = ast.alloc_formal_parameters(
         SPAN,
@@ -900,6 +1041,11 @@ fn compatibility_function_expression<'a>(
             ast,
             render_state_declarator(ast),
         ));
+        let render_component_call = if async_rendering {
+            ast.expression_await(SPAN, render_component_call)
+        } else {
+            render_component_call
+        };
         statements.push(ast.statement_expression(SPAN, render_component_call));
         statements.push(const_declaration_statement(
             ast,
@@ -954,7 +1100,7 @@ fn compatibility_function_expression<'a>(
         FunctionType::FunctionExpression,
         Some(ast.binding_identifier(SPAN, ast.ident(function_name))),
         false,
-        false,
+        async_rendering,
         false,
         NONE,
         NONE,
@@ -964,6 +1110,90 @@ fn compatibility_function_expression<'a>(
     )
 }
 
+fn stream_function_expression<'a>(ast: AstBuilder<'a>, stream: ServerStream<'a>) -> Expression<'a> {
+    let params = ast.alloc_formal_parameters(
+        SPAN,
+        FormalParameterKind::FormalParameter,
+        ast.vec_from_array([
+            renderer_formal_parameter(ast),
+            default_props_formal_parameter(ast),
+        ]),
+        NONE,
+    );
+    let statements = server_render_statements(
+        ast,
+        ServerRenderBody::Stream(stream.body_statements),
+        stream.setup_statements,
+        stream.head_expression,
+    );
+    ast.expression_function(
+        SPAN,
+        FunctionType::FunctionExpression,
+        Some(ast.binding_identifier(SPAN, ast.ident("__lux_stream"))),
+        false,
+        true,
+        false,
+        NONE,
+        NONE,
+        params,
+        NONE,
+        Some(ast.alloc_function_body(SPAN, ast.vec(), statements)),
+    )
+}
+
+/// Wraps the component body in `$$renderer.child(async ($$renderer) => ...)`
+/// so Svelte's server renderer awaits it before serializing the output.
+fn async_child_render_statement<'a>(
+    ast: AstBuilder<'a>,
+    statements: oxc_allocator::Vec<'a, Statement<'a>>,
+) -> Statement<'a> {
+    let params = ast.alloc_formal_parameters(
+        SPAN,
+        FormalParameterKind::ArrowFormalParameters,
+        ast.vec1(renderer_formal_parameter(ast)),
+        NONE,
+    );
+    let callback = ast.expression_arrow_function(
+        SPAN,
+        false,
+        true,
+        NONE,
+        params,
+        NONE,
+        ast.alloc_function_body(SPAN, ast.vec(), statements),
+    );
+    ast.statement_expression(
+        SPAN,
+        ast.expression_call(
+            SPAN,
+            ast.member_expression_static(
+                SPAN,
+                ast.expression_identifier(SPAN, ast.ident("$$renderer")),
+                ast.identifier_name(SPAN, ast.ident("child")),
+                false,
+            )
+            .into(),
+            NONE,
+            ast.vec1(callback.into()),
+            false,
+        ),
+    )
+}
+
+fn renderer_formal_parameter<'a>(ast: AstBuilder<'a>) -> oxc_ast::ast::FormalParameter<'a> {
+    ast.formal_parameter(
+        SPAN,
+        ast.vec(),
+        ast.binding_pattern_binding_identifier(SPAN, ast.ident("$$renderer")),
+        NONE,
+        NONE,
+        false,
+        None,
+        false,
+        false,
+    )
+}
+
 fn default_props_formal_parameter<'a>(ast: AstBuilder<'a>) -> oxc_ast::ast::FormalParameter<'a> {
     let props_pattern = ast.binding_pattern_assignment_pattern(
         SPAN,
@@ -1,8 +1,12 @@
 mod consts;
+mod custom_element;
 mod exports;
 mod script;
 
-pub(crate) use self::consts::{LUX_RENDER_COMPONENT, LUX_REST_PROPS};
+pub(crate) use self::consts::{
+    LUX_ATTACHMENT_KEY, LUX_BLOCK, LUX_BOUNDARY, LUX_CSS_PROPS, LUX_EFFECT_PENDING,
+    LUX_EFFECT_TRACKING, LUX_RENDER_COMPONENT, LUX_REST_PROPS,
+};
 
 use std::collections::BTreeSet;
 
@@ -20,50 +24,67 @@ use oxc_parser::Parser;
 use oxc_span::{SPAN, SourceType};
 
 use self::consts::{
-    LUX_ANIMATE_ATTR, LUX_ATTR, LUX_ATTRIBUTES, LUX_BEGIN_RENDER, LUX_BIND_ATTR,
-    LUX_BIND_TARGET_ATTR, LUX_CLASS_ATTR, LUX_CLEANUP_MOUNT, LUX_CSS, LUX_CSS_HASH, LUX_CSS_SCOPE,
-    LUX_END_RENDER, LUX_ESCAPE, LUX_ESCAPE_ATTR, LUX_EVENT_ATTR, LUX_EVENT_TARGET_ATTR,
-    LUX_HAS_DYNAMIC, LUX_IS_BOOLEAN_ATTR, LUX_MOUNT_ACTIONS, LUX_MOUNT_ANIMATIONS,
-    LUX_MOUNT_BINDINGS, LUX_MOUNT_EVENTS, LUX_MOUNT_HTML, LUX_MOUNT_TRANSITIONS, LUX_ONCE,
-    LUX_PROPS_ID, LUX_RUNTIME_CLIENT_IMPORT_SOURCE, LUX_RUNTIME_SERVER_IMPORT_SOURCE,
-    LUX_STRINGIFY, LUX_STYLE_ATTR, LUX_TEMPLATE, LUX_TRANSITION_ATTR, LUX_USE_ATTR,
-    optional_string_expr, push_const,
+    LUX_ANIMATE_ATTR, LUX_ATTACH_ATTR, LUX_ATTR, LUX_ATTRIBUTES, LUX_BEGIN_RENDER, LUX_BIND_ATTR,
+    LUX_BIND_TARGET_ATTR, LUX_CLASS_ATTR, LUX_CLEANUP_MOUNT, LUX_CREATE_CUSTOM_ELEMENT, LUX_CSS,
+    LUX_CSS_HASH, LUX_CSS_SCOPE, LUX_EFFECT, LUX_EFFECT_PRE, LUX_EFFECT_ROOT, LUX_END_RENDER,
+    LUX_ESCAPE, LUX_ESCAPE_ATTR, LUX_EVENT_ATTR, LUX_EVENT_TARGET_ATTR, LUX_HAS_DYNAMIC,
+    LUX_INSPECT, LUX_INSPECT_TRACE, LUX_INVALIDATE, LUX_IS_BOOLEAN_ATTR, LUX_MOUNT_ACTIONS,
+    LUX_MOUNT_ANIMATIONS, LUX_MOUNT_BINDINGS, LUX_MOUNT_EVENTS, LUX_MOUNT_HTML,
+    LUX_MOUNT_TRANSITIONS, LUX_OBSERVE, LUX_ONCE, LUX_PROPS, LUX_PROPS_ID, LUX_PROXY, LUX_READ,
+    LUX_RUNTIME_CLIENT_IMPORT_SOURCE, LUX_RUNTIME_SERVER_IMPORT_SOURCE, LUX_SET, LUX_SNAPSHOT,
+    LUX_STATE, LUX_STRINGIFY, LUX_STYLE_ATTR, LUX_TEMPLATE, LUX_TRACK, LUX_TRANSITION_ATTR,
+    LUX_USE_ATTR, optional_string_expr, push_const,
 };
 use self::exports::{
-    client_default_export_statement, default_export_statements, named_export_statement,
+    ServerStream, client_default_export_statement, default_export_statements,
+    named_export_statement,
 };
 use self::script::{
-    collect_instance_runtime_statements, collect_module_runtime_statements,
-    collect_runtime_binding_names, needs_rest_props_runtime, rewrite_server_store_subscriptions,
+    collect_instance_prop_names, collect_instance_proxied_state_names,
+    collect_instance_reactive_names, collect_instance_runtime_statements,
+    collect_instance_writable_names, collect_module_runtime_statements,
+    collect_runtime_binding_names, needs_rest_props_runtime, rewrite_client_state_accesses,
+    rewrite_client_state_declarations, rewrite_server_store_subscriptions,
 };
 use super::ComponentRenderOutput;
 use super::template::{
-    RuntimeScope, StaticRenderContext, build_render_nodes_expression, render_nodes_template,
+    RuntimeScope, StaticRenderContext, build_render_nodes_expression,
+    build_stream_nodes_statements, render_nodes_template,
 };
-use crate::TransformTarget;
-use crate::css;
+use crate::{TransformOptions, TransformTarget};
 
 pub(super) fn render(
     root: &Root<'_>,
     analysis: &AnalysisTables,
     css_hash: Option<&str>,
     css_scope: Option<&str>,
-    target: TransformTarget,
+    embedded_css: Option<&str>,
+    options: &TransformOptions<'_>,
 ) -> ComponentRenderOutput {
+    let target = options.target;
+    let async_rendering = target == TransformTarget::Server && options.experimental_async;
+    let streaming = target == TransformTarget::Server && options.streaming;
     let partition = partition_top_level_nodes(&root.fragment.nodes);
+    let namespace = root
+        .options
+        .as_ref()
+        .and_then(|options| options.namespace)
+        .unwrap_or_default();
+    let preserve_whitespace = root
+        .options
+        .as_ref()
+        .and_then(|options| options.preserve_whitespace)
+        .unwrap_or(false);
     let static_render_context = StaticRenderContext {
         stylesheet: root.css.as_ref(),
         analysis,
         css_scope,
+        namespace,
+        preserve_whitespace,
     };
-    let embedded_css = root.css.as_ref().and_then(|stylesheet| {
-        css_scope.map(|scope| {
-            css::render_stylesheet_embedded(stylesheet, analysis, scope, &root.fragment)
-        })
-    });
     let template_result = render_nodes_template(&partition.body_nodes, &static_render_context);
     let head_result = render_nodes_template(&partition.head_nodes, &static_render_context);
-    let injected_css_head_html = injected_css_head_html(root, embedded_css.as_deref(), css_scope);
+    let injected_css_head_html = injected_css_head_html(root, embedded_css, css_scope);
     let has_global_target_hooks = target == TransformTarget::Client
         && partition
             .body_nodes
@@ -77,9 +98,12 @@ pub(super) fn render(
     let needs_runtime = template_result.has_dynamic
         || head_result.has_dynamic
         || needs_props_id_runtime
+        || async_rendering
+        || streaming
         || target == TransformTarget::Client;
-    let needs_runtime_import =
-        needs_runtime || needs_rest_props_runtime(root) || (target == TransformTarget::Server && has_head);
+    let needs_runtime_import = needs_runtime
+        || needs_rest_props_runtime(root)
+        || (target == TransformTarget::Server && has_head);
 
     let allocator = Allocator::default();
     let ast = AstBuilder::new(&allocator);
@@ -89,7 +113,7 @@ pub(super) fn render(
         push_runtime_helper_import(ast, &mut body, target);
     }
     push_import_declarations(ast, &mut body, root, analysis);
-    let mut module_runtime = collect_module_runtime_statements(ast, root);
+    let mut module_runtime = collect_module_runtime_statements(ast, root, target);
     if target == TransformTarget::Server {
         rewrite_server_store_subscriptions(ast, &mut module_runtime);
     }
@@ -106,7 +130,7 @@ pub(super) fn render(
         ast,
         &mut body,
         LUX_CSS,
-        optional_string_expr(ast, embedded_css.as_deref()),
+        optional_string_expr(ast, embedded_css),
     );
     push_const(
         ast,
@@ -131,7 +155,7 @@ pub(super) fn render(
     );
 
     body.push(named_export_statement(ast));
-    let mut instance_runtime = collect_instance_runtime_statements(ast, root);
+    let mut instance_runtime = collect_instance_runtime_statements(ast, root, target, options.dev);
     if target == TransformTarget::Server {
         rewrite_server_store_subscriptions(ast, &mut instance_runtime);
     }
@@ -140,15 +164,19 @@ pub(super) fn render(
     scope_names.extend(collect_runtime_binding_names(&instance_runtime));
     let scope = RuntimeScope::from_names(scope_names)
         .with_css_scope(css_scope)
-        .with_store_subscriptions(target == TransformTarget::Server);
-    let render_expression = if template_result.has_dynamic || has_global_target_hooks {
+        .with_store_subscriptions(target == TransformTarget::Server)
+        .with_tracked_instances(target == TransformTarget::Client)
+        .with_namespace(namespace)
+        .with_preserve_whitespace(preserve_whitespace)
+        .with_async(async_rendering);
+    let mut render_expression = if template_result.has_dynamic || has_global_target_hooks {
         build_render_nodes_expression(ast, &partition.body_nodes, &scope)
     } else {
         ast.expression_identifier(SPAN, ast.ident(LUX_TEMPLATE))
     };
-    let head_expression = has_head.then(|| {
+    let build_head_expression = |scope: &RuntimeScope| {
         if head_result.has_dynamic {
-            let dynamic_head = build_render_nodes_expression(ast, &partition.head_nodes, &scope);
+            let dynamic_head = build_render_nodes_expression(ast, &partition.head_nodes, scope);
             if let Some(css_head_html) = injected_css_head_html.as_deref() {
                 ast.expression_binary(
                     SPAN,
@@ -166,6 +194,33 @@ pub(super) fn render(
             };
             ast.expression_string_literal(SPAN, ast.atom(head_html.as_str()), None)
         }
+    };
+    let mut head_expression = has_head.then(|| build_head_expression(&scope));
+    if target == TransformTarget::Client {
+        rewrite_client_state_declarations(ast, root, &mut instance_runtime);
+        rewrite_client_state_accesses(
+            ast,
+            &collect_instance_writable_names(root),
+            &collect_instance_reactive_names(root),
+            &collect_instance_proxied_state_names(root),
+            &mut instance_runtime,
+            std::iter::once(&mut render_expression).chain(head_expression.as_mut()),
+        );
+    }
+    let stream = streaming.then(|| {
+        let stream_scope = scope.with_async(true);
+        let mut setup_statements =
+            collect_instance_runtime_statements(ast, root, TransformTarget::Server, false);
+        rewrite_server_store_subscriptions(ast, &mut setup_statements);
+        ServerStream {
+            body_statements: build_stream_nodes_statements(
+                ast,
+                &partition.body_nodes,
+                &stream_scope,
+            ),
+            setup_statements,
+            head_expression: has_head.then(|| build_head_expression(&stream_scope)),
+        }
     });
     match target {
         TransformTarget::Server => body.extend(default_export_statements(
@@ -174,14 +229,26 @@ pub(super) fn render(
             render_expression,
             instance_runtime,
             head_expression,
-            has_head.then(|| collect_instance_runtime_statements(ast, root)),
-        )),
-        TransformTarget::Client => body.push(client_default_export_statement(
-            ast,
-            render_expression,
-            instance_runtime,
-            head_expression,
+            async_rendering,
+            stream,
         )),
+        TransformTarget::Client => {
+            let custom_element = root
+                .options
+                .as_ref()
+                .and_then(|options| options.custom_element.as_ref());
+            let prop_names = custom_element
+                .map(|_| collect_instance_prop_names(root))
+                .unwrap_or_default();
+            body.push(client_default_export_statement(
+                ast,
+                render_expression,
+                instance_runtime,
+                head_expression,
+                namespace,
+                custom_element.map(|options| (options, prop_names.as_slice())),
+            ));
+        }
     }
 
     let program = ast.program(
@@ -476,6 +543,9 @@ fn collect_scope_import_names(analysis: &AnalysisTables) -> Vec<String> {
     names.insert(LUX_CLEANUP_MOUNT.to_string());
     names.insert(LUX_BEGIN_RENDER.to_string());
     names.insert(LUX_END_RENDER.to_string());
+    names.insert(LUX_CSS_PROPS.to_string());
+    names.insert(LUX_BOUNDARY.to_string());
+    names.insert(LUX_BLOCK.to_string());
     names.insert(LUX_EVENT_ATTR.to_string());
     names.insert(LUX_EVENT_TARGET_ATTR.to_string());
     names.insert(LUX_MOUNT_EVENTS.to_string());
@@ -485,10 +555,29 @@ fn collect_scope_import_names(analysis: &AnalysisTables) -> Vec<String> {
     names.insert(LUX_MOUNT_BINDINGS.to_string());
     names.insert(LUX_USE_ATTR.to_string());
     names.insert(LUX_MOUNT_ACTIONS.to_string());
+    names.insert(LUX_ATTACH_ATTR.to_string());
+    names.insert(LUX_ATTACHMENT_KEY.to_string());
     names.insert(LUX_TRANSITION_ATTR.to_string());
     names.insert(LUX_MOUNT_TRANSITIONS.to_string());
     names.insert(LUX_ANIMATE_ATTR.to_string());
     names.insert(LUX_MOUNT_ANIMATIONS.to_string());
+    names.insert(LUX_CREATE_CUSTOM_ELEMENT.to_string());
+    names.insert(LUX_STATE.to_string());
+    names.insert(LUX_PROPS.to_string());
+    names.insert(LUX_SET.to_string());
+    names.insert(LUX_EFFECT.to_string());
+    names.insert(LUX_EFFECT_PRE.to_string());
+    names.insert(LUX_EFFECT_ROOT.to_string());
+    names.insert(LUX_EFFECT_TRACKING.to_string());
+    names.insert(LUX_EFFECT_PENDING.to_string());
+    names.insert(LUX_READ.to_string());
+    names.insert(LUX_TRACK.to_string());
+    names.insert(LUX_PROXY.to_string());
+    names.insert(LUX_SNAPSHOT.to_string());
+    names.insert(LUX_OBSERVE.to_string());
+    names.insert(LUX_INVALIDATE.to_string());
+    names.insert(LUX_INSPECT.to_string());
+    names.insert(LUX_INSPECT_TRACE.to_string());
     for import in &analysis.script_imports {
         for name in &import.local_names {
             names.insert(name.clone());
@@ -1,19 +1,30 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use lux_ast::template::root::Root;
-use oxc_allocator::CloneIn;
+use oxc_allocator::{CloneIn, TakeIn};
 use oxc_ast::ast::{
-    AccessorProperty, Argument, ArrowFunctionExpression, AssignmentTarget,
-    AssignmentTargetMaybeDefault, AssignmentTargetProperty, BindingPattern, CallExpression,
-    CatchParameter, Class, Declaration, ExportNamedDeclaration, Expression, FormalParameter,
-    Function, MethodDefinition, PropertyDefinition, Statement, VariableDeclarator,
+    AccessorProperty, Argument, ArrayExpressionElement, ArrowFunctionExpression,
+    AssignmentExpression, AssignmentOperator, AssignmentTarget, AssignmentTargetMaybeDefault,
+    AssignmentTargetProperty, BindingIdentifier, BindingPattern, BlockStatement, CallExpression,
+    CatchClause, CatchParameter, Class, ClassElement, Declaration, ExportNamedDeclaration,
+    Expression, ForInStatement, ForOfStatement, ForStatement, ForStatementInit, ForStatementLeft,
+    FormalParameter, FormalParameterKind, FormalParameters, Function, FunctionBody, FunctionType,
+    IdentifierReference, MethodDefinition, MethodDefinitionKind, MethodDefinitionType, NumberBase,
+    ObjectProperty, PrivateIdentifier, PropertyDefinition, PropertyDefinitionType, PropertyKey,
+    PropertyKind, SimpleAssignmentTarget, Statement, VariableDeclarator,
 };
 use oxc_ast::{AstBuilder, NONE};
-use oxc_ast_visit::{VisitMut, walk_mut};
+use oxc_ast_visit::{Visit, VisitMut, walk, walk_mut};
 use oxc_span::SPAN;
 use oxc_syntax::scope::ScopeFlags;
 
-use crate::js::component::LUX_REST_PROPS;
+use super::consts::{
+    LUX_EFFECT, LUX_EFFECT_PENDING, LUX_EFFECT_PRE, LUX_EFFECT_ROOT, LUX_EFFECT_TRACKING,
+    LUX_INSPECT, LUX_INSPECT_TRACE, LUX_INVALIDATE, LUX_OBSERVE, LUX_PROXY, LUX_READ, LUX_SET,
+    LUX_SNAPSHOT, LUX_STATE, LUX_TRACK,
+};
+use crate::TransformTarget;
+use crate::js::component::{LUX_BLOCK, LUX_BOUNDARY, LUX_REST_PROPS};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum ScriptTarget {
@@ -24,6 +35,7 @@ enum ScriptTarget {
 pub(super) fn collect_module_runtime_statements<'a>(
     ast: AstBuilder<'a>,
     root: &Root<'_>,
+    target: TransformTarget,
 ) -> oxc_allocator::Vec<'a, Statement<'a>> {
     let mut statements = ast.vec();
     let Some(module_script) = &root.module else {
@@ -31,7 +43,17 @@ pub(super) fn collect_module_runtime_statements<'a>(
     };
 
     for statement in &module_script.content.body {
-        if let Some(mut statement) = sanitize_script_statement(ast, statement, ScriptTarget::Module)
+        let mut statement = statement.clone_in(ast.allocator);
+        if target == TransformTarget::Client {
+            ClientRuneRewriter {
+                ast,
+                reactive_names: None,
+                dev: false,
+            }
+            .visit_statement(&mut statement);
+        }
+        if let Some(mut statement) =
+            sanitize_script_statement(ast, &statement, ScriptTarget::Module)
         {
             strip_typescript_from_statement(ast, &mut statement);
             statements.push(statement);
@@ -44,6 +66,8 @@ pub(super) fn collect_module_runtime_statements<'a>(
 pub(super) fn collect_instance_runtime_statements<'a>(
     ast: AstBuilder<'a>,
     root: &Root<'_>,
+    target: TransformTarget,
+    dev: bool,
 ) -> oxc_allocator::Vec<'a, Statement<'a>> {
     let mut statements = ast.vec();
     let mut declared_names = BTreeSet::new();
@@ -52,8 +76,21 @@ pub(super) fn collect_instance_runtime_statements<'a>(
         return statements;
     };
     let legacy_exported_props = collect_instance_exported_prop_names(root);
+    // The server drops effects; the client runs them after rendering.
+    let instance_reactive_names =
+        (target == TransformTarget::Client).then(|| collect_instance_reactive_names(root));
 
     for statement in &instance_script.content.body {
+        let mut statement = statement.clone_in(ast.allocator);
+        if let Some(names) = &instance_reactive_names {
+            ClientRuneRewriter {
+                ast,
+                reactive_names: Some(names),
+                dev,
+            }
+            .visit_statement(&mut statement);
+        }
+        let statement = &statement;
         if let Some((names, mut statement)) = sanitize_reactive_statement(ast, statement) {
             strip_typescript_from_statement(ast, &mut statement);
             reactive_names.extend(names);
@@ -99,6 +136,42 @@ pub(super) fn collect_runtime_binding_names(statements: &[Statement<'_>]) -> Vec
     names.into_iter().collect()
 }
 
+pub(super) fn collect_instance_prop_names(root: &Root<'_>) -> Vec<String> {
+    let mut names = collect_instance_exported_prop_names(root)
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    let Some(instance_script) = &root.instance else {
+        return names.into_iter().collect();
+    };
+
+    for statement in &instance_script.content.body {
+        let Statement::VariableDeclaration(declaration) = statement else {
+            continue;
+        };
+        for declarator in &declaration.declarations {
+            let Some(init) = &declarator.init else {
+                continue;
+            };
+            let Expression::CallExpression(call) = init.get_inner_expression() else {
+                continue;
+            };
+            if extract_rune_name(&call.callee).as_deref() != Some("$props") {
+                continue;
+            }
+            let BindingPattern::ObjectPattern(pattern) = &declarator.id else {
+                continue;
+            };
+            for property in &pattern.properties {
+                if let Some(name) = property.key.static_name() {
+                    names.insert(name.into_owned());
+                }
+            }
+        }
+    }
+
+    names.into_iter().collect()
+}
+
 pub(super) fn needs_rest_props_runtime(root: &Root<'_>) -> bool {
     !collect_instance_exported_prop_names(root).is_empty()
 }
@@ -113,6 +186,449 @@ pub(super) fn rewrite_server_store_subscriptions<'a>(
     }
 }
 
+/// Local names of the instance `$state` bindings and props, mapped to the
+/// state or prop name that the client runtime stores their writes under.
+pub(super) fn collect_instance_writable_names(root: &Root<'_>) -> BTreeMap<String, String> {
+    let mut names = collect_instance_exported_prop_names(root)
+        .into_iter()
+        .chain(collect_instance_state_names(
+            root,
+            &["$state", "$state.raw"],
+        ))
+        .map(|name| (name.clone(), name))
+        .collect::<BTreeMap<_, _>>();
+    let Some(instance_script) = &root.instance else {
+        return names;
+    };
+
+    for statement in &instance_script.content.body {
+        let Statement::VariableDeclaration(declaration) = statement else {
+            continue;
+        };
+        for declarator in &declaration.declarations {
+            if rune_initializer_name(declarator).as_deref() != Some("$props") {
+                continue;
+            }
+            let BindingPattern::ObjectPattern(pattern) = &declarator.id else {
+                continue;
+            };
+            for property in &pattern.properties {
+                let Some(key) = property.key.static_name() else {
+                    continue;
+                };
+                if let Some(local) = binding_identifier_with_default(&property.value) {
+                    names.insert(local.name.as_str().to_owned(), key.into_owned());
+                }
+            }
+        }
+    }
+
+    names
+}
+
+/// Names of the instance state, props and deriveds, which are the bindings
+/// whose changes run effects again.
+pub(super) fn collect_instance_reactive_names(root: &Root<'_>) -> BTreeSet<String> {
+    let mut names = collect_instance_writable_names(root)
+        .into_keys()
+        .collect::<BTreeSet<_>>();
+    let Some(instance_script) = &root.instance else {
+        return names;
+    };
+
+    for statement in &instance_script.content.body {
+        let Statement::VariableDeclaration(declaration) = statement else {
+            continue;
+        };
+        for declarator in &declaration.declarations {
+            if !matches!(
+                rune_initializer_name(declarator).as_deref(),
+                Some("$derived" | "$derived.by")
+            ) {
+                continue;
+            }
+            collect_binding_pattern_names(&declarator.id, &mut names);
+        }
+    }
+
+    names
+}
+
+/// Makes the top-level `$state` declarations of the instance read the value
+/// that the component instance holds from its previous render.
+pub(super) fn rewrite_client_state_declarations<'a>(
+    ast: AstBuilder<'a>,
+    root: &Root<'_>,
+    statements: &mut oxc_allocator::Vec<'a, Statement<'a>>,
+) {
+    let state_names = collect_instance_state_names(root, &["$state", "$state.raw"]);
+    if state_names.is_empty() {
+        return;
+    }
+
+    for statement in statements.iter_mut() {
+        let Statement::VariableDeclaration(declaration) = statement else {
+            continue;
+        };
+        for declarator in &mut declaration.declarations {
+            let BindingPattern::BindingIdentifier(identifier) = &declarator.id else {
+                continue;
+            };
+            let name = identifier.name.as_str();
+            if !state_names.contains(name) {
+                continue;
+            }
+            let initial = declarator
+                .init
+                .take()
+                .unwrap_or_else(|| ast.expression_identifier(SPAN, ast.ident("undefined")));
+            declarator.init = Some(
+                ast.expression_call(
+                    SPAN,
+                    ast.expression_identifier(SPAN, ast.ident(LUX_STATE)),
+                    NONE,
+                    ast.vec_from_array([
+                        ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+                            .into(),
+                        ast.expression_string_literal(SPAN, ast.atom(name), None)
+                            .into(),
+                        initial.into(),
+                    ]),
+                    false,
+                ),
+            );
+        }
+    }
+}
+
+/// Names of the top-level `$state` bindings, whose values are deeply
+/// reactive proxies. `$state.raw` values are left as they are.
+pub(super) fn collect_instance_proxied_state_names(root: &Root<'_>) -> BTreeSet<String> {
+    collect_instance_state_names(root, &["$state"])
+}
+
+/// Wraps assignments to the given state and prop bindings in `__lux_set`,
+/// which stores the written values and schedules a render of the component.
+/// Values assigned to `proxied` bindings are made reactive first. Reads of
+/// the `reactive` bindings from functions go through `__lux_read`, so that
+/// the effects calling them depend on the bindings, and the instance hands
+/// their current values to `__lux_track` to compare those reads against.
+pub(super) fn rewrite_client_state_accesses<'a, 'r>(
+    ast: AstBuilder<'a>,
+    names: &BTreeMap<String, String>,
+    reactive: &BTreeSet<String>,
+    proxied: &BTreeSet<String>,
+    statements: &mut oxc_allocator::Vec<'a, Statement<'a>>,
+    expressions: impl IntoIterator<Item = &'r mut Expression<'a>>,
+) where
+    'a: 'r,
+{
+    if reactive.is_empty() {
+        return;
+    }
+
+    let mut rewriter = ClientStateAccessRewriter {
+        ast,
+        names,
+        reactive,
+        proxied,
+        shadowed: Vec::new(),
+        function_depth: 0,
+        invoked_immediately: false,
+    };
+    for statement in statements.iter_mut() {
+        rewriter.visit_statement(statement);
+    }
+    for expression in expressions {
+        rewriter.visit_expression(expression);
+    }
+    statements.push(track_statement(ast, reactive));
+}
+
+/// `__lux_track(__lux_render_state, () => ({ name, ... }))`
+fn track_statement<'a>(ast: AstBuilder<'a>, names: &BTreeSet<String>) -> Statement<'a> {
+    let mut properties = ast.vec_with_capacity(names.len());
+    for name in names {
+        properties.push(ast.object_property_kind_object_property(
+            SPAN,
+            PropertyKind::Init,
+            ast.property_key_static_identifier(SPAN, ast.ident(name)),
+            ast.expression_identifier(SPAN, ast.ident(name)),
+            false,
+            true,
+            false,
+        ));
+    }
+    ast.statement_expression(
+        SPAN,
+        ast.expression_call(
+            SPAN,
+            ast.expression_identifier(SPAN, ast.ident(LUX_TRACK)),
+            NONE,
+            ast.vec_from_array([
+                ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+                    .into(),
+                expression_getter(ast, ast.expression_object(SPAN, properties)).into(),
+            ]),
+            false,
+        ),
+    )
+}
+
+fn collect_instance_state_names(root: &Root<'_>, runes: &[&str]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let Some(instance_script) = &root.instance else {
+        return names;
+    };
+
+    for statement in &instance_script.content.body {
+        let Statement::VariableDeclaration(declaration) = statement else {
+            continue;
+        };
+        for declarator in &declaration.declarations {
+            if !rune_initializer_name(declarator).is_some_and(|name| runes.contains(&name.as_str()))
+            {
+                continue;
+            }
+            if let BindingPattern::BindingIdentifier(identifier) = &declarator.id {
+                names.insert(identifier.name.as_str().to_owned());
+            }
+        }
+    }
+
+    names
+}
+
+fn rune_initializer_name(declarator: &VariableDeclarator<'_>) -> Option<String> {
+    let Expression::CallExpression(call) = declarator.init.as_ref()?.get_inner_expression() else {
+        return None;
+    };
+    extract_rune_name(&call.callee)
+}
+
+/// Returns the name of a class field declared with `$state` or `$state.raw`,
+/// and whether its values are proxied.
+fn state_field(definition: &PropertyDefinition<'_>) -> Option<(String, bool)> {
+    if definition.computed || definition.r#static {
+        return None;
+    }
+    let PropertyKey::StaticIdentifier(key) = &definition.key else {
+        return None;
+    };
+    let proxied = state_rune_proxied(definition.value.as_ref()?)?;
+    Some((key.name.as_str().to_owned(), proxied))
+}
+
+/// Whether `value` is a `$state` call, whose values are proxied, or a
+/// `$state.raw` call.
+fn state_rune_proxied(value: &Expression<'_>) -> Option<bool> {
+    let Expression::CallExpression(call) = value.get_inner_expression() else {
+        return None;
+    };
+    match extract_rune_name(&call.callee)?.as_str() {
+        "$state" => Some(true),
+        "$state.raw" => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns the name of a field declared in the constructor with
+/// `this.name = $state(...)`, and whether its values are proxied.
+fn constructor_state_field(statement: &Statement<'_>) -> Option<(String, bool)> {
+    let Statement::ExpressionStatement(statement) = statement else {
+        return None;
+    };
+    let Expression::AssignmentExpression(assignment) = &statement.expression else {
+        return None;
+    };
+    if assignment.operator != AssignmentOperator::Assign {
+        return None;
+    }
+    let AssignmentTarget::StaticMemberExpression(member) = &assignment.left else {
+        return None;
+    };
+    if !matches!(
+        member.object.get_inner_expression(),
+        Expression::ThisExpression(_)
+    ) {
+        return None;
+    }
+    let proxied = state_rune_proxied(&assignment.right)?;
+    Some((member.property.name.as_str().to_owned(), proxied))
+}
+
+/// Picks the name of the private field backing a state field, avoiding the
+/// private names that the class already uses.
+fn private_state_field_name(name: &str, taken: &mut BTreeSet<String>) -> String {
+    let mut private_name = name.to_owned();
+    let mut suffix = 1;
+    while taken.contains(&private_name) {
+        private_name = format!("{name}_{suffix}");
+        suffix += 1;
+    }
+    taken.insert(private_name.clone());
+    private_name
+}
+
+fn should_proxy(argument: &Argument<'_>) -> bool {
+    argument.as_expression().is_none_or(should_proxy_expression)
+}
+
+/// Whether the value of `expression` can be an object or an array.
+fn should_proxy_expression(expression: &Expression<'_>) -> bool {
+    match expression.get_inner_expression() {
+        Expression::Identifier(identifier) => identifier.name != "undefined",
+        expression => !matches!(
+            expression,
+            Expression::BooleanLiteral(_)
+                | Expression::NullLiteral(_)
+                | Expression::NumericLiteral(_)
+                | Expression::BigIntLiteral(_)
+                | Expression::StringLiteral(_)
+                | Expression::TemplateLiteral(_)
+                | Expression::ArrowFunctionExpression(_)
+                | Expression::FunctionExpression(_)
+                | Expression::UnaryExpression(_)
+                | Expression::BinaryExpression(_)
+                | Expression::UpdateExpression(_)
+        ),
+    }
+}
+
+/// `get name() { __lux_observe(this); return this.#private_name; }`
+fn state_field_getter<'a>(ast: AstBuilder<'a>, name: &str, private_name: &str) -> ClassElement<'a> {
+    let observe = ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident(LUX_OBSERVE)),
+        NONE,
+        ast.vec1(ast.expression_this(SPAN).into()),
+        false,
+    );
+    let body = ast.alloc_function_body(
+        SPAN,
+        ast.vec(),
+        ast.vec_from_array([
+            ast.statement_expression(SPAN, observe),
+            ast.statement_return(SPAN, Some(private_field_expression(ast, private_name))),
+        ]),
+    );
+    state_field_accessor(ast, name, MethodDefinitionKind::Get, ast.vec(), body)
+}
+
+/// `set name(value) { this.#private_name = __lux_proxy(value); __lux_invalidate(this); }`
+fn state_field_setter<'a>(
+    ast: AstBuilder<'a>,
+    name: &str,
+    private_name: &str,
+    proxied: bool,
+) -> ClassElement<'a> {
+    let mut value = ast.expression_identifier(SPAN, ast.ident("value"));
+    if proxied {
+        value = ast.expression_call(
+            SPAN,
+            ast.expression_identifier(SPAN, ast.ident(LUX_PROXY)),
+            NONE,
+            ast.vec1(value.into()),
+            false,
+        );
+    }
+    let assignment = ast.expression_assignment(
+        SPAN,
+        AssignmentOperator::Assign,
+        AssignmentTarget::PrivateFieldExpression(ast.alloc_private_field_expression(
+            SPAN,
+            ast.expression_this(SPAN),
+            ast.private_identifier(SPAN, ast.ident(private_name)),
+            false,
+        )),
+        value,
+    );
+    let invalidate = ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident(LUX_INVALIDATE)),
+        NONE,
+        ast.vec1(ast.expression_this(SPAN).into()),
+        false,
+    );
+    let body = ast.alloc_function_body(
+        SPAN,
+        ast.vec(),
+        ast.vec_from_array([
+            ast.statement_expression(SPAN, assignment),
+            ast.statement_expression(SPAN, invalidate),
+        ]),
+    );
+    let params = ast.vec1(ast.formal_parameter(
+        SPAN,
+        ast.vec(),
+        ast.binding_pattern_binding_identifier(SPAN, ast.ident("value")),
+        NONE,
+        NONE,
+        false,
+        None,
+        false,
+        false,
+    ));
+    state_field_accessor(ast, name, MethodDefinitionKind::Set, params, body)
+}
+
+fn state_field_accessor<'a>(
+    ast: AstBuilder<'a>,
+    name: &str,
+    kind: MethodDefinitionKind,
+    params: oxc_allocator::Vec<'a, FormalParameter<'a>>,
+    body: oxc_allocator::Box<'a, FunctionBody<'a>>,
+) -> ClassElement<'a> {
+    let function = ast.alloc_function(
+        SPAN,
+        FunctionType::FunctionExpression,
+        None,
+        false,
+        false,
+        false,
+        NONE,
+        NONE,
+        ast.alloc_formal_parameters(SPAN, FormalParameterKind::FormalParameter, params, NONE),
+        NONE,
+        Some(body),
+    );
+    ast.class_element_method_definition(
+        SPAN,
+        MethodDefinitionType::MethodDefinition,
+        ast.vec(),
+        ast.property_key_static_identifier(SPAN, ast.ident(name)),
+        function,
+        kind,
+        false,
+        false,
+        false,
+        false,
+        None,
+    )
+}
+
+fn private_field_expression<'a>(ast: AstBuilder<'a>, name: &str) -> Expression<'a> {
+    Expression::PrivateFieldExpression(ast.alloc_private_field_expression(
+        SPAN,
+        ast.expression_this(SPAN),
+        ast.private_identifier(SPAN, ast.ident(name)),
+        false,
+    ))
+}
+
+fn binding_identifier_with_default<'p>(
+    pattern: &'p BindingPattern<'_>,
+) -> Option<&'p BindingIdentifier<'p>> {
+    match pattern {
+        BindingPattern::BindingIdentifier(identifier) => Some(identifier),
+        BindingPattern::AssignmentPattern(pattern) => match &pattern.left {
+            BindingPattern::BindingIdentifier(identifier) => Some(identifier),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn sanitize_reactive_statement<'a>(
     ast: AstBuilder<'a>,
     statement: &Statement<'_>,
@@ -384,18 +900,31 @@ fn is_discardable_rune_expression(expression: &Expression<'_>) -> bool {
         return false;
     };
 
-    matches!(
-        extract_rune_name(&call.callee).as_deref(),
-        Some(
-            "$effect"
-                | "$effect.pre"
-                | "$effect.tracking"
-                | "$effect.root"
-                | "$effect.pending"
-                | "$inspect"
-                | "$inspect.trace"
+    is_inspect_with_call(call)
+        || matches!(
+            extract_rune_name(&call.callee).as_deref(),
+            Some(
+                "$effect"
+                    | "$effect.pre"
+                    | "$effect.tracking"
+                    | "$effect.root"
+                    | "$effect.pending"
+                    | "$inspect"
+                    | "$inspect.trace"
+            )
         )
-    )
+}
+
+/// `$inspect(...).with(callback)`
+fn is_inspect_with_call(call: &CallExpression<'_>) -> bool {
+    let Expression::StaticMemberExpression(member) = &call.callee else {
+        return false;
+    };
+    let Expression::CallExpression(inspect) = &member.object else {
+        return false;
+    };
+    member.property.name == "with"
+        && extract_rune_name(&inspect.callee).as_deref() == Some("$inspect")
 }
 
 fn rewrite_rune_call_expression<'a>(
@@ -405,10 +934,13 @@ fn rewrite_rune_call_expression<'a>(
     let Expression::CallExpression(call) = expression else {
         return None;
     };
+    if is_inspect_with_call(call) {
+        return Some(ast.expression_identifier(SPAN, ast.ident("undefined")));
+    }
 
     let name = extract_rune_name(&call.callee)?;
     match name.as_str() {
-        "$state" | "$state.raw" | "$derived" | "$state.snapshot" => Some(
+        "$state" | "$state.raw" | "$derived" | "$state.snapshot" | "$bindable" => Some(
             first_call_argument_expression(ast, call)
                 .unwrap_or_else(|| ast.expression_identifier(SPAN, ast.ident("undefined"))),
         ),
@@ -425,8 +957,8 @@ fn rewrite_rune_call_expression<'a>(
             ast.vec(),
             false,
         )),
-        "$bindable" | "$effect" | "$effect.pre" | "$effect.tracking" | "$effect.root"
-        | "$effect.pending" | "$inspect" | "$inspect.trace" | "$host" => {
+        "$effect" | "$effect.pre" | "$effect.tracking" | "$effect.root" | "$effect.pending"
+        | "$inspect" | "$inspect.trace" | "$host" => {
             Some(ast.expression_identifier(SPAN, ast.ident("undefined")))
         }
         _ => None,
@@ -476,6 +1008,44 @@ struct TypeScriptEraser<'a> {
     ast: AstBuilder<'a>,
 }
 
+struct ClientStateAccessRewriter<'a, 'n> {
+    ast: AstBuilder<'a>,
+    names: &'n BTreeMap<String, String>,
+    reactive: &'n BTreeSet<String>,
+    proxied: &'n BTreeSet<String>,
+    /// Names declared by the enclosing functions and blocks, which hide the
+    /// instance bindings of the same name.
+    shadowed: Vec<BTreeSet<String>>,
+    /// Functions entered that run later than the code around them, unlike
+    /// the ones called right where they are defined.
+    function_depth: usize,
+    invoked_immediately: bool,
+}
+
+/// Turns the state and effect runes into calls to the client runtime.
+/// `$state` values become deeply reactive proxies, and class fields declared
+/// with `$state` become accessors that schedule a render when written.
+/// Effects are left alone outside of the instance script, whose
+/// `reactive_names` `$inspect.trace` reports, and so is `$inspect` outside
+/// of dev mode.
+struct ClientRuneRewriter<'a, 'n> {
+    ast: AstBuilder<'a>,
+    reactive_names: Option<&'n BTreeSet<String>>,
+    dev: bool,
+}
+
+/// The reactive bindings that a function traced with `$inspect.trace` reads.
+struct TracedReadCollector<'n> {
+    names: &'n BTreeSet<String>,
+    dependencies: BTreeSet<String>,
+}
+
+/// The private names that a class declares or refers to.
+#[derive(Default)]
+struct PrivateNameCollector {
+    names: BTreeSet<String>,
+}
+
 struct ServerStoreSubscriptionRewriter<'a> {
     ast: AstBuilder<'a>,
 }
@@ -576,6 +1146,372 @@ impl<'a> VisitMut<'a> for TypeScriptEraser<'a> {
     }
 }
 
+impl<'a> ClientRuneRewriter<'a, '_> {
+    /// Rewrites the `this.name = $state(...)` statements of the constructor
+    /// to assign the private field backing `name`, and returns the fields
+    /// with their private names and whether their values are proxied.
+    fn rewrite_constructor_state_fields(
+        &self,
+        class: &mut Class<'a>,
+        taken: &mut BTreeSet<String>,
+    ) -> Vec<(String, String, bool)> {
+        let ast = self.ast;
+        let mut fields: Vec<(String, String, bool)> = Vec::new();
+        let constructor = class
+            .body
+            .body
+            .iter_mut()
+            .find_map(|element| match element {
+                ClassElement::MethodDefinition(method)
+                    if method.kind == MethodDefinitionKind::Constructor =>
+                {
+                    method.value.body.as_deref_mut()
+                }
+                _ => None,
+            });
+        let Some(body) = constructor else {
+            return fields;
+        };
+        for statement in body.statements.iter_mut() {
+            let Some((name, proxied)) = constructor_state_field(statement) else {
+                continue;
+            };
+            let Statement::ExpressionStatement(statement) = statement else {
+                continue;
+            };
+            let Expression::AssignmentExpression(assignment) = &mut statement.expression else {
+                continue;
+            };
+            let private_name = match fields.iter().find(|(field, _, _)| *field == name) {
+                Some((_, private_name, _)) => private_name.clone(),
+                None => {
+                    let private_name = private_state_field_name(&name, taken);
+                    fields.push((name, private_name.clone(), proxied));
+                    private_name
+                }
+            };
+            assignment.left =
+                AssignmentTarget::PrivateFieldExpression(ast.alloc_private_field_expression(
+                    SPAN,
+                    ast.expression_this(SPAN),
+                    ast.private_identifier(SPAN, ast.ident(&private_name)),
+                    false,
+                ));
+        }
+        fields
+    }
+
+    /// `__lux_inspect(__lux_render_state, site, [values], callback)`, where
+    /// `site` tells the `$inspect` calls of a component apart.
+    fn inspect_call(
+        &self,
+        site: u32,
+        values: oxc_allocator::Vec<'a, Argument<'a>>,
+        callback: Option<Argument<'a>>,
+    ) -> Expression<'a> {
+        let ast = self.ast;
+        let mut elements = ast.vec_with_capacity(values.len());
+        for value in values {
+            elements.push(match value {
+                Argument::SpreadElement(spread) => ArrayExpressionElement::SpreadElement(spread),
+                value => value.into_expression().into(),
+            });
+        }
+        let mut arguments = ast.vec_from_array([
+            ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+                .into(),
+            site_literal(ast, site).into(),
+            ast.expression_array(SPAN, elements).into(),
+        ]);
+        arguments.extend(callback);
+        ast.expression_call(
+            SPAN,
+            ast.expression_identifier(SPAN, ast.ident(LUX_INSPECT)),
+            NONE,
+            arguments,
+            false,
+        )
+    }
+
+    /// Replaces a leading `$inspect.trace(label)` with a call that logs the
+    /// reactive bindings read by the function which changed since its last
+    /// run.
+    fn rewrite_inspect_trace(&self, body: &mut FunctionBody<'a>, name: Option<&str>) {
+        let Some(names) = self.reactive_names.filter(|_| self.dev) else {
+            return;
+        };
+        let Some((first, rest)) = body.statements.split_first_mut() else {
+            return;
+        };
+        let Statement::ExpressionStatement(statement) = first else {
+            return;
+        };
+        let Expression::CallExpression(call) = &mut statement.expression else {
+            return;
+        };
+        if extract_rune_name(&call.callee).as_deref() != Some("$inspect.trace") {
+            return;
+        }
+
+        let ast = self.ast;
+        let mut collector = TracedReadCollector {
+            names,
+            dependencies: BTreeSet::new(),
+        };
+        for statement in rest.iter() {
+            collector.visit_statement(statement);
+        }
+        let mut properties = ast.vec();
+        for name in &collector.dependencies {
+            properties.push(ast.object_property_kind_object_property(
+                SPAN,
+                PropertyKind::Init,
+                ast.property_key_static_identifier(SPAN, ast.ident(name)),
+                ast.expression_identifier(SPAN, ast.ident(name)),
+                false,
+                true,
+                false,
+            ));
+        }
+        let label = call
+            .arguments
+            .take_in(ast.allocator)
+            .into_iter()
+            .find(|argument| !matches!(argument, Argument::SpreadElement(_)))
+            .map(Argument::into_expression)
+            .unwrap_or_else(|| {
+                ast.expression_string_literal(SPAN, ast.atom(name.unwrap_or("(anonymous)")), None)
+            });
+        statement.expression = ast.expression_call(
+            SPAN,
+            ast.expression_identifier(SPAN, ast.ident(LUX_INSPECT_TRACE)),
+            NONE,
+            ast.vec_from_array([
+                ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+                    .into(),
+                site_literal(ast, call.span.start).into(),
+                label.into(),
+                expression_getter(ast, ast.expression_object(SPAN, properties)).into(),
+            ]),
+            false,
+        );
+    }
+}
+
+/// `() => value`
+fn expression_getter<'a>(ast: AstBuilder<'a>, value: Expression<'a>) -> Expression<'a> {
+    ast.expression_arrow_function(
+        SPAN,
+        true,
+        false,
+        NONE,
+        ast.alloc_formal_parameters(
+            SPAN,
+            FormalParameterKind::ArrowFormalParameters,
+            ast.vec(),
+            NONE,
+        ),
+        NONE,
+        ast.alloc_function_body(
+            SPAN,
+            ast.vec(),
+            ast.vec1(ast.statement_expression(SPAN, value)),
+        ),
+    )
+}
+
+fn site_literal<'a>(ast: AstBuilder<'a>, site: u32) -> Expression<'a> {
+    ast.expression_numeric_literal(SPAN, f64::from(site), None, NumberBase::Decimal)
+}
+
+impl<'a> VisitMut<'a> for ClientRuneRewriter<'a, '_> {
+    fn visit_class(&mut self, class: &mut Class<'a>) {
+        let ast = self.ast;
+        let mut private_names = PrivateNameCollector::default();
+        private_names.visit_class(class);
+        let mut taken = private_names.names;
+        let constructor_fields = self.rewrite_constructor_state_fields(class, &mut taken);
+
+        let elements = class.body.body.take_in(ast.allocator);
+        let mut accessors = ast.vec_with_capacity(elements.len());
+        for element in elements {
+            let mut definition = match element {
+                ClassElement::MethodDefinition(method)
+                    if method.kind == MethodDefinitionKind::Constructor =>
+                {
+                    // Fields declared in the constructor are backed by
+                    // private fields declared along with it.
+                    for (name, private_name, proxied) in &constructor_fields {
+                        accessors.push(ast.class_element_property_definition(
+                            SPAN,
+                            PropertyDefinitionType::PropertyDefinition,
+                            ast.vec(),
+                            ast.property_key_private_identifier(SPAN, ast.ident(private_name)),
+                            NONE,
+                            None,
+                            false,
+                            false,
+                            false,
+                            false,
+                            false,
+                            false,
+                            false,
+                            None,
+                        ));
+                        accessors.push(state_field_getter(ast, name, private_name));
+                        accessors.push(state_field_setter(ast, name, private_name, *proxied));
+                    }
+                    accessors.push(ClassElement::MethodDefinition(method));
+                    continue;
+                }
+                ClassElement::PropertyDefinition(definition) => definition,
+                element => {
+                    accessors.push(element);
+                    continue;
+                }
+            };
+            // A plain declaration of a field that the constructor declares
+            // with `$state` would hide its accessors.
+            if definition.value.is_none()
+                && !definition.computed
+                && !definition.r#static
+                && let PropertyKey::StaticIdentifier(key) = &definition.key
+                && constructor_fields
+                    .iter()
+                    .any(|(name, _, _)| name == key.name.as_str())
+            {
+                continue;
+            }
+            let Some((name, proxied)) = state_field(&definition) else {
+                accessors.push(ClassElement::PropertyDefinition(definition));
+                continue;
+            };
+            let private_name = private_state_field_name(&name, &mut taken);
+            definition.key = ast.property_key_private_identifier(SPAN, ast.ident(&private_name));
+            accessors.push(ClassElement::PropertyDefinition(definition));
+            accessors.push(state_field_getter(ast, &name, &private_name));
+            accessors.push(state_field_setter(ast, &name, &private_name, proxied));
+        }
+        class.body.body = accessors;
+        walk_mut::walk_class(self, class);
+    }
+
+    fn visit_function(&mut self, function: &mut Function<'a>, flags: ScopeFlags) {
+        if let Some(body) = function.body.as_deref_mut() {
+            let name = function.id.as_ref().map(|id| id.name.as_str());
+            self.rewrite_inspect_trace(body, name);
+        }
+        walk_mut::walk_function(self, function, flags);
+    }
+
+    fn visit_arrow_function_expression(&mut self, expression: &mut ArrowFunctionExpression<'a>) {
+        self.rewrite_inspect_trace(&mut expression.body, None);
+        walk_mut::walk_arrow_function_expression(self, expression);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression<'a>) {
+        if self.dev
+            && self.reactive_names.is_some()
+            && let Expression::CallExpression(call) = expression
+            && is_inspect_with_call(call)
+        {
+            let callback = call
+                .arguments
+                .take_in(self.ast.allocator)
+                .into_iter()
+                .next();
+            if let Expression::StaticMemberExpression(member) = &mut call.callee
+                && let Expression::CallExpression(inspect) = &mut member.object
+            {
+                let values = inspect.arguments.take_in(self.ast.allocator);
+                *expression = self.inspect_call(inspect.span.start, values, callback);
+            }
+        }
+        walk_mut::walk_expression(self, expression);
+
+        let Expression::CallExpression(call) = expression else {
+            return;
+        };
+        let Some(name) = extract_rune_name(&call.callee) else {
+            return;
+        };
+        if self.dev && self.reactive_names.is_some() && name == "$inspect" {
+            let values = call.arguments.take_in(self.ast.allocator);
+            *expression = self.inspect_call(call.span.start, values, None);
+            return;
+        }
+        let ast = self.ast;
+        let render_state = ast.expression_identifier(SPAN, ast.ident("__lux_render_state"));
+        let mut arguments = ast.vec();
+        let callee = match (name.as_str(), self.reactive_names) {
+            ("$state", _) if call.arguments.first().is_some_and(should_proxy) => {
+                arguments.extend(call.arguments.take_in(ast.allocator));
+                arguments.truncate(1);
+                LUX_PROXY
+            }
+            ("$state.snapshot", _) if !call.arguments.is_empty() => {
+                arguments.extend(call.arguments.take_in(ast.allocator));
+                arguments.truncate(1);
+                LUX_SNAPSHOT
+            }
+            ("$effect" | "$effect.pre", Some(_)) => {
+                arguments.push(render_state.into());
+                arguments.extend(call.arguments.take_in(ast.allocator));
+                arguments.truncate(2);
+                if name == "$effect" {
+                    LUX_EFFECT
+                } else {
+                    LUX_EFFECT_PRE
+                }
+            }
+            ("$effect.root", Some(_)) => {
+                arguments.push(render_state.into());
+                arguments.extend(call.arguments.take_in(ast.allocator));
+                LUX_EFFECT_ROOT
+            }
+            ("$effect.tracking", Some(_)) => LUX_EFFECT_TRACKING,
+            ("$effect.pending", Some(_)) => LUX_EFFECT_PENDING,
+            _ => return,
+        };
+        *expression = ast.expression_call(
+            SPAN,
+            ast.expression_identifier(SPAN, ast.ident(callee)),
+            NONE,
+            arguments,
+            false,
+        );
+    }
+}
+
+impl<'a> Visit<'a> for TracedReadCollector<'_> {
+    fn visit_identifier_reference(&mut self, identifier: &IdentifierReference<'a>) {
+        let name = identifier.name.as_str();
+        if self.names.contains(name) {
+            self.dependencies.insert(name.to_owned());
+        }
+    }
+
+    fn visit_assignment_expression(&mut self, expression: &AssignmentExpression<'a>) {
+        // Only writing to a binding does not make it a dependency.
+        if expression.operator == AssignmentOperator::Assign
+            && matches!(
+                expression.left,
+                AssignmentTarget::AssignmentTargetIdentifier(_)
+            )
+        {
+            self.visit_expression(&expression.right);
+            return;
+        }
+        walk::walk_assignment_expression(self, expression);
+    }
+}
+
+impl<'a> Visit<'a> for PrivateNameCollector {
+    fn visit_private_identifier(&mut self, identifier: &PrivateIdentifier<'a>) {
+        self.names.insert(identifier.name.as_str().to_owned());
+    }
+}
+
 impl<'a> VisitMut<'a> for ServerStoreSubscriptionRewriter<'a> {
     fn visit_expression(&mut self, expression: &mut Expression<'a>) {
         walk_mut::walk_expression(self, expression);
@@ -610,6 +1546,277 @@ impl<'a> VisitMut<'a> for ServerStoreSubscriptionRewriter<'a> {
     }
 }
 
+impl<'a> ClientStateAccessRewriter<'a, '_> {
+    fn push_function_scope(
+        &mut self,
+        params: &FormalParameters<'_>,
+        body: Option<&FunctionBody<'_>>,
+    ) {
+        let mut names = BTreeSet::new();
+        for parameter in &params.items {
+            collect_binding_pattern_names(&parameter.pattern, &mut names);
+        }
+        if let Some(rest) = &params.rest {
+            collect_binding_pattern_names(&rest.rest.argument, &mut names);
+        }
+        for statement in body.into_iter().flat_map(|body| &body.statements) {
+            collect_statement_binding_names(statement, &mut names);
+        }
+        self.shadowed.push(names);
+    }
+
+    fn push_block_scope(&mut self, statements: &[Statement<'_>]) {
+        let mut names = BTreeSet::new();
+        for statement in statements {
+            collect_statement_binding_names(statement, &mut names);
+        }
+        self.shadowed.push(names);
+    }
+
+    fn is_shadowed(&self, name: &str) -> bool {
+        self.shadowed.iter().any(|names| names.contains(name))
+    }
+
+    fn is_instance_binding(&self, name: &str) -> bool {
+        self.names.contains_key(name) && !self.is_shadowed(name)
+    }
+
+    /// `__lux_read(__lux_render_state, "name", name)`
+    fn tracked_read(&self, name: &str) -> Expression<'a> {
+        let ast = self.ast;
+        ast.expression_call(
+            SPAN,
+            ast.expression_identifier(SPAN, ast.ident(LUX_READ)),
+            NONE,
+            ast.vec_from_array([
+                ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+                    .into(),
+                ast.expression_string_literal(SPAN, ast.atom(name), None)
+                    .into(),
+                ast.expression_identifier(SPAN, ast.ident(name)).into(),
+            ]),
+            false,
+        )
+    }
+
+    fn proxy_assigned_value(&self, assignment: &mut AssignmentExpression<'a>) {
+        let AssignmentTarget::AssignmentTargetIdentifier(identifier) = &assignment.left else {
+            return;
+        };
+        let name = identifier.name.as_str();
+        if !self.proxied.contains(name)
+            || !self.is_instance_binding(name)
+            || !matches!(
+                assignment.operator,
+                AssignmentOperator::Assign
+                    | AssignmentOperator::LogicalOr
+                    | AssignmentOperator::LogicalAnd
+                    | AssignmentOperator::LogicalNullish
+            )
+        {
+            return;
+        }
+        if !should_proxy_expression(&assignment.right) {
+            return;
+        }
+        let ast = self.ast;
+        let value = assignment.right.take_in(ast.allocator);
+        assignment.right = ast.expression_call(
+            SPAN,
+            ast.expression_identifier(SPAN, ast.ident(LUX_PROXY)),
+            NONE,
+            ast.vec1(value.into()),
+            false,
+        );
+    }
+
+    fn written_values(&self, names: &BTreeSet<String>) -> Option<Expression<'a>> {
+        let ast = self.ast;
+        let mut properties = ast.vec();
+        for name in names.iter().filter(|name| self.is_instance_binding(name)) {
+            let key = &self.names[name];
+            properties.push(ast.object_property_kind_object_property(
+                SPAN,
+                PropertyKind::Init,
+                ast.property_key_static_identifier(SPAN, ast.ident(key)),
+                ast.expression_identifier(SPAN, ast.ident(name)),
+                false,
+                key == name,
+                false,
+            ));
+        }
+        (!properties.is_empty()).then(|| ast.expression_object(SPAN, properties))
+    }
+}
+
+impl<'a> VisitMut<'a> for ClientStateAccessRewriter<'a, '_> {
+    fn visit_function(&mut self, function: &mut Function<'a>, flags: ScopeFlags) {
+        self.push_function_scope(&function.params, function.body.as_deref());
+        let deferred = usize::from(!std::mem::take(&mut self.invoked_immediately));
+        self.function_depth += deferred;
+        walk_mut::walk_function(self, function, flags);
+        self.function_depth -= deferred;
+        self.shadowed.pop();
+    }
+
+    fn visit_arrow_function_expression(&mut self, expression: &mut ArrowFunctionExpression<'a>) {
+        self.push_function_scope(&expression.params, Some(&expression.body));
+        let deferred = usize::from(!std::mem::take(&mut self.invoked_immediately));
+        self.function_depth += deferred;
+        walk_mut::walk_arrow_function_expression(self, expression);
+        self.function_depth -= deferred;
+        self.shadowed.pop();
+    }
+
+    fn visit_call_expression(&mut self, call: &mut CallExpression<'a>) {
+        // The values that `$inspect.trace` reports are not read by the
+        // traced function itself.
+        let reported = match &call.callee {
+            Expression::Identifier(callee) if callee.name == LUX_INSPECT_TRACE => {
+                call.arguments.pop()
+            }
+            _ => None,
+        };
+        self.invoked_immediately = matches!(
+            call.callee.without_parentheses(),
+            Expression::FunctionExpression(_) | Expression::ArrowFunctionExpression(_)
+        );
+        self.visit_expression(&mut call.callee);
+        let renders_arguments = renders_function_arguments(&call.callee);
+        for argument in call.arguments.iter_mut() {
+            self.invoked_immediately = renders_arguments;
+            self.visit_argument(argument);
+        }
+        self.invoked_immediately = false;
+        call.arguments.extend(reported);
+    }
+
+    fn visit_block_statement(&mut self, block: &mut BlockStatement<'a>) {
+        self.push_block_scope(&block.body);
+        walk_mut::walk_block_statement(self, block);
+        self.shadowed.pop();
+    }
+
+    fn visit_for_statement(&mut self, statement: &mut ForStatement<'a>) {
+        let mut names = BTreeSet::new();
+        if let Some(ForStatementInit::VariableDeclaration(declaration)) = &statement.init {
+            collect_variable_declaration_binding_names(declaration, &mut names);
+        }
+        self.shadowed.push(names);
+        walk_mut::walk_for_statement(self, statement);
+        self.shadowed.pop();
+    }
+
+    fn visit_for_in_statement(&mut self, statement: &mut ForInStatement<'a>) {
+        let mut names = BTreeSet::new();
+        if let ForStatementLeft::VariableDeclaration(declaration) = &statement.left {
+            collect_variable_declaration_binding_names(declaration, &mut names);
+        }
+        self.shadowed.push(names);
+        walk_mut::walk_for_in_statement(self, statement);
+        self.shadowed.pop();
+    }
+
+    fn visit_for_of_statement(&mut self, statement: &mut ForOfStatement<'a>) {
+        let mut names = BTreeSet::new();
+        if let ForStatementLeft::VariableDeclaration(declaration) = &statement.left {
+            collect_variable_declaration_binding_names(declaration, &mut names);
+        }
+        self.shadowed.push(names);
+        walk_mut::walk_for_of_statement(self, statement);
+        self.shadowed.pop();
+    }
+
+    fn visit_catch_clause(&mut self, clause: &mut CatchClause<'a>) {
+        let mut names = BTreeSet::new();
+        if let Some(parameter) = &clause.param {
+            collect_binding_pattern_names(&parameter.pattern, &mut names);
+        }
+        self.shadowed.push(names);
+        walk_mut::walk_catch_clause(self, clause);
+        self.shadowed.pop();
+    }
+
+    fn visit_object_property(&mut self, property: &mut ObjectProperty<'a>) {
+        walk_mut::walk_object_property(self, property);
+        if property.shorthand && !matches!(property.value, Expression::Identifier(_)) {
+            property.shorthand = false;
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression<'a>) {
+        // Reads at the top level of the instance happen while it renders,
+        // where nothing tracks them.
+        if self.function_depth > 0
+            && let Expression::Identifier(identifier) = expression
+        {
+            let name = identifier.name.as_str();
+            if self.reactive.contains(name) && !self.is_shadowed(name) {
+                *expression = self.tracked_read(name);
+            }
+            return;
+        }
+        walk_mut::walk_expression(self, expression);
+
+        let mut names = BTreeSet::new();
+        match expression {
+            Expression::AssignmentExpression(assignment) => {
+                collect_assignment_target_names(&assignment.left, &mut names);
+                self.proxy_assigned_value(assignment);
+            }
+            Expression::UpdateExpression(update) => {
+                if let SimpleAssignmentTarget::AssignmentTargetIdentifier(identifier) =
+                    &update.argument
+                {
+                    names.insert(identifier.name.as_str().to_owned());
+                }
+            }
+            _ => return,
+        }
+        let Some(values) = self.written_values(&names) else {
+            return;
+        };
+
+        let ast = self.ast;
+        let write = expression.take_in(ast.allocator);
+        *expression = ast.expression_call(
+            SPAN,
+            ast.expression_identifier(SPAN, ast.ident(LUX_SET)),
+            NONE,
+            ast.vec_from_array([
+                ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+                    .into(),
+                write.into(),
+                values.into(),
+            ]),
+            false,
+        );
+    }
+}
+
+/// Whether the callee is one of the template helpers that call the functions
+/// they are given while the template renders: blocks, boundaries and the
+/// loop over the items of an each block.
+fn renders_function_arguments(callee: &Expression<'_>) -> bool {
+    match callee {
+        Expression::Identifier(callee) => {
+            matches!(callee.name.as_str(), LUX_BLOCK | LUX_BOUNDARY)
+        }
+        Expression::StaticMemberExpression(member) if member.property.name == "map" => {
+            let Expression::CallExpression(call) = &member.object else {
+                return false;
+            };
+            matches!(
+                &call.callee,
+                Expression::StaticMemberExpression(from)
+                    if from.property.name == "from"
+                        && matches!(&from.object, Expression::Identifier(array) if array.name == "Array")
+            )
+        }
+        _ => false,
+    }
+}
+
 fn collect_statement_binding_names(statement: &Statement<'_>, names: &mut BTreeSet<String>) {
     match statement {
         Statement::ExportNamedDeclaration(declaration) => {
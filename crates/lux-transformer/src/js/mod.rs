@@ -4,7 +4,7 @@ mod template;
 use lux_ast::analysis::AnalysisTables;
 use lux_ast::template::root::Root;
 
-use crate::TransformTarget;
+use crate::TransformOptions;
 
 pub(super) struct ComponentRenderOutput {
     pub(super) js: String,
@@ -16,7 +16,8 @@ pub(super) fn render_component(
     analysis: &AnalysisTables,
     css_hash: Option<&str>,
     css_scope: Option<&str>,
-    target: TransformTarget,
+    embedded_css: Option<&str>,
+    options: &TransformOptions<'_>,
 ) -> ComponentRenderOutput {
-    component::render(root, analysis, css_hash, css_scope, target)
+    component::render(root, analysis, css_hash, css_scope, embedded_css, options)
 }
@@ -1,8 +1,12 @@
 use lux_ast::template::attribute::{Attribute, AttributeNode, AttributeValue};
+use lux_ast::template::root::Namespace;
 use lux_ast::template::tag::TextOrExpressionTag;
 
+use super::namespace::attribute_name_for_namespace;
+
 pub(super) fn render_static_attribute(
     attribute: &AttributeNode<'_>,
+    namespace: Namespace,
     has_dynamic: &mut bool,
 ) -> Option<String> {
     match attribute {
@@ -10,7 +14,7 @@ pub(super) fn render_static_attribute(
             if is_event_attribute_name(attribute.name) {
                 return None;
             }
-            serialize_attribute(attribute, has_dynamic)
+            serialize_attribute(attribute, namespace, has_dynamic)
         }
         // bind:this requires runtime wiring in client mode.
         AttributeNode::BindDirective(directive) if directive.name == "this" => {
@@ -24,9 +28,14 @@ pub(super) fn render_static_attribute(
     }
 }
 
-fn serialize_attribute(attribute: &Attribute<'_>, has_dynamic: &mut bool) -> Option<String> {
+fn serialize_attribute(
+    attribute: &Attribute<'_>,
+    namespace: Namespace,
+    has_dynamic: &mut bool,
+) -> Option<String> {
+    let name = attribute_name_for_namespace(attribute.name, namespace);
     match &attribute.value {
-        AttributeValue::True => Some(attribute.name.to_string()),
+        AttributeValue::True => Some(name.into_owned()),
         AttributeValue::ExpressionTag(_) => {
             *has_dynamic = true;
             None
@@ -44,11 +53,7 @@ fn serialize_attribute(attribute: &Attribute<'_>, has_dynamic: &mut bool) -> Opt
                 }
             }
 
-            Some(format!(
-                "{}=\"{}\"",
-                attribute.name,
-                escape_attribute_value(&value)
-            ))
+            Some(format!("{}=\"{}\"", name, escape_attribute_value(&value)))
         }
     }
 }
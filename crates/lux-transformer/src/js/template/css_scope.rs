@@ -1,12 +1,12 @@
 use lux_ast::analysis::{AnalysisNodeKind, AnalysisTables, RelativeSelectorAnalysis, SpanKey};
-use lux_ast::css::selector::{ClassSelector, RelativeSelector, SelectorList, SimpleSelector};
+use lux_ast::css::selector::{RelativeSelector, SelectorList, SimpleSelector};
 use lux_ast::css::stylesheet::{
     CssAtrule, CssBlock, CssBlockChild, CssRule, StyleSheet, StyleSheetChild,
 };
-use lux_ast::template::attribute::{AttributeNode, AttributeValue};
-use lux_ast::template::tag::TextOrExpressionTag;
+use lux_ast::template::attribute::AttributeNode;
 
 use super::StaticRenderContext;
+use lux_analyzer::{possible_attribute_values, possible_class_names};
 
 pub(super) fn scope_class_for_element<'a>(
     context: &StaticRenderContext<'a>,
@@ -88,12 +88,32 @@ fn selector_list_targets_element(
 ) -> bool {
     selector_list.children.iter().any(|complex| {
         complex.children.iter().any(|relative| {
-            relative_analysis(relative, analysis).scoped
-                && relative_targets_element(relative, analysis, name, attributes)
+            (relative_analysis(relative, analysis).scoped
+                && relative_targets_element(relative, analysis, name, attributes))
+                || nested_selector_lists_target_element(relative, analysis, name, attributes)
         })
     })
 }
 
+/// Selectors inside `:has(...)`, `:is(...)`, `:where(...)` and `:not(...)`
+/// are scoped like the rest of the selector.
+fn nested_selector_lists_target_element(
+    relative: &RelativeSelector<'_>,
+    analysis: &AnalysisTables,
+    name: &str,
+    attributes: &[AttributeNode<'_>],
+) -> bool {
+    relative.selectors.iter().any(|selector| match selector {
+        SimpleSelector::PseudoClassSelector(pseudo_class) if pseudo_class.name != "global" => {
+            pseudo_class
+                .args
+                .as_ref()
+                .is_some_and(|args| selector_list_targets_element(args, analysis, name, attributes))
+        }
+        _ => false,
+    })
+}
+
 fn relative_targets_element(
     relative: &RelativeSelector<'_>,
     analysis: &AnalysisTables,
@@ -119,20 +139,23 @@ fn simple_selector_targets_element(
         SimpleSelector::TypeSelector(type_selector) => {
             type_selector.name == "*" || type_selector.name.eq_ignore_ascii_case(name)
         }
-        SimpleSelector::IdSelector(id_selector) => {
-            static_attribute_value(attributes, "id").is_some_and(|value| value == id_selector.name)
-        }
+        SimpleSelector::IdSelector(id_selector) => possible_attribute_values(attributes, "id")
+            .is_some_and(|values| values.contains(id_selector.name)),
         SimpleSelector::ClassSelector(class_selector) => {
-            element_has_class(attributes, class_selector)
+            possible_class_names(attributes).contains(class_selector.name)
         }
         SimpleSelector::AttributeSelector(attribute_selector) => {
-            let Some(value) = static_attribute_value(attributes, attribute_selector.name) else {
-                return has_boolean_attribute(attributes, attribute_selector.name);
+            let Some(values) = possible_attribute_values(attributes, attribute_selector.name)
+            else {
+                return false;
             };
 
-            attribute_selector
-                .value
-                .is_none_or(|expected| value == strip_attribute_selector_quotes(expected))
+            match (attribute_selector.matcher, attribute_selector.value) {
+                (Some("="), Some(expected)) if attribute_selector.flags.is_none() => {
+                    values.contains(strip_attribute_selector_quotes(expected))
+                }
+                _ => true,
+            }
         }
         SimpleSelector::PseudoClassSelector(pseudo_class) => match pseudo_class.name {
             "global" | "root" | "host" => false,
@@ -170,67 +193,6 @@ fn relative_analysis(
         })
 }
 
-fn element_has_class(attributes: &[AttributeNode<'_>], selector: &ClassSelector<'_>) -> bool {
-    attributes.iter().any(|attribute| match attribute {
-        AttributeNode::Attribute(attribute) if attribute.name == "class" => {
-            static_class_tokens(&attribute.value)
-                .iter()
-                .any(|token| *token == selector.name)
-        }
-        AttributeNode::ClassDirective(directive) => directive.name == selector.name,
-        _ => false,
-    })
-}
-
-fn static_attribute_value<'a>(attributes: &'a [AttributeNode<'a>], name: &str) -> Option<&'a str> {
-    attributes.iter().find_map(|attribute| {
-        let AttributeNode::Attribute(attribute) = attribute else {
-            return None;
-        };
-        if attribute.name != name {
-            return None;
-        }
-        match &attribute.value {
-            AttributeValue::True => Some(""),
-            AttributeValue::ExpressionTag(_) => None,
-            AttributeValue::Sequence(chunks) => static_text_chunks(chunks),
-        }
-    })
-}
-
-fn has_boolean_attribute(attributes: &[AttributeNode<'_>], name: &str) -> bool {
-    attributes.iter().any(|attribute| {
-        matches!(attribute, AttributeNode::Attribute(attribute) if attribute.name == name)
-    })
-}
-
-fn static_class_tokens<'a>(value: &'a AttributeValue<'a>) -> Vec<&'a str> {
-    match value {
-        AttributeValue::True | AttributeValue::ExpressionTag(_) => Vec::new(),
-        AttributeValue::Sequence(chunks) => static_text_chunks(chunks)
-            .map(|text| text.split_whitespace().collect::<Vec<_>>())
-            .unwrap_or_default(),
-    }
-}
-
-fn static_text_chunks<'a>(chunks: &'a [TextOrExpressionTag<'a>]) -> Option<&'a str> {
-    if chunks
-        .iter()
-        .any(|chunk| matches!(chunk, TextOrExpressionTag::ExpressionTag(_)))
-    {
-        return None;
-    }
-
-    if chunks.len() != 1 {
-        return None;
-    }
-
-    match &chunks[0] {
-        TextOrExpressionTag::Text(text) => Some(text.raw),
-        TextOrExpressionTag::ExpressionTag(_) => None,
-    }
-}
-
 fn strip_attribute_selector_quotes(value: &str) -> &str {
     value
         .strip_prefix('"')
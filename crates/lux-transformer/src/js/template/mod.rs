@@ -1,13 +1,19 @@
 mod attribute;
 mod css_scope;
 mod marker;
+mod namespace;
 mod render;
 mod runtime;
+mod whitespace;
 
 use lux_ast::analysis::AnalysisTables;
 use lux_ast::css::stylesheet::StyleSheet;
-use lux_ast::template::root::FragmentNode;
-use oxc_ast::{AstBuilder, ast::Expression};
+use lux_ast::template::root::{FragmentNode, Namespace};
+pub(crate) use namespace::namespace_name;
+use oxc_ast::{
+    AstBuilder,
+    ast::{Expression, Statement},
+};
 pub(crate) use runtime::RuntimeScope;
 
 pub(super) struct TemplateRenderResult {
@@ -15,10 +21,13 @@ pub(super) struct TemplateRenderResult {
     pub has_dynamic: bool,
 }
 
+#[derive(Clone, Copy)]
 pub(super) struct StaticRenderContext<'a> {
     pub stylesheet: Option<&'a StyleSheet<'a>>,
     pub analysis: &'a AnalysisTables,
     pub css_scope: Option<&'a str>,
+    pub namespace: Namespace,
+    pub preserve_whitespace: bool,
 }
 
 pub(super) fn render_nodes_template(
@@ -38,3 +47,11 @@ pub(super) fn build_render_nodes_expression<'a>(
 ) -> Expression<'a> {
     runtime::build_render_nodes_expression(ast, nodes, scope)
 }
+
+pub(super) fn build_stream_nodes_statements<'a>(
+    ast: AstBuilder<'a>,
+    nodes: &[&'a FragmentNode<'a>],
+    scope: &RuntimeScope,
+) -> oxc_allocator::Vec<'a, Statement<'a>> {
+    runtime::build_stream_nodes_statements(ast, nodes, scope)
+}
@@ -0,0 +1,44 @@
+use std::borrow::Cow;
+
+use lux_ast::template::root::Namespace;
+
+/// Returns the namespace an element named `name` renders in when its parent
+/// renders in `parent`.
+pub(super) fn element_namespace(name: &str, parent: Namespace) -> Namespace {
+    match name {
+        "svg" => Namespace::Svg,
+        "math" => Namespace::Mathml,
+        _ => parent,
+    }
+}
+
+/// Returns the namespace the children of an element render in.
+pub(super) fn child_namespace(name: &str, namespace: Namespace) -> Namespace {
+    if namespace == Namespace::Svg && name == "foreignObject" {
+        Namespace::Html
+    } else {
+        namespace
+    }
+}
+
+pub(super) fn is_foreign_namespace(namespace: Namespace) -> bool {
+    namespace != Namespace::Html
+}
+
+/// HTML attribute names are case-insensitive and serialize lowercased, while
+/// SVG and MathML keep their casing (`viewBox`, `xlink:href`).
+pub(super) fn attribute_name_for_namespace(name: &str, namespace: Namespace) -> Cow<'_, str> {
+    if is_foreign_namespace(namespace) || !name.bytes().any(|byte| byte.is_ascii_uppercase()) {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(name.to_ascii_lowercase())
+    }
+}
+
+pub(crate) fn namespace_name(namespace: Namespace) -> &'static str {
+    match namespace {
+        Namespace::Html => "html",
+        Namespace::Svg => "svg",
+        Namespace::Mathml => "mathml",
+    }
+}
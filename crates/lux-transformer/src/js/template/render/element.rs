@@ -1,9 +1,13 @@
 use lux_ast::template::attribute::{AttributeNode, AttributeValue};
-use lux_ast::template::root::Fragment;
+use lux_ast::template::root::{Fragment, Namespace};
 use lux_ast::template::tag::TextOrExpressionTag;
 use lux_utils::elements::{is_load_error_element, is_void};
 
 use crate::js::template::attribute::{is_event_attribute_name, render_static_attribute};
+use crate::js::template::namespace::{child_namespace, element_namespace, is_foreign_namespace};
+use crate::js::template::whitespace::{
+    drops_whitespace_children, is_whitespace_text_node, preserves_whitespace_children,
+};
 
 use super::super::StaticRenderContext;
 use super::super::css_scope::scope_class_for_element;
@@ -18,6 +22,12 @@ pub(super) fn render_regular_element(
     context: &StaticRenderContext<'_>,
     select_value: Option<&str>,
 ) {
+    let namespace = element_namespace(name, context.namespace);
+    let child_context = StaticRenderContext {
+        namespace: child_namespace(name, namespace),
+        preserve_whitespace: context.preserve_whitespace || preserves_whitespace_children(name),
+        ..*context
+    };
     let scope_class = scope_class_for_element(context, name, attributes);
     let textarea_value = if name == "textarea" {
         find_textarea_value_attribute(attributes)
@@ -58,7 +68,7 @@ pub(super) fn render_regular_element(
         if name == "option" && option_selected && is_named_attribute(attribute, "selected") {
             continue;
         }
-        if let Some(serialized) = render_static_attribute(attribute, has_dynamic) {
+        if let Some(serialized) = render_static_attribute(attribute, namespace, has_dynamic) {
             out.push(' ');
             out.push_str(&serialized);
         }
@@ -92,9 +102,14 @@ pub(super) fn render_regular_element(
         out.push_str(" selected");
     }
 
+    if is_foreign_namespace(namespace) && children.nodes.is_empty() {
+        out.push_str("/>");
+        return;
+    }
+
     out.push('>');
 
-    if !is_void(name) {
+    if is_foreign_namespace(namespace) || !is_void(name) {
         if let Some(value) = textarea_value {
             render_static_textarea_value(value, out, has_dynamic);
         } else {
@@ -103,7 +118,7 @@ pub(super) fn render_regular_element(
                 children,
                 out,
                 has_dynamic,
-                context,
+                &child_context,
                 child_select_value,
             );
         }
@@ -121,7 +136,9 @@ fn render_child_fragment(
     context: &StaticRenderContext<'_>,
     select_value: Option<&str>,
 ) {
-    if parent_name != "select" {
+    if context.preserve_whitespace
+        || !drops_whitespace_children(parent_name, context.namespace == Namespace::Svg)
+    {
         render_fragment(children, out, has_dynamic, context, select_value);
         return;
     }
@@ -149,10 +166,6 @@ fn is_textarea_value_attribute(attribute: &AttributeNode<'_>) -> bool {
     matches!(attribute, AttributeNode::Attribute(attribute) if attribute.name == "value")
 }
 
-fn is_whitespace_text_node(node: &lux_ast::template::root::FragmentNode<'_>) -> bool {
-    matches!(node, lux_ast::template::root::FragmentNode::Text(text) if text.raw.trim().is_empty())
-}
-
 fn is_class_attribute(attribute: &AttributeNode<'_>) -> bool {
     matches!(attribute, AttributeNode::Attribute(attribute) if attribute.name == "class")
 }
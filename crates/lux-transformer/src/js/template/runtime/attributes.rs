@@ -1,10 +1,11 @@
 use lux_ast::template::attribute::{Attribute, AttributeNode, AttributeValue};
 use lux_ast::template::directive::{
     AnimateDirective, BindDirective, ClassDirective, EventModifier, OnDirective, StyleDirective,
-    StyleDirectiveValue, StyleModifier, TransitionDirective, UseDirective,
+    StyleDirectiveValue, StyleModifier, TransitionDirective, TransitionModifier, UseDirective,
 };
 use lux_ast::template::tag::TextOrExpressionTag;
 use lux_utils::attributes::is_boolean_attribute;
+use lux_utils::events::{can_delegate_event, is_capture_event, is_passive_event};
 use oxc_allocator::CloneIn;
 use oxc_ast::{
     AstBuilder, NONE,
@@ -15,6 +16,7 @@ use oxc_ast::{
 };
 use oxc_span::SPAN;
 
+use super::super::namespace::attribute_name_for_namespace;
 use super::expr::{
     call_static_method, escape_attr_expression, join_chunks_expression, string_expr,
     stringify_expression,
@@ -30,13 +32,14 @@ pub(super) fn render_attribute_expression<'a>(
     match attribute {
         AttributeNode::Attribute(attribute) => {
             if is_event_attribute_name(attribute.name) {
-                return string_expr(ast, "");
+                return render_event_attribute_expression(ast, attribute, scope, element_name);
             }
+            let name = attribute_name_for_namespace(attribute.name, scope.namespace());
             match &attribute.value {
-                AttributeValue::True => string_expr(ast, &format!(" {}", attribute.name)),
+                AttributeValue::True => string_expr(ast, &format!(" {name}")),
                 AttributeValue::ExpressionTag(tag) => render_named_expression_attribute(
                     ast,
-                    attribute.name,
+                    &name,
                     resolve_expression(ast, tag.expression.clone_in(ast.allocator), scope),
                 ),
                 AttributeValue::Sequence(chunks) => {
@@ -58,7 +61,7 @@ pub(super) fn render_attribute_expression<'a>(
 
                     render_named_expression_attribute(
                         ast,
-                        attribute.name,
+                        &name,
                         join_chunks_expression(ast, value_parts),
                     )
                 }
@@ -1037,6 +1040,49 @@ fn render_on_directive_attribute_expression<'a>(
     )
 }
 
+/// `onclick={handler}` and friends. Delegatable events on regular elements
+/// are dispatched by one listener on the mount root; the others, including
+/// `on...capture` attributes, get their own listener. Touch events default
+/// to passive listeners.
+fn render_event_attribute_expression<'a>(
+    ast: AstBuilder<'a>,
+    attribute: &Attribute<'_>,
+    scope: &RuntimeScope,
+    element_name: Option<&str>,
+) -> Expression<'a> {
+    let AttributeValue::ExpressionTag(tag) = &attribute.value else {
+        return string_expr(ast, "");
+    };
+
+    let mut event_name = &attribute.name[2..];
+    let capture = is_capture_event(event_name);
+    if capture {
+        event_name = &event_name[..event_name.len() - "capture".len()];
+    }
+    let mut modifiers = ast.vec();
+    if capture {
+        modifiers.push(string_expr(ast, "capture").into());
+    }
+    if is_passive_event(event_name) {
+        modifiers.push(string_expr(ast, "passive").into());
+    }
+    let delegated = !capture && element_name.is_some() && can_delegate_event(event_name);
+
+    let handler = resolve_expression(ast, tag.expression.clone_in(ast.allocator), scope);
+    ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident("__lux_event_attr")),
+        NONE,
+        ast.vec_from_array([
+            string_expr(ast, event_name).into(),
+            handler.into(),
+            ast.expression_array(SPAN, modifiers).into(),
+            ast.expression_boolean_literal(SPAN, delegated).into(),
+        ]),
+        false,
+    )
+}
+
 fn render_on_target_directive_attribute_expression<'a>(
     ast: AstBuilder<'a>,
     directive: &OnDirective<'_>,
@@ -1143,6 +1189,11 @@ fn render_transition_directive_attribute_expression<'a>(
             parameter_expression.into(),
             ast.expression_boolean_literal(SPAN, directive.intro).into(),
             ast.expression_boolean_literal(SPAN, directive.outro).into(),
+            ast.expression_boolean_literal(
+                SPAN,
+                directive.modifiers.contains(&TransitionModifier::Global),
+            )
+            .into(),
         ]),
         false,
     )
@@ -1182,16 +1233,31 @@ fn render_attach_tag_attribute_expression<'a>(
     scope: &RuntimeScope,
 ) -> Expression<'a> {
     let attach_expression = resolve_expression(ast, tag.expression.clone_in(ast.allocator), scope);
+    // The runtime evaluates the expression when it runs the attachment, so
+    // that the state read by the expression is tracked as well.
+    let attach_getter = ast.expression_arrow_function(
+        SPAN,
+        true,
+        false,
+        NONE,
+        ast.alloc_formal_parameters(
+            SPAN,
+            FormalParameterKind::ArrowFormalParameters,
+            ast.vec(),
+            NONE,
+        ),
+        NONE,
+        ast.alloc_function_body(
+            SPAN,
+            ast.vec(),
+            ast.vec1(ast.statement_expression(SPAN, attach_expression)),
+        ),
+    );
     ast.expression_call(
         SPAN,
-        ast.expression_identifier(SPAN, ast.ident("__lux_use_attr")),
+        ast.expression_identifier(SPAN, ast.ident("__lux_attach_attr")),
         NONE,
-        ast.vec_from_array([
-            string_expr(ast, "attach").into(),
-            attach_expression.into(),
-            ast.expression_identifier(SPAN, ast.ident("undefined"))
-                .into(),
-        ]),
+        ast.vec1(attach_getter.into()),
         false,
     )
 }
@@ -1,4 +1,7 @@
-use lux_ast::template::block::{AwaitBlock, EachBlock, IfBlock, SnippetBlock};
+use lux_ast::template::attribute::AttributeNode;
+use lux_ast::template::block::{AwaitBlock, EachBlock, IfBlock, KeyBlock, SnippetBlock};
+use lux_ast::template::element::SvelteBoundary;
+use lux_ast::template::root::FragmentNode;
 use lux_ast::template::tag::ConstTag;
 use oxc_allocator::CloneIn;
 use oxc_ast::{
@@ -10,11 +13,14 @@ use oxc_ast::{
 };
 use oxc_span::SPAN;
 
+use super::elements::attribute_value_to_component_prop_expression;
 use super::expr::{
-    bind_pattern_value_expression, call_iife, call_static_method, const_statement, string_expr,
+    await_if_async, bind_pattern_value_expression, call_iife, call_static_method, const_statement,
+    string_expr,
 };
-use super::render_fragment_expression;
 use super::scope::{RuntimeScope, resolve_expression};
+use super::{render_fragment_expression, render_fragment_nodes_expression};
+use crate::js::component::{LUX_BLOCK, LUX_BOUNDARY};
 
 pub(super) fn render_if_block_expression<'a>(
     ast: AstBuilder<'a>,
@@ -22,15 +28,101 @@ pub(super) fn render_if_block_expression<'a>(
     scope: &RuntimeScope,
 ) -> Expression<'a> {
     let test = resolve_expression(ast, block.test.clone_in(ast.allocator), scope);
-    let consequent = render_fragment_expression(ast, &block.consequent, scope);
+    let consequent = tracked_block_expression(
+        ast,
+        LUX_BLOCK,
+        block.span.start,
+        number_expr(ast, 0),
+        render_fragment_expression(ast, &block.consequent, scope),
+        scope,
+    );
     let alternate = block.alternate.as_ref().map_or_else(
         || string_expr(ast, ""),
-        |alternate| render_fragment_expression(ast, alternate, scope),
+        |alternate| {
+            let rendered = render_fragment_expression(ast, alternate, scope);
+            // An `{:else if}` branch is tracked by its own if block.
+            if matches!(alternate.nodes.as_slice(), [FragmentNode::IfBlock(nested)] if nested.elseif)
+            {
+                rendered
+            } else {
+                tracked_block_expression(
+                    ast,
+                    LUX_BLOCK,
+                    block.span.start,
+                    number_expr(ast, 1),
+                    rendered,
+                    scope,
+                )
+            }
+        },
     );
 
     ast.expression_conditional(SPAN, test, consequent, alternate)
 }
 
+/// Content of a `{#key}` block is a new block instance whenever the key
+/// changes.
+pub(super) fn render_key_block_expression<'a>(
+    ast: AstBuilder<'a>,
+    block: &'a KeyBlock<'a>,
+    scope: &RuntimeScope,
+) -> Expression<'a> {
+    let rendered = render_fragment_expression(ast, &block.fragment, scope);
+    let key = resolve_expression(ast, block.expression.clone_in(ast.allocator), scope);
+    tracked_block_expression(ast, LUX_BLOCK, block.span.start, key, rendered, scope)
+}
+
+/// Renders `rendered` through a block runtime helper, as
+/// `helper(id, key, () => rendered)`, when the scope tracks blocks.
+fn tracked_block_expression<'a>(
+    ast: AstBuilder<'a>,
+    helper: &str,
+    id: u32,
+    key: Expression<'a>,
+    rendered: Expression<'a>,
+    scope: &RuntimeScope,
+) -> Expression<'a> {
+    if !scope.track_instances() {
+        return rendered;
+    }
+
+    let render = ast.expression_arrow_function(
+        SPAN,
+        true,
+        scope.is_async(),
+        NONE,
+        ast.alloc_formal_parameters(
+            SPAN,
+            FormalParameterKind::ArrowFormalParameters,
+            ast.vec(),
+            NONE,
+        ),
+        NONE,
+        ast.alloc_function_body(
+            SPAN,
+            ast.vec(),
+            ast.vec1(ast.statement_expression(SPAN, rendered)),
+        ),
+    );
+    let call = ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident(helper)),
+        NONE,
+        ast.vec_from_array([number_expr(ast, id).into(), key.into(), render.into()]),
+        false,
+    );
+    await_if_async(ast, call, scope)
+}
+
+fn number_expr<'a>(ast: AstBuilder<'a>, value: u32) -> Expression<'a> {
+    ast.expression_numeric_literal(
+        SPAN,
+        f64::from(value),
+        None,
+        oxc_ast::ast::NumberBase::Decimal,
+    )
+}
+
 pub(super) fn render_each_block_expression<'a>(
     ast: AstBuilder<'a>,
     block: &'a EachBlock<'a>,
@@ -66,7 +158,10 @@ pub(super) fn render_each_block_expression<'a>(
         false,
         false,
     ));
-    if let Some(index) = block.index {
+    let index_name = block
+        .index
+        .or_else(|| (scope.track_instances() && block.key.is_none()).then_some("__lux_each_index"));
+    if let Some(index) = index_name {
         params_items.push(ast.formal_parameter(
             SPAN,
             ast.vec(),
@@ -95,6 +190,22 @@ pub(super) fn render_each_block_expression<'a>(
     }
 
     let body_expr = render_fragment_expression(ast, &block.body, &body_scope);
+    // Keyed items are identified by their key, the others by their index.
+    let item_key = match (&block.key, index_name) {
+        (Some(key), _) => Some(resolve_expression(
+            ast,
+            key.clone_in(ast.allocator),
+            &body_scope,
+        )),
+        (None, Some(index)) => Some(ast.expression_identifier(SPAN, ast.ident(index))),
+        (None, None) => None,
+    };
+    let body_expr = match item_key {
+        Some(key) => {
+            tracked_block_expression(ast, LUX_BLOCK, block.span.start, key, body_expr, scope)
+        }
+        None => body_expr,
+    };
     let body = ast.alloc_function_body(
         SPAN,
         ast.vec(),
@@ -105,7 +216,7 @@ pub(super) fn render_each_block_expression<'a>(
         FunctionType::FunctionExpression,
         None,
         false,
-        false,
+        scope.is_async(),
         false,
         NONE,
         NONE,
@@ -114,12 +225,26 @@ pub(super) fn render_each_block_expression<'a>(
         Some(body),
     );
 
-    let mapped = call_static_method(
+    let mut mapped = call_static_method(
         ast,
         from_call.clone_in(ast.allocator),
         "map",
         ast.vec1(callback.into()),
     );
+    if scope.is_async() {
+        mapped = ast.expression_parenthesized(
+            SPAN,
+            ast.expression_await(
+                SPAN,
+                call_static_method(
+                    ast,
+                    ast.expression_identifier(SPAN, ast.ident("Promise")),
+                    "all",
+                    ast.vec1(mapped.into()),
+                ),
+            ),
+        );
+    }
     let joined = call_static_method(ast, mapped, "join", ast.vec1(string_expr(ast, "").into()));
 
     if let Some(fallback) = &block.fallback {
@@ -147,12 +272,10 @@ pub(super) fn render_await_block_expression<'a>(
     block: &'a AwaitBlock<'a>,
     scope: &RuntimeScope,
 ) -> Expression<'a> {
-    let awaited_expression =
-        resolve_expression(ast, block.expression.clone_in(ast.allocator), scope);
-
-    let pending_expr = block.pending.as_ref().map_or_else(
-        || string_expr(ast, ""),
-        |pending| render_fragment_expression(ast, pending, scope),
+    let awaited_expression = await_if_async(
+        ast,
+        resolve_expression(ast, block.expression.clone_in(ast.allocator), scope),
+        scope,
     );
 
     let then_expr = block.then.as_ref().map_or_else(
@@ -168,26 +291,17 @@ pub(super) fn render_await_block_expression<'a>(
         },
     );
 
-    let await_value_ident = ast.expression_identifier(SPAN, ast.ident("__lux_await_value"));
-    let has_then = ast.member_expression_static(
-        SPAN,
-        await_value_ident.clone_in(ast.allocator),
-        ast.identifier_name(SPAN, ast.ident("then")),
-        false,
-    );
-    let promise_like = ast.expression_logical(
-        SPAN,
-        await_value_ident.clone_in(ast.allocator),
-        LogicalOperator::And,
-        ast.expression_binary(
-            SPAN,
-            ast.expression_unary(SPAN, UnaryOperator::Typeof, has_then.into()),
-            BinaryOperator::StrictEquality,
-            string_expr(ast, "function"),
-        ),
-    );
-
-    let try_result = ast.expression_conditional(SPAN, promise_like, pending_expr, then_expr);
+    // Async rendering settles the promise before emitting, so the pending
+    // branch never reaches the output.
+    let try_result = if scope.is_async() {
+        then_expr
+    } else {
+        let pending_expr = block.pending.as_ref().map_or_else(
+            || string_expr(ast, ""),
+            |pending| render_fragment_expression(ast, pending, scope),
+        );
+        sync_await_result_expression(ast, pending_expr, then_expr)
+    };
 
     let mut body_statements = ast.vec();
     if let Some(catch_fragment) = &block.catch {
@@ -219,7 +333,34 @@ pub(super) fn render_await_block_expression<'a>(
         body_statements.push(ast.statement_return(SPAN, Some(try_result)));
     }
 
-    call_iife(ast, body_statements)
+    call_iife(ast, body_statements, scope)
+}
+
+fn sync_await_result_expression<'a>(
+    ast: AstBuilder<'a>,
+    pending_expr: Expression<'a>,
+    then_expr: Expression<'a>,
+) -> Expression<'a> {
+    let await_value_ident = ast.expression_identifier(SPAN, ast.ident("__lux_await_value"));
+    let has_then = ast.member_expression_static(
+        SPAN,
+        await_value_ident.clone_in(ast.allocator),
+        ast.identifier_name(SPAN, ast.ident("then")),
+        false,
+    );
+    let promise_like = ast.expression_logical(
+        SPAN,
+        await_value_ident.clone_in(ast.allocator),
+        LogicalOperator::And,
+        ast.expression_binary(
+            SPAN,
+            ast.expression_unary(SPAN, UnaryOperator::Typeof, has_then.into()),
+            BinaryOperator::StrictEquality,
+            string_expr(ast, "function"),
+        ),
+    );
+
+    ast.expression_conditional(SPAN, promise_like, pending_expr, then_expr)
 }
 
 pub(super) fn render_snippet_block_declaration<'a>(
@@ -229,7 +370,8 @@ pub(super) fn render_snippet_block_declaration<'a>(
 ) -> Expression<'a> {
     let name = block.expression.name.as_str();
 
-    let mut snippet_scope = scope.clone();
+    // Snippets are called synchronously by the components they are passed to.
+    let mut snippet_scope = scope.with_async(false);
     for parameter in &block.parameters {
         snippet_scope = snippet_scope.with_binding_pattern(parameter);
     }
@@ -291,6 +433,105 @@ pub(super) fn render_snippet_block_declaration<'a>(
     ast.expression_sequence(SPAN, ast.vec_from_array([assignment, string_expr(ast, "")]))
 }
 
+/// Renders the children of a `<svelte:boundary>` through the boundary
+/// runtime, which renders the `failed` snippet with `(error, reset)` and
+/// calls `onerror` when they throw.
+pub(super) fn render_svelte_boundary_expression<'a>(
+    ast: AstBuilder<'a>,
+    boundary: &'a SvelteBoundary<'a>,
+    scope: &RuntimeScope,
+) -> Expression<'a> {
+    let mut statements = ast.vec();
+    let mut failed = None;
+    let mut pending = None;
+    let mut onerror = None;
+    for attribute in &boundary.attributes {
+        let AttributeNode::Attribute(attribute) = attribute else {
+            continue;
+        };
+        let slot = match attribute.name {
+            "failed" => &mut failed,
+            "pending" => &mut pending,
+            "onerror" => &mut onerror,
+            _ => continue,
+        };
+        *slot = Some(attribute_value_to_component_prop_expression(
+            ast,
+            &attribute.value,
+            scope,
+        ));
+    }
+
+    let mut children = Vec::with_capacity(boundary.fragment.nodes.len());
+    for node in &boundary.fragment.nodes {
+        let FragmentNode::SnippetBlock(block) = node else {
+            children.push(node);
+            continue;
+        };
+        let slot = match block.expression.name.as_str() {
+            "failed" => &mut failed,
+            "pending" => &mut pending,
+            _ => {
+                children.push(node);
+                continue;
+            }
+        };
+        statements.push(
+            ast.statement_expression(SPAN, render_snippet_block_declaration(ast, block, scope)),
+        );
+        *slot = Some(resolve_expression(
+            ast,
+            ast.expression_identifier(SPAN, block.expression.name),
+            scope,
+        ));
+    }
+
+    let params =
+        ast.alloc_formal_parameters(SPAN, FormalParameterKind::FormalParameter, ast.vec(), NONE);
+    let body = ast.alloc_function_body(
+        SPAN,
+        ast.vec(),
+        ast.vec1(ast.statement_return(
+            SPAN,
+            Some(render_fragment_nodes_expression(ast, &children, scope)),
+        )),
+    );
+    let render_children = ast.expression_function(
+        SPAN,
+        FunctionType::FunctionExpression,
+        None,
+        false,
+        scope.is_async(),
+        false,
+        NONE,
+        NONE,
+        params,
+        NONE,
+        Some(body),
+    );
+    // Async rendering settles the children before emitting, so the pending
+    // snippet never reaches the output.
+    let pending = pending.filter(|_| !scope.is_async());
+
+    let or_null = |expression: Option<Expression<'a>>| {
+        expression.unwrap_or_else(|| ast.expression_null_literal(SPAN))
+    };
+    let rendered = ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident(LUX_BOUNDARY)),
+        NONE,
+        ast.vec_from_array([
+            render_children.into(),
+            or_null(failed).into(),
+            or_null(onerror).into(),
+            or_null(pending).into(),
+        ]),
+        false,
+    );
+    statements.push(ast.statement_return(SPAN, Some(await_if_async(ast, rendered, scope))));
+    call_iife(ast, statements, scope)
+}
+
 pub(super) fn render_const_tag_declaration_statement<'a>(
     ast: AstBuilder<'a>,
     tag: &ConstTag<'_>,
@@ -1,23 +1,23 @@
 use lux_ast::template::attribute::{AttributeNode, AttributeValue};
 use lux_ast::template::directive::{EventModifier, LetDirective};
 use lux_ast::template::element::{Component, SvelteComponent, SvelteSelf};
-use lux_ast::template::root::{Fragment, FragmentNode};
+use lux_ast::template::root::{Fragment, FragmentNode, Namespace};
 use lux_ast::template::tag::TextOrExpressionTag;
 use oxc_allocator::CloneIn;
 use oxc_ast::{
     AstBuilder, NONE,
     ast::{
         AssignmentOperator, BinaryOperator, Expression, FormalParameterKind, FunctionType,
-        LogicalOperator, PropertyKind, Statement, VariableDeclarationKind,
+        LogicalOperator, PropertyKey, PropertyKind, Statement, VariableDeclarationKind,
     },
 };
 use oxc_span::SPAN;
 
 use super::elements::{attribute_value_to_component_prop_expression, object_init_property};
-use super::expr::{call_iife, const_statement, string_expr, stringify_expression};
+use super::expr::{await_if_async, call_iife, const_statement, string_expr, stringify_expression};
 use super::render_fragment_nodes_expression;
 use super::scope::{RuntimeScope, resolve_expression};
-use crate::js::component::LUX_RENDER_COMPONENT;
+use crate::js::component::{LUX_ATTACHMENT_KEY, LUX_CSS_PROPS, LUX_RENDER_COMPONENT};
 
 pub(super) fn render_component_expression<'a>(
     ast: AstBuilder<'a>,
@@ -102,18 +102,12 @@ fn render_component_like_expression<'a>(
 
     let component_ident = ast.expression_identifier(SPAN, ast.ident("__lux_component"));
     let props_ident = ast.expression_identifier(SPAN, ast.ident("__lux_component_props"));
-    let rendered = ast.expression_call(
-        SPAN,
-        ast.expression_identifier(SPAN, ast.ident(LUX_RENDER_COMPONENT)),
-        NONE,
-        ast.vec_from_array([
-            component_ident.clone_in(ast.allocator).into(),
-            props_ident.clone_in(ast.allocator).into(),
-            ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
-                .into(),
-        ]),
-        false,
-    );
+    let mut render_arguments = ast.vec_from_array([
+        component_ident.clone_in(ast.allocator).into(),
+        props_ident.clone_in(ast.allocator).into(),
+        ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+            .into(),
+    ]);
 
     let mut statements = ast.vec_with_capacity(4);
     statements.push(const_statement(ast, "__lux_component", callee));
@@ -122,15 +116,80 @@ fn render_component_like_expression<'a>(
         "__lux_component_props",
         props_expression,
     ));
-    if let Some(bind_expression) = bind_this_expression {
-        if let Some(bind_statement) =
-            build_component_bind_this_statement(ast, &bind_expression, scope)
-        {
+    let bind_statement = bind_this_expression.and_then(|bind_expression| {
+        build_component_bind_this_statement(ast, &bind_expression, scope)
+    });
+    if let Some(bind_statement) = bind_statement {
+        // The client runtime binds the component once it is rendered and
+        // unbinds it with `null` when the component is destroyed.
+        if scope.track_instances() {
+            render_arguments.push(build_component_bind_this_callback(ast, bind_statement).into());
+        } else {
             statements.push(bind_statement);
         }
     }
+    let rendered = ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident(LUX_RENDER_COMPONENT)),
+        NONE,
+        render_arguments,
+        false,
+    );
+    let rendered = await_if_async(ast, rendered, scope);
+    let rendered = match build_css_props_expression(ast, attributes, scope) {
+        Some(css_props) => wrap_css_props(ast, rendered, css_props, scope),
+        None => rendered,
+    };
     statements.push(ast.statement_return(SPAN, Some(rendered)));
-    stringify_expression(ast, call_iife(ast, statements))
+    stringify_expression(ast, call_iife(ast, statements, scope))
+}
+
+/// `--custom-property` attributes are not props: they are set on a wrapper
+/// element around the component instead.
+fn is_css_custom_property(attribute: &AttributeNode<'_>) -> bool {
+    matches!(attribute, AttributeNode::Attribute(attribute) if attribute.name.starts_with("--"))
+}
+
+fn build_css_props_expression<'a>(
+    ast: AstBuilder<'a>,
+    attributes: &[AttributeNode<'a>],
+    scope: &RuntimeScope,
+) -> Option<Expression<'a>> {
+    let mut properties = ast.vec();
+    for attribute in attributes {
+        if let AttributeNode::Attribute(attribute) = attribute
+            && attribute.name.starts_with("--")
+        {
+            properties.push(object_init_property(
+                ast,
+                attribute.name,
+                attribute_value_to_component_prop_expression(ast, &attribute.value, scope),
+            ));
+        }
+    }
+    (!properties.is_empty()).then(|| ast.expression_object(SPAN, properties))
+}
+
+/// Wraps the rendered component in `<svelte-css-wrapper>`, or in a `<g>`
+/// inside SVG, carrying the custom properties as inline styles.
+fn wrap_css_props<'a>(
+    ast: AstBuilder<'a>,
+    rendered: Expression<'a>,
+    css_props: Expression<'a>,
+    scope: &RuntimeScope,
+) -> Expression<'a> {
+    ast.expression_call(
+        SPAN,
+        ast.expression_identifier(SPAN, ast.ident(LUX_CSS_PROPS)),
+        NONE,
+        ast.vec_from_array([
+            rendered.into(),
+            css_props.into(),
+            ast.expression_boolean_literal(SPAN, scope.namespace() == Namespace::Svg)
+                .into(),
+        ]),
+        false,
+    )
 }
 
 fn build_component_props_expression<'a>(
@@ -144,6 +203,9 @@ fn build_component_props_expression<'a>(
     let mut bind_this_expression: Option<Expression<'a>> = None;
 
     for attribute in attributes {
+        if is_css_custom_property(attribute) {
+            continue;
+        }
         match attribute {
             AttributeNode::Attribute(attribute) => {
                 properties.push(object_init_property(
@@ -194,13 +256,32 @@ fn build_component_props_expression<'a>(
                     event_handlers.push((attribute.name, vec![resolved]));
                 }
             }
+            AttributeNode::AttachTag(tag) => {
+                // Attachments are passed under a unique symbol key, so that
+                // spreading the props onto an element attaches them there.
+                let key = ast.expression_call(
+                    SPAN,
+                    ast.expression_identifier(SPAN, ast.ident(LUX_ATTACHMENT_KEY)),
+                    NONE,
+                    ast.vec(),
+                    false,
+                );
+                properties.push(ast.object_property_kind_object_property(
+                    SPAN,
+                    PropertyKind::Init,
+                    PropertyKey::from(key),
+                    resolve_expression(ast, tag.expression.clone_in(ast.allocator), scope),
+                    false,
+                    false,
+                    true,
+                ));
+            }
             AttributeNode::ClassDirective(_)
             | AttributeNode::StyleDirective(_)
             | AttributeNode::TransitionDirective(_)
             | AttributeNode::AnimateDirective(_)
             | AttributeNode::UseDirective(_)
-            | AttributeNode::LetDirective(_)
-            | AttributeNode::AttachTag(_) => {}
+            | AttributeNode::LetDirective(_) => {}
         }
     }
 
@@ -378,6 +459,41 @@ fn build_component_bind_this_statement<'a>(
     )
 }
 
+fn build_component_bind_this_callback<'a>(
+    ast: AstBuilder<'a>,
+    bind_statement: Statement<'a>,
+) -> Expression<'a> {
+    let params = ast.alloc_formal_parameters(
+        SPAN,
+        FormalParameterKind::FormalParameter,
+        ast.vec1(ast.formal_parameter(
+            SPAN,
+            ast.vec(),
+            ast.binding_pattern_binding_identifier(SPAN, ast.ident("__lux_component")),
+            NONE,
+            NONE,
+            false,
+            None,
+            false,
+            false,
+        )),
+        NONE,
+    );
+    ast.expression_function(
+        SPAN,
+        FunctionType::FunctionExpression,
+        None,
+        false,
+        false,
+        false,
+        NONE,
+        NONE,
+        params,
+        NONE,
+        Some(ast.alloc_function_body(SPAN, ast.vec(), ast.vec1(bind_statement))),
+    )
+}
+
 fn build_component_assignment_statement<'a>(
     ast: AstBuilder<'a>,
     expression: &Expression<'a>,
@@ -718,7 +834,8 @@ fn build_slot_function_for_nodes<'a>(
     let_bindings: &[SlotLetBinding<'_>],
     scope: &RuntimeScope,
 ) -> Expression<'a> {
-    let mut slot_scope = scope.clone();
+    // Slot functions are called synchronously by the child component.
+    let mut slot_scope = scope.with_async(false);
     let mut body_statements = ast.vec();
 
     if !let_bindings.is_empty() {
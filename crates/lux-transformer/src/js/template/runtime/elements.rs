@@ -1,22 +1,31 @@
 use lux_ast::template::attribute::{AttributeNode, AttributeValue};
-use lux_ast::template::element::{SlotElement, SvelteElement};
-use lux_ast::template::root::Fragment;
+use lux_ast::template::element::{RegularElement, SlotElement, SvelteElement};
+use lux_ast::template::root::{Fragment, FragmentNode, Namespace};
 use lux_ast::template::tag::TextOrExpressionTag;
+use std::borrow::Cow;
+
 use lux_utils::elements::{is_load_error_element, is_void};
 use oxc_allocator::CloneIn;
 use oxc_ast::{
     AstBuilder, NONE,
-    ast::{BinaryOperator, Expression, LogicalOperator, PropertyKind},
+    ast::{BinaryOperator, Expression, LogicalOperator, PropertyKind, Statement},
 };
 use oxc_span::SPAN;
 
+use super::super::namespace::{child_namespace, element_namespace, is_foreign_namespace};
+use super::super::whitespace::{
+    drops_whitespace_children, is_whitespace_text_node, preserves_whitespace_children,
+};
 use super::attributes::render_element_attribute_chunks;
 use super::expr::{
     call_iife, const_statement, escape_html_expression, join_chunks_expression, string_expr,
     stringify_expression,
 };
-use super::{render_fragment_expression, render_fragment_nodes_expression, render_node_expression};
 use super::scope::{RuntimeScope, is_valid_js_identifier, resolve_expression};
+use super::{
+    push_chunk_statement, push_fragment_nodes_statements, render_fragment_expression,
+    render_fragment_nodes_expression, render_node_expression,
+};
 
 pub(super) fn render_regular_element_expression<'a>(
     ast: AstBuilder<'a>,
@@ -25,6 +34,10 @@ pub(super) fn render_regular_element_expression<'a>(
     children: &'a Fragment<'a>,
     scope: &RuntimeScope,
 ) -> Expression<'a> {
+    let namespace = element_namespace(name, scope.namespace());
+    let child_scope = child_element_scope(scope, name, namespace);
+    let scope = scope_for_namespace(scope, namespace);
+    let scope = scope.as_ref();
     let textarea_value = if name == "textarea" {
         find_textarea_value_expression(ast, attributes, scope)
     } else {
@@ -35,36 +48,30 @@ pub(super) fn render_regular_element_expression<'a>(
     } else {
         None
     };
-    let mut chunks = ast.vec();
-    chunks.push(string_expr(ast, &format!("<{name}")));
+    let mut chunks = render_open_tag_chunks(ast, name, attributes, scope);
 
-    let rendered_attributes =
-        render_element_attribute_chunks(ast, attributes, scope, Some(name), scope.css_scope());
-    for attribute in rendered_attributes {
-        chunks.push(attribute);
-    }
-    let (capture_onload, capture_onerror) = detect_load_error_captures(name, attributes);
-    if capture_onload {
-        chunks.push(string_expr(ast, " onload=\"this.__e=event\""));
-    }
-    if capture_onerror {
-        chunks.push(string_expr(ast, " onerror=\"this.__e=event\""));
+    if is_foreign_namespace(namespace) && children.nodes.is_empty() {
+        chunks.push(string_expr(ast, "/>"));
+        return join_chunks_expression(ast, chunks);
     }
 
     chunks.push(string_expr(ast, ">"));
-    if !is_void(name) {
+    if is_foreign_namespace(namespace) || !is_void(name) {
         if let Some(value) = textarea_value {
             chunks.push(escape_html_expression(ast, value));
         } else if let Some(select_value_expression) = &select_value_expression {
             chunks.push(render_select_children_expression(
                 ast,
                 children,
-                scope,
+                &child_scope,
                 select_value_expression.clone_in(ast.allocator),
             ));
         } else {
             chunks.push(render_child_fragment_expression(
-                ast, name, children, scope,
+                ast,
+                name,
+                children,
+                &child_scope,
             ));
         }
         chunks.push(string_expr(ast, &format!("</{name}>")));
@@ -73,6 +80,81 @@ pub(super) fn render_regular_element_expression<'a>(
     join_chunks_expression(ast, chunks)
 }
 
+/// Streams a regular element that holds an `{#await}` or a
+/// `<svelte:boundary>`: its tags are pushed around the statements of its
+/// children, so that the sink is flushed before the nested blocks as well.
+pub(super) fn push_streamed_element_statements<'a>(
+    ast: AstBuilder<'a>,
+    statements: &mut oxc_allocator::Vec<'a, Statement<'a>>,
+    element: &'a RegularElement<'a>,
+    scope: &RuntimeScope,
+    sink_name: &str,
+) {
+    let name = element.name;
+    let namespace = element_namespace(name, scope.namespace());
+    let child_scope = child_element_scope(scope, name, namespace);
+    let scope = scope_for_namespace(scope, namespace);
+    let mut chunks = render_open_tag_chunks(ast, name, &element.attributes, &scope);
+    chunks.push(string_expr(ast, ">"));
+    statements.push(push_chunk_statement(
+        ast,
+        sink_name,
+        join_chunks_expression(ast, chunks),
+    ));
+    let nodes = child_fragment_nodes(name, &element.fragment, &child_scope);
+    push_fragment_nodes_statements(ast, statements, &nodes, &child_scope, sink_name, true);
+    statements.push(push_chunk_statement(
+        ast,
+        sink_name,
+        string_expr(ast, &format!("</{name}>")),
+    ));
+}
+
+fn child_element_scope<'s>(
+    scope: &'s RuntimeScope,
+    name: &str,
+    namespace: Namespace,
+) -> Cow<'s, RuntimeScope> {
+    let child_scope = scope_for_namespace(scope, child_namespace(name, namespace));
+    if !scope.preserve_whitespace() && preserves_whitespace_children(name) {
+        return Cow::Owned(child_scope.with_preserve_whitespace(true));
+    }
+    child_scope
+}
+
+/// `<name` followed by the attributes of the element.
+fn render_open_tag_chunks<'a>(
+    ast: AstBuilder<'a>,
+    name: &str,
+    attributes: &[AttributeNode<'a>],
+    scope: &RuntimeScope,
+) -> oxc_allocator::Vec<'a, Expression<'a>> {
+    let mut chunks = ast.vec();
+    chunks.push(string_expr(ast, &format!("<{name}")));
+
+    let rendered_attributes =
+        render_element_attribute_chunks(ast, attributes, scope, Some(name), scope.css_scope());
+    for attribute in rendered_attributes {
+        chunks.push(attribute);
+    }
+    let (capture_onload, capture_onerror) = detect_load_error_captures(name, attributes);
+    if capture_onload {
+        chunks.push(string_expr(ast, " onload=\"this.__e=event\""));
+    }
+    if capture_onerror {
+        chunks.push(string_expr(ast, " onerror=\"this.__e=event\""));
+    }
+    chunks
+}
+
+fn scope_for_namespace(scope: &RuntimeScope, namespace: Namespace) -> Cow<'_, RuntimeScope> {
+    if scope.namespace() == namespace {
+        Cow::Borrowed(scope)
+    } else {
+        Cow::Owned(scope.with_namespace(namespace))
+    }
+}
+
 fn find_textarea_value_expression<'a>(
     ast: AstBuilder<'a>,
     attributes: &[AttributeNode<'a>],
@@ -251,16 +333,22 @@ fn render_child_fragment_expression<'a>(
     children: &'a Fragment<'a>,
     scope: &RuntimeScope,
 ) -> Expression<'a> {
-    if parent_name != "select" {
-        return render_fragment_expression(ast, children, scope);
-    }
+    let nodes = child_fragment_nodes(parent_name, children, scope);
+    render_fragment_nodes_expression(ast, &nodes, scope)
+}
 
-    let nodes = children
+fn child_fragment_nodes<'a>(
+    parent_name: &str,
+    children: &'a Fragment<'a>,
+    scope: &RuntimeScope,
+) -> Vec<&'a FragmentNode<'a>> {
+    let drops_whitespace = !scope.preserve_whitespace()
+        && drops_whitespace_children(parent_name, scope.namespace() == Namespace::Svg);
+    children
         .nodes
         .iter()
-        .filter(|node| !is_whitespace_text_node(node))
-        .collect::<Vec<_>>();
-    render_fragment_nodes_expression(ast, &nodes, scope)
+        .filter(|node| !drops_whitespace || !is_whitespace_text_node(node))
+        .collect()
 }
 
 fn detect_load_error_captures(name: &str, attributes: &[AttributeNode<'_>]) -> (bool, bool) {
@@ -294,10 +382,6 @@ fn detect_load_error_captures(name: &str, attributes: &[AttributeNode<'_>]) -> (
     (onload, onerror)
 }
 
-fn is_whitespace_text_node(node: &lux_ast::template::root::FragmentNode<'_>) -> bool {
-    matches!(node, lux_ast::template::root::FragmentNode::Text(text) if text.raw.trim().is_empty())
-}
-
 pub(super) fn render_slot_element_expression<'a>(
     ast: AstBuilder<'a>,
     element: &'a SlotElement<'a>,
@@ -389,7 +473,7 @@ pub(super) fn render_slot_element_expression<'a>(
         fallback,
     );
     statements.push(ast.statement_return(SPAN, Some(rendered)));
-    call_iife(ast, statements)
+    call_iife(ast, statements, scope)
 }
 
 pub(super) fn render_svelte_element_expression<'a>(
@@ -456,7 +540,7 @@ pub(super) fn render_svelte_element_expression<'a>(
         ),
     ));
     statements.push(ast.statement_return(SPAN, Some(join_chunks_expression(ast, chunks))));
-    call_iife(ast, statements)
+    call_iife(ast, statements, scope)
 }
 
 const VOID_ELEMENT_NAMES: &[&str] = &[
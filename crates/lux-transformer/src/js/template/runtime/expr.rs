@@ -105,7 +105,7 @@ pub(super) fn bind_pattern_value_expression<'a>(
         FunctionType::FunctionExpression,
         None,
         false,
-        false,
+        scope.is_async(),
         false,
         NONE,
         NONE,
@@ -113,21 +113,28 @@ pub(super) fn bind_pattern_value_expression<'a>(
         NONE,
         Some(body),
     );
-    ast.expression_call(
-        SPAN,
-        binder,
-        NONE,
-        ast.vec1(
-            ast.expression_identifier(SPAN, ast.ident(value_ident_name))
-                .into(),
+    await_if_async(
+        ast,
+        ast.expression_call(
+            SPAN,
+            binder,
+            NONE,
+            ast.vec1(
+                ast.expression_identifier(SPAN, ast.ident(value_ident_name))
+                    .into(),
+            ),
+            false,
         ),
-        false,
+        scope,
     )
 }
 
+/// Wraps `statements` in an immediately invoked function. In async scopes the
+/// function is async and its result awaited, so the body may use `await`.
 pub(super) fn call_iife<'a>(
     ast: AstBuilder<'a>,
     statements: oxc_allocator::Vec<'a, Statement<'a>>,
+    scope: &RuntimeScope,
 ) -> Expression<'a> {
     let params =
         ast.alloc_formal_parameters(SPAN, FormalParameterKind::FormalParameter, ast.vec(), NONE);
@@ -137,7 +144,7 @@ pub(super) fn call_iife<'a>(
         FunctionType::FunctionExpression,
         None,
         false,
-        false,
+        scope.is_async(),
         false,
         NONE,
         NONE,
@@ -145,7 +152,23 @@ pub(super) fn call_iife<'a>(
         NONE,
         Some(body),
     );
-    ast.expression_call(SPAN, function, NONE, ast.vec(), false)
+    await_if_async(
+        ast,
+        ast.expression_call(SPAN, function, NONE, ast.vec(), false),
+        scope,
+    )
+}
+
+pub(super) fn await_if_async<'a>(
+    ast: AstBuilder<'a>,
+    expression: Expression<'a>,
+    scope: &RuntimeScope,
+) -> Expression<'a> {
+    if scope.is_async() {
+        ast.expression_await(SPAN, expression)
+    } else {
+        expression
+    }
 }
 
 pub(super) fn const_statement<'a>(
@@ -5,29 +5,32 @@ mod elements;
 mod expr;
 mod scope;
 
+use lux_ast::template::element::RegularElement;
 use lux_ast::template::root::{Fragment, FragmentNode};
 use oxc_allocator::CloneIn;
-use oxc_ast::ast::PropertyKind;
+use oxc_ast::ast::{PropertyKind, Statement};
 use oxc_ast::{AstBuilder, ast::Expression};
 use oxc_span::SPAN;
 
 use self::attributes::render_target_attribute_expression;
 use self::blocks::{
     render_await_block_expression, render_const_tag_declaration_statement,
-    render_each_block_expression, render_if_block_expression, render_snippet_block_declaration,
+    render_each_block_expression, render_if_block_expression, render_key_block_expression,
+    render_snippet_block_declaration, render_svelte_boundary_expression,
 };
 use self::components::{
     render_component_expression, render_svelte_component_expression, render_svelte_self_expression,
 };
 use self::elements::{
-    render_regular_element_expression, render_slot_element_expression,
-    render_svelte_element_expression,
+    push_streamed_element_statements, render_regular_element_expression,
+    render_slot_element_expression, render_svelte_element_expression,
 };
 use self::expr::{
     call_iife, call_static_method, escape_html_expression, string_expr, stringify_expression,
 };
 use self::scope::resolve_expression;
 use super::marker::sanitize_comment;
+use super::whitespace::{CleanNode, clean_nodes};
 
 pub(crate) use self::scope::RuntimeScope;
 
@@ -36,8 +39,7 @@ pub(super) fn build_render_nodes_expression<'a>(
     nodes: &[&'a FragmentNode<'a>],
     scope: &RuntimeScope,
 ) -> Expression<'a> {
-    let trimmed = trim_edge_whitespace_nodes(nodes);
-    render_fragment_nodes_expression(ast, &trimmed, scope)
+    render_fragment_nodes_expression(ast, nodes, scope)
 }
 
 fn render_fragment_expression<'a>(
@@ -45,10 +47,25 @@ fn render_fragment_expression<'a>(
     fragment: &'a Fragment<'a>,
     scope: &RuntimeScope,
 ) -> Expression<'a> {
-    let nodes = trim_edge_whitespace_nodes(&fragment.nodes.iter().collect::<Vec<_>>());
+    let nodes = fragment.nodes.iter().collect::<Vec<_>>();
     render_fragment_nodes_expression(ast, &nodes, scope)
 }
 
+/// Builds the statements of a streamed top-level fragment. Chunks are pushed
+/// straight to `$$renderer`, and the sink is flushed before each `{#await}`
+/// and `<svelte:boundary>`, including the ones nested in elements, so the
+/// finished markup reaches the client while the slower part is still
+/// rendering.
+pub(super) fn build_stream_nodes_statements<'a>(
+    ast: AstBuilder<'a>,
+    nodes: &[&'a FragmentNode<'a>],
+    scope: &RuntimeScope,
+) -> oxc_allocator::Vec<'a, Statement<'a>> {
+    let mut statements = ast.vec();
+    push_fragment_nodes_statements(ast, &mut statements, nodes, scope, "$$renderer", true);
+    statements
+}
+
 fn render_fragment_nodes_expression<'a>(
     ast: AstBuilder<'a>,
     nodes: &[&'a FragmentNode<'a>],
@@ -73,7 +90,27 @@ fn render_fragment_nodes_expression<'a>(
         )
         .into(),
     );
+    push_fragment_nodes_statements(ast, &mut statements, nodes, scope, "__lux_chunks", false);
 
+    let joined = call_static_method(
+        ast,
+        chunks_ident,
+        "join",
+        ast.vec1(string_expr(ast, "").into()),
+    );
+    statements.push(ast.statement_return(SPAN, Some(joined)));
+    call_iife(ast, statements, scope)
+}
+
+fn push_fragment_nodes_statements<'a>(
+    ast: AstBuilder<'a>,
+    statements: &mut oxc_allocator::Vec<'a, Statement<'a>>,
+    nodes: &[&'a FragmentNode<'a>],
+    scope: &RuntimeScope,
+    sink_name: &str,
+    flush_before_blocking_nodes: bool,
+) {
+    let chunks_ident = ast.expression_identifier(SPAN, ast.ident(sink_name));
     let snippet_blocks = nodes
         .iter()
         .filter_map(|node| match node {
@@ -96,7 +133,22 @@ fn render_fragment_nodes_expression<'a>(
         ));
     }
 
-    for node in nodes {
+    for node in clean_nodes(nodes, scope.preserve_whitespace()) {
+        let node = match node {
+            CleanNode::Text(text) => {
+                statements.push(ast.statement_expression(
+                    SPAN,
+                    call_static_method(
+                        ast,
+                        chunks_ident.clone_in(ast.allocator),
+                        "push",
+                        ast.vec1(string_expr(ast, &text).into()),
+                    ),
+                ));
+                continue;
+            }
+            CleanNode::Node(node) => node,
+        };
         match node {
             FragmentNode::ConstTag(tag) => {
                 statements.push(render_const_tag_declaration_statement(
@@ -107,7 +159,37 @@ fn render_fragment_nodes_expression<'a>(
                 current_scope = current_scope.with_binding_pattern(&tag.declaration.id);
             }
             FragmentNode::SnippetBlock(_) => {}
+            FragmentNode::RegularElement(element)
+                if flush_before_blocking_nodes && is_streamed_element(element) =>
+            {
+                push_streamed_element_statements(
+                    ast,
+                    statements,
+                    element,
+                    &current_scope,
+                    sink_name,
+                );
+            }
             _ => {
+                if flush_before_blocking_nodes
+                    && matches!(
+                        node,
+                        FragmentNode::AwaitBlock(_) | FragmentNode::SvelteBoundary(_)
+                    )
+                {
+                    statements.push(ast.statement_expression(
+                        SPAN,
+                        ast.expression_await(
+                            SPAN,
+                            call_static_method(
+                                ast,
+                                chunks_ident.clone_in(ast.allocator),
+                                "flush",
+                                ast.vec(),
+                            ),
+                        ),
+                    ));
+                }
                 let rendered = render_node_expression(ast, node, &current_scope);
                 statements.push(ast.statement_expression(
                     SPAN,
@@ -121,36 +203,33 @@ fn render_fragment_nodes_expression<'a>(
             }
         }
     }
-
-    let joined = call_static_method(
-        ast,
-        chunks_ident,
-        "join",
-        ast.vec1(string_expr(ast, "").into()),
-    );
-    statements.push(ast.statement_return(SPAN, Some(joined)));
-    call_iife(ast, statements)
 }
 
-fn trim_edge_whitespace_nodes<'a>(nodes: &[&'a FragmentNode<'a>]) -> Vec<&'a FragmentNode<'a>> {
-    let start = nodes
-        .iter()
-        .position(|node| !is_whitespace_text_node(node))
-        .unwrap_or(nodes.len());
-    let end = nodes
-        .iter()
-        .rposition(|node| !is_whitespace_text_node(node))
-        .map(|index| index + 1)
-        .unwrap_or(start);
-
-    nodes[start..end].to_vec()
+/// Whether the element holds, through regular elements only, a node that
+/// streaming flushes before.
+fn is_streamed_element(element: &RegularElement<'_>) -> bool {
+    !matches!(element.name, "textarea" | "select")
+        && element.fragment.nodes.iter().any(|node| match node {
+            FragmentNode::AwaitBlock(_) | FragmentNode::SvelteBoundary(_) => true,
+            FragmentNode::RegularElement(element) => is_streamed_element(element),
+            _ => false,
+        })
 }
 
-fn is_whitespace_text_node(node: &FragmentNode<'_>) -> bool {
-    match node {
-        FragmentNode::Text(text) => text.raw.trim().is_empty(),
-        _ => false,
-    }
+fn push_chunk_statement<'a>(
+    ast: AstBuilder<'a>,
+    sink_name: &str,
+    chunk: Expression<'a>,
+) -> Statement<'a> {
+    ast.statement_expression(
+        SPAN,
+        call_static_method(
+            ast,
+            ast.expression_identifier(SPAN, ast.ident(sink_name)),
+            "push",
+            ast.vec1(chunk.into()),
+        ),
+    )
 }
 
 fn render_node_expression<'a>(
@@ -195,7 +274,7 @@ fn render_node_expression<'a>(
         FragmentNode::IfBlock(block) => render_if_block_expression(ast, block, scope),
         FragmentNode::EachBlock(block) => render_each_block_expression(ast, block, scope),
         FragmentNode::AwaitBlock(block) => render_await_block_expression(ast, block, scope),
-        FragmentNode::KeyBlock(block) => render_fragment_expression(ast, &block.fragment, scope),
+        FragmentNode::KeyBlock(block) => render_key_block_expression(ast, block, scope),
 
         FragmentNode::ConstTag(_) => string_expr(ast, ""),
         FragmentNode::DebugTag(tag) => render_debug_tag_expression(ast, tag, scope),
@@ -239,7 +318,7 @@ fn render_node_expression<'a>(
             scope,
         ),
         FragmentNode::SvelteBoundary(element) => {
-            render_fragment_expression(ast, &element.fragment, scope)
+            render_svelte_boundary_expression(ast, element, scope)
         }
         FragmentNode::SvelteOptionsRaw(_) => string_expr(ast, ""),
     }
@@ -261,7 +340,7 @@ fn render_global_target_node_expression<'a>(
     }
     statements
         .push(ast.statement_return(SPAN, Some(render_fragment_expression(ast, fragment, scope))));
-    call_iife(ast, statements)
+    call_iife(ast, statements, scope)
 }
 
 fn render_debug_tag_expression<'a>(
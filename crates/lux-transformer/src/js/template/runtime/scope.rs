@@ -1,10 +1,11 @@
+use lux_ast::template::root::Namespace;
 use oxc_allocator::CloneIn;
 use oxc_ast::{
     AstBuilder, NONE,
     ast::{
         AccessorProperty, ArrowFunctionExpression, BindingPattern, CallExpression, CatchParameter,
         Class, Expression, FormalParameter, FormalParameterKind, Function, FunctionType,
-        IdentifierReference, MethodDefinition, PropertyDefinition, VariableDeclarator,
+        IdentifierReference, MethodDefinition, NumberBase, PropertyDefinition, VariableDeclarator,
     },
 };
 use oxc_ast_visit::{Visit, VisitMut, walk, walk_mut};
@@ -12,11 +13,17 @@ use oxc_span::SPAN;
 use oxc_syntax::scope::ScopeFlags;
 use rustc_hash::FxHashSet;
 
+use crate::js::component::{LUX_EFFECT_PENDING, LUX_EFFECT_TRACKING};
+
 #[derive(Default, Clone)]
 pub(crate) struct RuntimeScope {
     local_bindings: FxHashSet<String>,
     css_scope: Option<String>,
     store_subscriptions: bool,
+    namespace: Namespace,
+    preserve_whitespace: bool,
+    async_rendering: bool,
+    track_instances: bool,
 }
 
 impl RuntimeScope {
@@ -28,6 +35,22 @@ impl RuntimeScope {
         self.css_scope.as_deref()
     }
 
+    pub(super) fn namespace(&self) -> Namespace {
+        self.namespace
+    }
+
+    pub(super) fn preserve_whitespace(&self) -> bool {
+        self.preserve_whitespace
+    }
+
+    pub(super) fn is_async(&self) -> bool {
+        self.async_rendering
+    }
+
+    pub(super) fn track_instances(&self) -> bool {
+        self.track_instances
+    }
+
     pub(crate) fn from_names<I, S>(names: I) -> Self
     where
         I: IntoIterator<Item = S>,
@@ -63,6 +86,33 @@ impl RuntimeScope {
         next.store_subscriptions = enabled;
         next
     }
+
+    pub(crate) fn with_namespace(&self, namespace: Namespace) -> Self {
+        let mut next = self.clone();
+        next.namespace = namespace;
+        next
+    }
+
+    pub(crate) fn with_preserve_whitespace(&self, preserve_whitespace: bool) -> Self {
+        let mut next = self.clone();
+        next.preserve_whitespace = preserve_whitespace;
+        next
+    }
+
+    pub(crate) fn with_async(&self, async_rendering: bool) -> Self {
+        let mut next = self.clone();
+        next.async_rendering = async_rendering;
+        next
+    }
+
+    /// Records block instances, keyed each items and component bindings so
+    /// that the client runtime can reconcile them, and run their transitions,
+    /// when the component is mounted again, and unbind them when they go away.
+    pub(crate) fn with_tracked_instances(&self, enabled: bool) -> Self {
+        let mut next = self.clone();
+        next.track_instances = enabled;
+        next
+    }
 }
 
 pub(super) fn resolve_expression<'a>(
@@ -70,7 +120,12 @@ pub(super) fn resolve_expression<'a>(
     expression: Expression<'a>,
     scope: &RuntimeScope,
 ) -> Expression<'a> {
-    let expression = strip_typescript_expression(ast, expression);
+    let mut expression = strip_typescript_expression(ast, expression);
+    TemplateRuneRewriter {
+        ast,
+        client: scope.track_instances,
+    }
+    .visit_expression(&mut expression);
     let expression = if scope.store_subscriptions {
         rewrite_store_subscriptions(ast, expression, scope)
     } else {
@@ -210,6 +265,7 @@ fn is_runtime_global(name: &str) -> bool {
     matches!(
         name,
         "_props"
+            | "__lux_render_state"
             | "__lux_store_get"
             | "__lux_store_values"
             | "undefined"
@@ -284,6 +340,14 @@ struct TypeScriptExpressionStripper<'a> {
     ast: AstBuilder<'a>,
 }
 
+/// Answers `$effect.tracking()` and `$effect.pending()` in the template. On
+/// the client the runtime knows the rendering instance and its boundary; the
+/// server tracks nothing and never waits.
+struct TemplateRuneRewriter<'a> {
+    ast: AstBuilder<'a>,
+    client: bool,
+}
+
 struct StoreSubscriptionRewriter<'ast, 'scope> {
     ast: AstBuilder<'ast>,
     scope: &'scope RuntimeScope,
@@ -327,6 +391,47 @@ impl<'ast> VisitMut<'ast> for StoreSubscriptionRewriter<'ast, '_> {
     }
 }
 
+impl<'a> VisitMut<'a> for TemplateRuneRewriter<'a> {
+    fn visit_expression(&mut self, expression: &mut Expression<'a>) {
+        walk_mut::walk_expression(self, expression);
+
+        let Expression::CallExpression(call) = expression else {
+            return;
+        };
+        let Expression::StaticMemberExpression(member) = &call.callee else {
+            return;
+        };
+        if !matches!(&member.object, Expression::Identifier(object) if object.name == "$effect") {
+            return;
+        }
+        let ast = self.ast;
+        *expression = match (member.property.name.as_str(), self.client) {
+            ("tracking", true) => ast.expression_call(
+                SPAN,
+                ast.expression_identifier(SPAN, ast.ident(LUX_EFFECT_TRACKING)),
+                NONE,
+                ast.vec1(
+                    ast.expression_identifier(SPAN, ast.ident("__lux_render_state"))
+                        .into(),
+                ),
+                false,
+            ),
+            ("pending", true) => ast.expression_call(
+                SPAN,
+                ast.expression_identifier(SPAN, ast.ident(LUX_EFFECT_PENDING)),
+                NONE,
+                ast.vec(),
+                false,
+            ),
+            ("tracking", false) => ast.expression_boolean_literal(SPAN, false),
+            ("pending", false) => {
+                ast.expression_numeric_literal(SPAN, 0.0, None, NumberBase::Decimal)
+            }
+            _ => return,
+        };
+    }
+}
+
 impl<'a> VisitMut<'a> for TypeScriptExpressionStripper<'a> {
     fn visit_expression(&mut self, expression: &mut Expression<'a>) {
         while let Some(inner) = strip_typescript_expression_wrapper(self.ast, expression) {
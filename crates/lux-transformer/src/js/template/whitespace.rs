@@ -0,0 +1,137 @@
+use std::borrow::Cow;
+
+use lux_ast::template::root::FragmentNode;
+
+/// A fragment child after whitespace normalization. Text nodes always come out
+/// as [`CleanNode::Text`] carrying their normalized raw source.
+pub(super) enum CleanNode<'n, 'a> {
+    Node(&'n FragmentNode<'a>),
+    Text(Cow<'n, str>),
+}
+
+/// Normalizes fragment whitespace the way Svelte does.
+///
+/// Hoisted nodes (`{@const}`, snippets, `<svelte:head>`, ...) move to the front,
+/// whitespace at the fragment edges is dropped and whitespace runs next to
+/// anything but an expression tag collapse to a single space. With
+/// `preserve_whitespace` the text is passed through untouched.
+pub(super) fn clean_nodes<'n, 'a>(
+    nodes: &[&'n FragmentNode<'a>],
+    preserve_whitespace: bool,
+) -> Vec<CleanNode<'n, 'a>> {
+    let mut cleaned = Vec::with_capacity(nodes.len());
+    let mut regular = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        if is_hoisted_node(node) {
+            cleaned.push(CleanNode::Node(node));
+        } else {
+            regular.push(*node);
+        }
+    }
+
+    if preserve_whitespace {
+        cleaned.extend(regular.into_iter().map(|node| match node {
+            FragmentNode::Text(text) => CleanNode::Text(Cow::Borrowed(text.raw)),
+            _ => CleanNode::Node(node),
+        }));
+        return cleaned;
+    }
+
+    let start = regular
+        .iter()
+        .position(|node| !is_whitespace_text_node(node))
+        .unwrap_or(regular.len());
+    let end = regular
+        .iter()
+        .rposition(|node| !is_whitespace_text_node(node))
+        .map_or(start, |index| index + 1);
+    let regular = &regular[start..end];
+
+    let mut previous_text_ends_with_whitespace = false;
+    for (index, node) in regular.iter().enumerate() {
+        let FragmentNode::Text(text) = node else {
+            cleaned.push(CleanNode::Node(node));
+            continue;
+        };
+
+        let previous = index.checked_sub(1).map(|index| regular[index]);
+        let next = regular.get(index + 1);
+        let mut value = text.raw;
+        let mut prefix = "";
+        let mut suffix = "";
+
+        if previous.is_none() {
+            value = value.trim_start_matches(is_whitespace_char);
+        } else if !matches!(previous, Some(FragmentNode::ExpressionTag(_))) {
+            let trimmed = value.trim_start_matches(is_whitespace_char);
+            if trimmed.len() != value.len() {
+                let previous_is_text = matches!(previous, Some(FragmentNode::Text(_)));
+                if !(previous_is_text && previous_text_ends_with_whitespace) {
+                    prefix = " ";
+                }
+                value = trimmed;
+            }
+        }
+
+        if next.is_none() {
+            value = value.trim_end_matches(is_whitespace_char);
+        } else if !matches!(next, Some(FragmentNode::ExpressionTag(_))) {
+            let trimmed = value.trim_end_matches(is_whitespace_char);
+            if trimmed.len() != value.len() {
+                suffix = " ";
+                value = trimmed;
+            }
+        }
+
+        let text = if prefix.is_empty() && suffix.is_empty() {
+            Cow::Borrowed(value)
+        } else {
+            Cow::Owned(format!("{prefix}{value}{suffix}"))
+        };
+        previous_text_ends_with_whitespace = text.ends_with(is_whitespace_char);
+        if !text.is_empty() {
+            cleaned.push(CleanNode::Text(text));
+        }
+    }
+
+    cleaned
+}
+
+/// Whitespace-only children are insignificant in these parents and are
+/// dropped rather than collapsed to a space.
+pub(super) fn drops_whitespace_children(parent_name: &str, in_svg: bool) -> bool {
+    matches!(
+        parent_name,
+        "select" | "tr" | "table" | "tbody" | "thead" | "tfoot" | "colgroup" | "datalist"
+    ) || (in_svg && !matches!(parent_name, "text" | "tspan" | "textPath"))
+}
+
+/// `<pre>` and `<textarea>` keep the whitespace of everything inside them.
+pub(super) fn preserves_whitespace_children(parent_name: &str) -> bool {
+    matches!(parent_name, "pre" | "textarea")
+}
+
+pub(super) fn is_whitespace_text_node(node: &FragmentNode<'_>) -> bool {
+    match node {
+        FragmentNode::Text(text) => text.raw.chars().all(is_whitespace_char),
+        _ => false,
+    }
+}
+
+fn is_whitespace_char(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\r' | '\n')
+}
+
+fn is_hoisted_node(node: &FragmentNode<'_>) -> bool {
+    matches!(
+        node,
+        FragmentNode::ConstTag(_)
+            | FragmentNode::DebugTag(_)
+            | FragmentNode::SnippetBlock(_)
+            | FragmentNode::TitleElement(_)
+            | FragmentNode::SvelteHead(_)
+            | FragmentNode::SvelteBody(_)
+            | FragmentNode::SvelteWindow(_)
+            | FragmentNode::SvelteDocument(_)
+    )
+}
@@ -2,9 +2,11 @@ mod css;
 mod js;
 mod runtime;
 
+use std::fmt;
+
 use lux_ast::analysis::AnalysisTables;
+use lux_ast::common::Span;
 use lux_ast::template::root::Root;
-use lux_utils::hash::hash;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RuntimeModule {
@@ -18,15 +20,121 @@ pub struct TransformResult {
     pub css: Option<String>,
     pub css_hash: Option<String>,
     pub css_scope: Option<String>,
+    /// `url(...)` and `@import` references of the rules of the component
+    /// stylesheet that are emitted.
+    pub css_assets: Vec<CssAssetReference>,
     pub runtime_modules: Vec<RuntimeModule>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CssAssetKind {
+    Url,
+    Import,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssAssetReference {
+    pub kind: CssAssetKind,
+    /// The referenced path as written, without quotes.
+    pub value: String,
+    /// Span of the `url(...)` or import string in the component source.
+    pub span: Span,
+}
+
+/// Rewrites CSS asset references; returning `None` keeps a reference as
+/// written.
+#[derive(Clone, Copy)]
+pub struct CssUrlResolver<'a>(pub &'a dyn Fn(&CssAssetReference) -> Option<String>);
+
+impl fmt::Debug for CssUrlResolver<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CssUrlResolver")
+    }
+}
+
+impl PartialEq for CssUrlResolver<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for CssUrlResolver<'_> {}
+
+/// How the CSS scope class of a component is named, like Svelte's `cssHash`
+/// option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CssHash<'a> {
+    /// A class name in which `[name]` and `[hash]` are replaced with the
+    /// component name and the default hash, e.g. `[name]-[hash]`.
+    Template(&'a str),
+    Callback(CssHashCallback<'a>),
+}
+
+/// Computes the CSS scope class of a component.
+#[derive(Clone, Copy)]
+pub struct CssHashCallback<'a>(pub &'a dyn Fn(&CssHashInput<'_>) -> String);
+
+impl fmt::Debug for CssHashCallback<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CssHashCallback")
+    }
+}
+
+impl PartialEq for CssHashCallback<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for CssHashCallback<'_> {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CssHashInput<'i> {
+    /// Contents of the `<style>` element.
+    pub css: &'i str,
+    /// Component name derived from the filename, e.g. `Button`.
+    pub name: &'i str,
+    pub filename: Option<&'i str>,
+    /// Hash of the filename, or of the CSS when there is none, as used by
+    /// the default `svelte-[hash]` class.
+    pub default_hash: &'i str,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum TransformTarget {
+    #[default]
     Server,
     Client,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransformOptions<'a> {
+    pub target: TransformTarget,
+    pub filename: Option<&'a str>,
+    /// Emits an async server component that awaits `{#await}` blocks and
+    /// `await` expressions instead of rendering their pending branch.
+    pub experimental_async: bool,
+    /// Adds a `stream($$renderer, props)` method to server components that
+    /// pushes markup in chunks and flushes before `{#await}` and
+    /// `<svelte:boundary>`.
+    pub streaming: bool,
+    /// Minifies both the external stylesheet and CSS injected into the head.
+    pub minify_css: bool,
+    /// Keeps `$inspect` and `$inspect.trace` in client components, which log
+    /// the inspected values and the reactive bindings that made a function
+    /// run again. They are stripped otherwise.
+    pub dev: bool,
+    /// Flattens nested CSS rules into plain selectors for browsers without
+    /// native nesting support.
+    pub lower_css_nesting: bool,
+    /// Called for each `url(...)` and `@import` of the emitted stylesheet;
+    /// the URL it returns replaces the reference in both the external and
+    /// the injected CSS.
+    pub css_url_resolver: Option<CssUrlResolver<'a>>,
+    /// Overrides the default `svelte-[hash]` scope class.
+    pub css_hash: Option<CssHash<'a>>,
+}
+
 pub fn transform(root: &Root<'_>, analysis: &AnalysisTables) -> TransformResult {
     transform_with_filename(root, analysis, None)
 }
@@ -53,12 +161,31 @@ pub fn transform_for_target_with_filename(
     target: TransformTarget,
     filename: Option<&str>,
 ) -> TransformResult {
+    transform_with_options(
+        root,
+        analysis,
+        &TransformOptions {
+            target,
+            filename,
+            ..TransformOptions::default()
+        },
+    )
+}
+
+pub fn transform_with_options(
+    root: &Root<'_>,
+    analysis: &AnalysisTables,
+    options: &TransformOptions<'_>,
+) -> TransformResult {
+    let css_assets = root
+        .css
+        .as_ref()
+        .map(|stylesheet| css::collect_asset_references(stylesheet, analysis))
+        .unwrap_or_default();
     let (css, css_hash, css_scope) = match &root.css {
         Some(stylesheet) => {
-            let css_hash_input = css_hash_input(stylesheet.content_styles, filename);
-            let css_hash = hash(&css_hash_input);
-            let css_scope = format!("svelte-{css_hash}");
-            let css = css::render_stylesheet(stylesheet, analysis, &css_scope, &root.fragment);
+            let (css_hash, css_scope) = css::css_scope(stylesheet, options);
+            let css = css::render_stylesheet(stylesheet, analysis, &css_scope, options);
             (Some(css), Some(css_hash), Some(css_scope))
         }
         None => (None, None, None),
@@ -69,9 +196,10 @@ pub fn transform_for_target_with_filename(
         analysis,
         css_hash.as_deref(),
         css_scope.as_deref(),
-        target,
+        css.as_ref().map(|css| css.embedded.as_str()),
+        options,
     );
-    let (runtime_specifier, runtime_source) = match target {
+    let (runtime_specifier, runtime_source) = match options.target {
         TransformTarget::Server => (
             runtime::SERVER_RUNTIME_SPECIFIER,
             runtime::server_runtime_source(),
@@ -92,16 +220,10 @@ pub fn transform_for_target_with_filename(
 
     TransformResult {
         js: component.js,
-        css,
+        css: css.map(|css| css.external),
         css_hash,
         css_scope,
+        css_assets,
         runtime_modules,
     }
 }
-
-fn css_hash_input(css: &str, filename: Option<&str>) -> String {
-    match filename {
-        Some("(unknown)") | None => css.to_string(),
-        Some(filename) => filename.replace('\\', "/"),
-    }
-}
@@ -30,11 +30,43 @@ const BOOLEAN_ATTRIBUTE_NAMES = new Set([
 ]);
 const INVALID_ATTR_NAME_CHAR_REGEX =
   /[\s'">/=\u{FDD0}-\u{FDEF}\u{FFFE}\u{FFFF}\u{1FFFE}\u{1FFFF}\u{2FFFE}\u{2FFFF}\u{3FFFE}\u{3FFFF}\u{4FFFE}\u{4FFFF}\u{5FFFE}\u{5FFFF}\u{6FFFE}\u{6FFFF}\u{7FFFE}\u{7FFFF}\u{8FFFE}\u{8FFFF}\u{9FFFE}\u{9FFFF}\u{AFFFE}\u{AFFFF}\u{BFFFE}\u{BFFFF}\u{CFFFE}\u{CFFFF}\u{DFFFE}\u{DFFFF}\u{EFFFE}\u{EFFFF}\u{FFFFE}\u{FFFFF}\u{10FFFE}\u{10FFFF}]/u;
+const NAMESPACE_URIS = {
+  svg: "http://www.w3.org/2000/svg",
+  mathml: "http://www.w3.org/1998/Math/MathML"
+};
 let props_id_counter = 0;
 const anchor_regions = new WeakMap();
 const anchor_head_state = new WeakMap();
 const anchor_mount_state = new WeakMap();
+const anchor_boundary_failures = new WeakMap();
+const anchor_boundary_pending = new WeakMap();
+const anchor_pending_blocks = new WeakMap();
+const anchor_mounted_blocks = new WeakMap();
+const anchor_instances = new WeakMap();
+const proxy_targets = new WeakMap();
+const target_proxies = new WeakMap();
+const state_versions = new WeakMap();
+const live_transitions = new WeakMap();
+const rendered_attributes = new WeakMap();
+const outroing_nodes = new WeakSet();
+const delegated_root_events = new WeakMap();
+const DELEGATED_HANDLER_PREFIX = "__lux_on_";
+const DELEGATED_ROOT_KEY = "__lux_delegated_root";
+const moved_element_rects = new WeakMap();
+const object_key_ids = new WeakMap();
+let object_key_counter = 0;
+const BLOCK_MARKER = "lux-block:";
+const BLOCK_END_MARKER = "/lux-block";
+const ATTACHMENT_KEY = "@attach";
 let current_render_state = null;
+let current_boundary = null;
+let current_pending = null;
+let current_block_path = "";
+let pending_bind_this = null;
+let scheduled_instances = null;
+let rerendering_anchor = null;
+let active_effect = null;
+const RENDER_STATE_LISTS = ["events", "bindings", "actions", "transitions", "animations"];
 
 export function stringify(value) {
   if (typeof value === "string") return value;
@@ -167,7 +199,11 @@ export function attributes(
     .join("")
     .concat(
       attr("class", merged_class, false),
-      attr("style", merged_style, false)
+      attr("style", merged_style, false),
+      Object.getOwnPropertySymbols(source)
+        .filter((key) => key.description === ATTACHMENT_KEY)
+        .map((key) => attach_attr(() => source[key]))
+        .join("")
     );
 }
 
@@ -197,18 +233,42 @@ export function rest_props(props, exclude = []) {
       result[key] = value;
     }
   }
+  for (const key of Object.getOwnPropertySymbols(source)) {
+    result[key] = source[key];
+  }
   return result;
 }
 
-export function begin_render() {
+export function begin_render(anchor = null, remount = null) {
+  const mounted = is_mount_target(anchor);
+  // Calling the component again outside of a re-render is a new mount,
+  // which replaces the one at the anchor.
+  if (mounted && anchor !== rerendering_anchor) {
+    cleanup_mount(anchor);
+  }
+  if (mounted) {
+    rerendering_anchor = null;
+  }
+  // Nested components record their events, bindings and the like with the
+  // component that mounts them.
+  const lists = !mounted && current_render_state ? current_render_state : null;
   const state = {
-    events: [],
-    bindings: [],
-    actions: [],
-    transitions: [],
-    animations: [],
-    parent: current_render_state
+    events: lists?.events ?? [],
+    bindings: lists?.bindings ?? [],
+    actions: lists?.actions ?? [],
+    transitions: lists?.transitions ?? [],
+    animations: lists?.animations ?? [],
+    parent: current_render_state,
+    anchor: mounted ? anchor : null,
+    remount: typeof remount === "function" ? remount : null,
+    boundary_count: 0,
+    blocks: new Set(),
+    instances: null,
+    previous_instances: null,
+    rendered_instances: [],
+    child_counts: new Map()
   };
+  state.instance = claim_instance(state);
   current_render_state = state;
   return state;
 }
@@ -216,6 +276,13 @@ export function begin_render() {
 export function end_render(state) {
   if (state && current_render_state === state) {
     current_render_state = state.parent ?? null;
+    if (state.instance) {
+      root_render_state(state).rendered_instances.push(state.instance);
+    }
+    if (state.anchor) {
+      anchor_pending_blocks.set(state.anchor, state.blocks);
+      finish_instances(state);
+    }
     return {
       events: state.events,
       bindings: state.bindings,
@@ -233,17 +300,387 @@ export function end_render(state) {
   };
 }
 
-export function render_component(component, props) {
-  if (component && typeof component.render === "function") {
-    return component.render(props ?? {});
+export function render_component(component, props, render_state, bind_this) {
+  const binding =
+    typeof bind_this === "function" ? { bind: bind_this, component, claimed: false } : null;
+  pending_bind_this = binding;
+  try {
+    if (component && typeof component.render === "function") {
+      return component.render(props ?? {});
+    }
+    if (typeof component === "function") {
+      return component(props ?? {});
+    }
+    return "";
+  } finally {
+    pending_bind_this = null;
+    // Component instances are bound once the whole render is complete.
+    if (binding && !binding.claimed) {
+      binding.bind(component);
+    }
   }
-  if (typeof component === "function") {
-    return component(props ?? {});
+}
+
+// Reads a `$state` binding of the rendering component instance, which keeps
+// the value last written to it from one render to the next.
+export function state(render_state, name, initial) {
+  const instance = render_state?.instance;
+  if (!instance) {
+    return initial;
   }
-  return "";
+  if (instance.values.has(name)) {
+    return instance.values.get(name);
+  }
+  instance.values.set(name, initial);
+  return initial;
+}
+
+// The props of the rendering component instance. Props that the component
+// wrote to and that its parent does not bind keep their local value until
+// the parent passes a different one.
+export function props(render_state, source) {
+  const instance = render_state?.instance;
+  if (!instance) {
+    return source;
+  }
+  instance.props = source;
+  if (instance.overrides.size === 0) {
+    return source;
+  }
+  const local = Object.defineProperties({}, Object.getOwnPropertyDescriptors(source));
+  for (const [name, override] of instance.overrides) {
+    if (!Object.is(source[name], override.parent)) {
+      instance.overrides.delete(name);
+      continue;
+    }
+    Object.defineProperty(local, name, {
+      value: override.value,
+      writable: true,
+      enumerable: true,
+      configurable: true
+    });
+  }
+  return local;
+}
+
+// Records the values of the `$state` bindings and props that an assignment
+// wrote to, and renders the component again. Bound props are written back
+// through the setter of the parent.
+export function set(render_state, result, values) {
+  const instance = render_state?.instance;
+  if (!instance || instance.destroyed) {
+    return result;
+  }
+  for (const [name, value] of Object.entries(values)) {
+    if (instance.values.has(name)) {
+      instance.values.set(name, value);
+      continue;
+    }
+    const setter = instance.props
+      ? Object.getOwnPropertyDescriptor(instance.props, name)?.set
+      : undefined;
+    if (typeof setter === "function") {
+      setter.call(instance.props, value);
+      continue;
+    }
+    instance.overrides.set(name, { parent: instance.props?.[name], value });
+    instance.forced = true;
+  }
+  schedule_update(instance);
+  return result;
+}
+
+export function boundary(render_children, failed, onerror, pending) {
+  const root = root_render_state(current_render_state);
+  const anchor = root?.anchor ?? null;
+  const index = root ? root.boundary_count++ : 0;
+  const reset = () => {
+    if (!anchor) {
+      return;
+    }
+    anchor_boundary_failures.get(anchor)?.delete(index);
+    rerender(anchor, root.remount);
+  };
+  const render_failed = (error) =>
+    typeof failed === "function" ? failed(error, reset) : "";
+  const handle = (error) => {
+    if (typeof failed !== "function" && typeof onerror !== "function") {
+      throw error;
+    }
+    if (anchor) {
+      let failures = anchor_boundary_failures.get(anchor);
+      if (!failures) {
+        failures = new Map();
+        anchor_boundary_failures.set(anchor, failures);
+      }
+      failures.set(index, { error });
+    }
+    if (typeof onerror === "function") {
+      onerror(error, reset);
+    }
+  };
+
+  const failure = anchor ? anchor_boundary_failures.get(anchor)?.get(index) : undefined;
+  if (failure) {
+    return render_failed(failure.error);
+  }
+
+  const state = current_render_state;
+  const marks = state ? RENDER_STATE_LISTS.map((list) => state[list].length) : null;
+  const parent_boundary = current_boundary;
+  const parent_pending = current_pending;
+  current_pending = boundary_pending(anchor, index);
+  // Errors thrown by actions, transitions and animations once the children
+  // are mounted show the failed snippet on the next render.
+  current_boundary = (error) => {
+    handle(error);
+    queueMicrotask(() => rerender(anchor, root?.remount));
+  };
+  try {
+    const result = render_children();
+    if (result && typeof result.then === "function") {
+      const pending_record = current_pending;
+      pending_record.count++;
+      const settle = () => {
+        pending_record.count--;
+      };
+      result.then(settle, settle);
+      if (typeof pending === "function") {
+        return pending();
+      }
+    }
+    return result;
+  } catch (error) {
+    if (state) {
+      RENDER_STATE_LISTS.forEach((list, i) => {
+        state[list].length = marks[i];
+      });
+    }
+    handle(error);
+    return render_failed(error);
+  } finally {
+    current_boundary = parent_boundary;
+    current_pending = parent_pending;
+  }
+}
+
+// Runs after the component is rendered and mounted, and again after each
+// render in which a reactive binding or object that its last run read
+// changed, once the previous run is torn down.
+export function effect(render_state, fn) {
+  register_effect(render_state, fn, false);
+}
+
+// Like `effect`, but runs before the DOM is updated.
+export function effect_pre(render_state, fn) {
+  register_effect(render_state, fn, true);
 }
 
-export function event_attr(name, handler, modifiers = []) {
+// Runs `fn` and the effects it creates outside of the component lifecycle,
+// until the returned function is called. A root created while rendering a
+// component instance is kept across its renders.
+export function effect_root(render_state, fn) {
+  const instance = render_state?.instance;
+  const persisted = instance && !active_effect && current_render_state === render_state;
+  const index = persisted ? instance.root_count++ : -1;
+  if (persisted && instance.roots[index]) {
+    return instance.roots[index];
+  }
+
+  const record = run_effect_function(
+    fn,
+    active_effect?.pending ?? current_pending,
+    active_effect?.instance ?? instance ?? null
+  );
+  const destroy = () => teardown_effect(record);
+  if (persisted) {
+    instance.roots[index] = destroy;
+  }
+  return destroy;
+}
+
+// Reads are tracked inside effects, and in the template of the component
+// being rendered, which passes its render state.
+export function effect_tracking(render_state) {
+  return active_effect !== null || (render_state != null && current_render_state === render_state);
+}
+
+// The number of promises pending in the nearest boundary.
+export function effect_pending() {
+  return (active_effect ? active_effect.pending : current_pending)?.count ?? 0;
+}
+
+// Reads a reactive binding of a component instance from a function. The
+// running effect depends on the bindings it read, however indirectly.
+export function read(render_state, name, value) {
+  const instance = render_state?.instance;
+  if (instance && active_effect && !current_render_state) {
+    let reads = active_effect.reads.get(instance);
+    if (!reads) {
+      reads = new Map();
+      active_effect.reads.set(instance, reads);
+    }
+    if (!reads.has(name)) {
+      reads.set(name, { value, version: undefined });
+    }
+  }
+  return value;
+}
+
+// Keeps the current values of the reactive bindings of the rendering
+// instance, which the reads of its effects are compared against.
+export function track(render_state, values) {
+  const instance = render_state?.instance;
+  if (instance) {
+    instance.tracked = values;
+  }
+}
+
+// Makes plain objects and arrays deeply reactive: writes to them, or to the
+// objects and arrays they contain, render again the components that read them.
+export function proxy(value, source = create_source()) {
+  if (!is_proxyable(value) || proxy_targets.has(value)) {
+    return value;
+  }
+  const existing = target_proxies.get(value);
+  if (existing) {
+    return existing;
+  }
+
+  const result = new Proxy(value, {
+    get(target, key, receiver) {
+      observe_source(source);
+      const child = Reflect.get(target, key, receiver);
+      const descriptor = Object.getOwnPropertyDescriptor(target, key);
+      if (descriptor && !descriptor.configurable && !descriptor.writable) {
+        return child;
+      }
+      return proxy(child, source);
+    },
+    set(target, key, child) {
+      const raw = proxy_targets.get(child) ?? child;
+      const changed = !Object.hasOwn(target, key) || !Object.is(target[key], raw);
+      const result = Reflect.set(target, key, raw);
+      if (changed) {
+        notify(source);
+      }
+      return result;
+    },
+    defineProperty(target, key, descriptor) {
+      const result = Reflect.defineProperty(target, key, descriptor);
+      notify(source);
+      return result;
+    },
+    deleteProperty(target, key) {
+      const had = Object.hasOwn(target, key);
+      const result = Reflect.deleteProperty(target, key);
+      if (had) {
+        notify(source);
+      }
+      return result;
+    }
+  });
+  proxy_targets.set(result, value);
+  target_proxies.set(value, result);
+  state_versions.set(result, source);
+  return result;
+}
+
+// Returns a copy of `value` that is no longer reactive.
+export function snapshot(value) {
+  return clone_snapshot(value, new Map());
+}
+
+// `$inspect(...)` in dev mode: reports the values with `callback`, or logs
+// them, when the component first renders them and whenever they change.
+export function inspect(render_state, site, values, callback) {
+  const instance = render_state?.instance;
+  if (!instance) {
+    return;
+  }
+  const versions = values.map((value) => state_versions.get(value)?.version);
+  const previous = instance.inspections.get(site);
+  instance.inspections.set(site, { values, versions });
+  if (
+    previous &&
+    previous.values.length === values.length &&
+    previous.values.every(
+      (value, i) => Object.is(value, values[i]) && previous.versions[i] === versions[i]
+    )
+  ) {
+    return;
+  }
+  const type = previous ? "update" : "init";
+  const snapshots = values.map(snapshot);
+  if (typeof callback === "function") {
+    callback(type, ...snapshots);
+  } else {
+    console.log(type, ...snapshots);
+  }
+}
+
+// `$inspect.trace(label)` in dev mode: logs which of the reactive bindings
+// read by the enclosing function changed since it last ran.
+export function inspect_trace(render_state, site, label, dependencies) {
+  const instance = render_state?.instance;
+  const values = dependencies();
+  const versions = {};
+  for (const name of Object.keys(values)) {
+    versions[name] = state_versions.get(values[name])?.version;
+  }
+  const previous = instance?.inspections.get(site);
+  instance?.inspections.set(site, { values, versions });
+  const changed = Object.keys(values).filter(
+    (name) =>
+      !previous ||
+      !Object.hasOwn(previous.values, name) ||
+      !Object.is(previous.values[name], values[name]) ||
+      previous.versions[name] !== versions[name]
+  );
+  if (previous && changed.length === 0) {
+    return;
+  }
+  console.groupCollapsed(`${label} ${previous ? "re-ran" : "ran"}`);
+  for (const name of changed) {
+    console.log(name, snapshot(values[name]));
+  }
+  console.groupEnd();
+}
+
+// Tracks a read of a class field declared with `$state`.
+export function observe(object) {
+  observe_source(object_source(object));
+}
+
+// Renders again the components that read the class fields declared with
+// `$state` of the object, after a write to one of them.
+export function invalidate(object) {
+  notify(object_source(object));
+}
+
+// Renders one instance of an if branch, each item or key block. Instances
+// are delimited by marker comments naming the block and its key, so that a
+// re-mount reuses the DOM of the instances that still exist, moves keyed
+// items, and runs transitions on those created or destroyed.
+export function block(id, key, render_children) {
+  const root = root_render_state(current_render_state);
+  if (!root?.anchor) {
+    return render_children();
+  }
+
+  const identity = encode_block_identity(`${id}:${key_identity(key)}`);
+  const parent_path = current_block_path;
+  current_block_path = `${parent_path}/${identity}`;
+  root.blocks.add(current_block_path);
+  try {
+    const html = render_children();
+    return `<!--${BLOCK_MARKER}${identity}-->${html}<!--${BLOCK_END_MARKER}-->`;
+  } finally {
+    current_block_path = parent_path;
+  }
+}
+
+export function event_attr(name, handler, modifiers = [], delegated = false) {
   if (!current_render_state || typeof handler !== "function") {
     return "";
   }
@@ -258,7 +695,8 @@ export function event_attr(name, handler, modifiers = []) {
     id,
     name: event_name,
     handler,
-    modifiers: normalize_modifiers(modifiers)
+    modifiers: normalize_modifiers(modifiers),
+    delegated: Boolean(delegated)
   });
 
   return ` data-lux-on-${event_name}="${id}"`;
@@ -344,13 +782,47 @@ export function use_attr(name, action, parameter) {
     id,
     name: action_name,
     action,
-    parameter
+    parameter,
+    boundary: current_boundary
   });
 
   return ` data-lux-use-${action_name}="${id}"`;
 }
 
-export function transition_attr(name, transition, parameter, intro = true, outro = true) {
+// Attachments run on their element once it is mounted, like effects: they
+// run again, after their cleanup, only once the reactive state that they or
+// the `get_attachment` expression read changed.
+export function attach_attr(get_attachment) {
+  if (!current_render_state || typeof get_attachment !== "function") {
+    return "";
+  }
+
+  const id = current_render_state.actions.length;
+  const name = `attach-${id}`;
+  current_render_state.actions.push({
+    id,
+    name,
+    attachment: get_attachment,
+    boundary: current_boundary
+  });
+
+  return ` data-lux-use-${name}="${id}"`;
+}
+
+// A key under which component props carry an attachment to the element
+// that the props are spread onto.
+export function attachment_key() {
+  return Symbol(ATTACHMENT_KEY);
+}
+
+export function transition_attr(
+  name,
+  transition,
+  parameter,
+  intro = true,
+  outro = true,
+  global = false
+) {
   if (!current_render_state || typeof transition !== "function") {
     return "";
   }
@@ -367,7 +839,10 @@ export function transition_attr(name, transition, parameter, intro = true, outro
     transition,
     parameter,
     intro: Boolean(intro),
-    outro: Boolean(outro)
+    outro: Boolean(outro),
+    global: Boolean(global),
+    path: current_block_path,
+    boundary: current_boundary
   });
 
   return ` data-lux-transition-${transition_name}="${id}"`;
@@ -388,7 +863,8 @@ export function animate_attr(name, animation, parameter) {
     id,
     name: animation_name,
     animation,
-    parameter
+    parameter,
+    boundary: current_boundary
   });
 
   return ` data-lux-animate-${animation_name}="${id}"`;
@@ -419,6 +895,12 @@ export function cleanup_mount(anchor) {
 
   clear_anchor_mount_state(anchor);
   clear_anchor_head_state(anchor);
+  anchor_mounted_blocks.delete(anchor);
+  for (const instance of anchor_instances.get(anchor)?.values() ?? []) {
+    destroy_instance(instance);
+  }
+  anchor_instances.delete(anchor);
+  anchor_boundary_pending.delete(anchor);
 
   const region = anchor_regions.get(anchor);
   if (region && region.start?.parentNode && region.end?.parentNode) {
@@ -440,17 +922,51 @@ export function cleanup_mount(anchor) {
   }
 }
 
-export function mount_html(anchor, html) {
+export function mount_html(anchor, html, namespace = "html") {
   if (!is_mount_target(anchor)) return;
+  // Transitions keep running on the elements that a re-mount reuses, and
+  // attachments are kept until `mount_actions` knows which ones changed.
+  const mount_state = anchor_mount_state.get(anchor);
+  const transition_cleanups = mount_state?.transition_cleanups ?? [];
+  const attachments = mount_state?.attachments ?? new Map();
+  if (mount_state) {
+    mount_state.transition_cleanups = [];
+    mount_state.attachments = new Map();
+  }
   clear_anchor_mount_state(anchor);
+  ensure_anchor_mount_state(anchor).attachments = attachments;
+
+  const previous = anchor_mounted_blocks.get(anchor);
+  const blocks = {
+    current: anchor_pending_blocks.get(anchor) ?? new Set(),
+    previous: previous?.current ?? null
+  };
+  anchor_pending_blocks.delete(anchor);
+  anchor_mounted_blocks.set(anchor, blocks);
 
-  const safe_html = stringify(html);
+  replace_html(anchor, stringify(html), namespace, previous ? blocks : null);
+  run_cleanup_list(transition_cleanups.filter((cleanup) => !cleanup.element?.isConnected));
+}
+
+function replace_html(anchor, html, namespace, reconcile) {
+  const namespace_uri = NAMESPACE_URIS[namespace] ?? null;
 
   if (anchor.nodeType === 1 || anchor.nodeType === 11 || anchor.nodeType === 9) {
     // Element, DocumentFragment, Document
     const host = anchor.nodeType === 9 ? anchor.body ?? anchor.documentElement : anchor;
     if (host && "innerHTML" in host) {
-      host.innerHTML = safe_html;
+      if (reconcile && host.firstChild) {
+        const fragment = create_fragment(resolve_owner_document(anchor), html, namespace);
+        morph_range(host, host.firstChild, null, Array.from(fragment.childNodes), reconcile);
+        return;
+      }
+      if (!namespace_uri || host.namespaceURI === namespace_uri) {
+        host.innerHTML = html;
+        record_rendered_attributes(host);
+        return;
+      }
+      host.innerHTML = "";
+      host.appendChild(create_fragment(resolve_owner_document(anchor), html, namespace));
       return;
     }
   }
@@ -463,11 +979,14 @@ export function mount_html(anchor, html) {
   const region = ensure_anchor_region(anchor, owner_document);
   if (!region) return;
 
+  const fragment = create_fragment(owner_document, html, namespace);
+  if (reconcile && region.start.nextSibling !== region.end) {
+    morph_range(parent, region.start.nextSibling, region.end, Array.from(fragment.childNodes), reconcile);
+    return;
+  }
   clear_region(region);
 
-  const template = owner_document.createElement("template");
-  template.innerHTML = safe_html;
-  parent.insertBefore(template.content, region.end);
+  parent.insertBefore(fragment, region.end);
 }
 
 export function mount_head(anchor, html) {
@@ -509,6 +1028,7 @@ export function mount_events(anchor, events) {
   if (event_targets.length === 0) {
     return;
   }
+  const delegation_root = resolve_delegation_root(anchor);
 
   for (const event of events) {
     if (!event || typeof event.name !== "string" || typeof event.id !== "number") {
@@ -527,7 +1047,14 @@ export function mount_events(anchor, events) {
     const attr_value = String(event.id);
 
     for (const root of event_targets) {
-      bind_event_for_root(root, attr_name, attr_value, event, mount_state.event_cleanups);
+      bind_event_for_root(
+        root,
+        attr_name,
+        attr_value,
+        event,
+        delegation_root,
+        mount_state.event_cleanups
+      );
     }
   }
 }
@@ -621,26 +1148,47 @@ export function mount_actions(anchor, actions) {
   const mount_state = ensure_anchor_mount_state(anchor);
   run_cleanup_list(mount_state.action_cleanups);
   mount_state.action_cleanups = [];
-
-  if (!Array.isArray(actions) || actions.length === 0) {
-    return;
-  }
-
-  const action_targets = resolve_mount_event_targets(anchor);
-  if (action_targets.length === 0) {
-    return;
-  }
-
-  for (const action of actions) {
-    if (!action || typeof action.name !== "string" || typeof action.id !== "number") {
-      continue;
+  // Attachments of the elements that the render kept stay in place unless
+  // what they read changed; the others are torn down.
+  const previous_attachments = mount_state.attachments;
+  const attachments = new Map();
+  mount_state.attachments = attachments;
+  try {
+    if (!Array.isArray(actions) || actions.length === 0) {
+      return;
     }
 
-    const attr_name = `data-lux-use-${action.name}`;
-    const attr_value = String(action.id);
-    for (const root of action_targets) {
-      mount_action_for_root(root, attr_name, attr_value, action, mount_state.action_cleanups);
+    const action_targets = resolve_mount_event_targets(anchor);
+    const instance = anchor_instances.get(anchor)?.get("") ?? null;
+    for (const action of actions) {
+      if (!action || typeof action.name !== "string" || typeof action.id !== "number") {
+        continue;
+      }
+
+      const attr_name = `data-lux-use-${action.name}`;
+      const attr_value = String(action.id);
+      for (const root of action_targets) {
+        for_each_marked_element(root, attr_name, attr_value, (element) => {
+          if (typeof action.attachment === "function") {
+            mount_attachment(
+              element,
+              attr_name,
+              action,
+              previous_attachments,
+              attachments,
+              instance
+            );
+            return;
+          }
+          const cleanup = apply_action(element, attr_name, action);
+          if (typeof cleanup === "function") {
+            mount_state.action_cleanups.push(cleanup);
+          }
+        });
+      }
     }
+  } finally {
+    teardown_attachments(previous_attachments);
   }
 }
 
@@ -661,6 +1209,7 @@ export function mount_transitions(anchor, transitions) {
   if (transition_targets.length === 0) {
     return;
   }
+  const blocks = anchor_mounted_blocks.get(anchor) ?? null;
 
   for (const transition of transitions) {
     if (!transition || typeof transition.name !== "string" || typeof transition.id !== "number") {
@@ -675,6 +1224,7 @@ export function mount_transitions(anchor, transitions) {
         attr_name,
         attr_value,
         transition,
+        blocks,
         mount_state.transition_cleanups
       );
     }
@@ -718,79 +1268,894 @@ export function mount_animations(anchor, animations) {
   }
 }
 
-function ensure_anchor_mount_state(anchor) {
-  const existing = anchor_mount_state.get(anchor);
-  if (existing) {
-    return existing;
+export function create_custom_element(component, options = {}) {
+  if (typeof HTMLElement === "undefined") {
+    return component;
+  }
+
+  const props_definition = options.props ?? {};
+  const prop_names = Array.from(
+    new Set([...(options.names ?? []), ...Object.keys(props_definition)])
+  );
+  const attribute_to_prop = new Map(
+    prop_names.map((name) => [
+      custom_element_attribute_name(name, props_definition[name]),
+      name
+    ])
+  );
+
+  // Without a shadow root the styles go into the document head, once for all
+  // elements of the component.
+  let head_style = null;
+  const insert_head_style = (owner_document) => {
+    const head = owner_document?.head;
+    if (!options.css || !head || head_style?.isConnected) {
+      return;
+    }
+    head_style = owner_document.createElement("style");
+    head_style.textContent = options.css;
+    head.appendChild(head_style);
+  };
+
+  class LuxElement extends HTMLElement {
+    constructor() {
+      super();
+      this.$$props = {};
+      this.$$root = null;
+      this.$$anchor = this;
+      this.$$mount_props = null;
+      this.$$cleanup = null;
+      this.$$connected = false;
+      this.$$reflecting = false;
+      if (options.shadow !== "none" && typeof this.attachShadow === "function") {
+        this.$$root = this.attachShadow(
+          options.shadow && typeof options.shadow === "object"
+            ? options.shadow
+            : { mode: "open" }
+        );
+        // The component mounts after the styles, which stay in place while
+        // it renders again.
+        const owner_document = this.$$root.ownerDocument ?? document;
+        if (options.css) {
+          const style = owner_document.createElement("style");
+          style.textContent = options.css;
+          this.$$root.appendChild(style);
+        }
+        this.$$anchor = this.$$root.appendChild(owner_document.createComment(""));
+      }
+    }
+
+    static get observedAttributes() {
+      return Array.from(attribute_to_prop.keys());
+    }
+
+    connectedCallback() {
+      this.$$connected = true;
+      if (!this.$$root) {
+        insert_head_style(this.ownerDocument ?? document);
+      }
+      for (const [attribute_name, prop_name] of attribute_to_prop) {
+        if (!(prop_name in this.$$props) && this.hasAttribute(attribute_name)) {
+          this.$$props[prop_name] = custom_element_prop_value(
+            this.getAttribute(attribute_name),
+            props_definition[prop_name]
+          );
+        }
+      }
+      this.$$render();
+    }
+
+    disconnectedCallback() {
+      this.$$connected = false;
+      Promise.resolve().then(() => {
+        if (!this.$$connected && typeof this.$$cleanup === "function") {
+          this.$$cleanup();
+          this.$$cleanup = null;
+        }
+      });
+    }
+
+    attributeChangedCallback(attribute_name, _previous, next) {
+      if (this.$$reflecting) {
+        return;
+      }
+      const prop_name = attribute_to_prop.get(attribute_name);
+      if (!prop_name) {
+        return;
+      }
+      this.$$props[prop_name] = custom_element_prop_value(next, props_definition[prop_name]);
+      if (this.$$connected) {
+        this.$$render();
+      }
+    }
+
+    $$set(prop_name, value) {
+      this.$$props[prop_name] = value;
+      const definition = props_definition[prop_name];
+      if (definition?.reflect) {
+        const attribute_name = custom_element_attribute_name(prop_name, definition);
+        const attribute_value = custom_element_attribute_value(value, definition);
+        this.$$reflecting = true;
+        if (attribute_value == null) {
+          this.removeAttribute(attribute_name);
+        } else {
+          this.setAttribute(attribute_name, attribute_value);
+        }
+        this.$$reflecting = false;
+      }
+      if (this.$$connected) {
+        this.$$render();
+      }
+    }
+
+    // Mounts the component on the first render, and afterwards renders the
+    // mounted instance again with the current props, keeping its state.
+    $$render() {
+      const props = this.$$mount_props ?? {};
+      for (const key of Object.keys(props)) {
+        delete props[key];
+      }
+      Object.assign(props, this.$$props);
+      if (this.$$root) {
+        Object.assign(props, custom_element_slot_props(this));
+      }
+      const instance = anchor_instances.get(this.$$anchor)?.get("");
+      if (this.$$cleanup && instance && !instance.destroyed && instance.remount) {
+        rerender(this.$$anchor, instance.remount);
+        return;
+      }
+      this.$$cleanup?.();
+      this.$$mount_props = props;
+      this.$$cleanup = component(this.$$anchor, props);
+    }
+  }
+
+  for (const prop_name of prop_names) {
+    Object.defineProperty(LuxElement.prototype, prop_name, {
+      configurable: true,
+      get() {
+        return this.$$props[prop_name];
+      },
+      set(value) {
+        this.$$set(prop_name, value);
+      }
+    });
+  }
+
+  const element =
+    typeof options.extend === "function" ? options.extend(LuxElement) : LuxElement;
+  if (
+    options.tag &&
+    typeof customElements !== "undefined" &&
+    !customElements.get(options.tag)
+  ) {
+    customElements.define(options.tag, element);
+  }
+  component.element = element;
+  return component;
+}
+
+function root_render_state(state) {
+  let root = state;
+  while (root?.parent) {
+    root = root.parent;
+  }
+  return root;
+}
+
+// Component instances keep their state across renders. The component
+// mounted at an anchor is the root instance; nested components are told
+// apart by the instance and block that render them and by their order in it.
+function claim_instance(state) {
+  let root = state;
+  let key = "";
+  if (!state.anchor) {
+    const parent = state.parent?.instance;
+    if (!parent) {
+      pending_bind_this = null;
+      return null;
+    }
+    root = root_render_state(state);
+    const counts = state.parent.child_counts;
+    const index = counts.get(current_block_path) ?? 0;
+    counts.set(current_block_path, index + 1);
+    key = `${parent.key}>${current_block_path}#${index}`;
+  } else {
+    root.instances = new Map();
+    root.previous_instances = anchor_instances.get(root.anchor) ?? null;
+  }
+
+  let instance = root.previous_instances?.get(key) ?? root.instances.get(key);
+  if (!instance) {
+    instance = {
+      key,
+      values: new Map(),
+      rendered: null,
+      props: null,
+      overrides: new Map(),
+      forced: false,
+      destroyed: false,
+      bind_this: null,
+      remount: null,
+      anchor: null,
+      tracked: null,
+      effects: new Map(),
+      effect_queue: [],
+      roots: [],
+      root_count: 0,
+      inspections: new Map()
+    };
+  }
+  instance.effect_queue = [];
+  instance.root_count = 0;
+  instance.remount = root.remount;
+  instance.anchor = root.anchor;
+  instance.bind_this = pending_bind_this;
+  if (pending_bind_this) {
+    pending_bind_this.claimed = true;
+    pending_bind_this = null;
+  }
+  root.instances.set(key, instance);
+  return instance;
+}
+
+function finish_instances(state) {
+  for (const instance of state.instances.values()) {
+    instance.rendered = new Map(instance.values);
+    instance.forced = false;
+  }
+  anchor_instances.set(state.anchor, state.instances);
+  for (const [key, instance] of state.previous_instances ?? []) {
+    if (!state.instances.has(key)) {
+      destroy_instance(instance);
+    }
+  }
+  state.previous_instances = null;
+  // Binding after the snapshot renders the parent again when the bound
+  // component is new.
+  for (const instance of state.instances.values()) {
+    instance.bind_this?.bind(instance.bind_this.component);
+  }
+  // Effects run children first, pre effects now and the others once the
+  // DOM is updated.
+  const rendered = state.rendered_instances;
+  run_effects(rendered, true);
+  queueMicrotask(() => run_effects(rendered, false));
+}
+
+function register_effect(render_state, fn, pre) {
+  if (typeof fn !== "function") {
+    return;
+  }
+  if (active_effect) {
+    active_effect.children.push(
+      run_effect_function(fn, active_effect.pending, active_effect.instance)
+    );
+    return;
+  }
+  const instance = render_state?.instance;
+  if (!instance || current_render_state !== render_state) {
+    return;
+  }
+  instance.effect_queue.push({ fn, pre, pending: current_pending });
+}
+
+// Effects are told apart by their order in the instance script.
+function run_effects(instances, pre) {
+  for (const instance of instances) {
+    if (instance.destroyed) {
+      continue;
+    }
+    if (pre) {
+      for (const [index, record] of instance.effects) {
+        if (index >= instance.effect_queue.length) {
+          teardown_effect(record);
+          instance.effects.delete(index);
+        }
+      }
+    }
+    instance.effect_queue.forEach((registered, index) => {
+      if (registered.pre !== pre) {
+        return;
+      }
+      const previous = instance.effects.get(index);
+      if (previous && !has_changed_reads(previous)) {
+        return;
+      }
+      teardown_effect(previous);
+      instance.effects.set(
+        index,
+        run_effect_function(registered.fn, registered.pending, instance)
+      );
+    });
+  }
+}
+
+// Runs `fn` as an effect of `instance`, recording the reactive bindings and
+// objects it reads. Objects are versioned once the run is over, so that an
+// effect writing to what it read does not run itself again.
+function run_effect_function(fn, pending = null, instance = null) {
+  const record = {
+    teardown: null,
+    children: [],
+    reads: new Map(),
+    sources: new Map(),
+    pending,
+    instance
+  };
+  const parent = active_effect;
+  active_effect = record;
+  try {
+    const result = fn();
+    if (typeof result === "function") {
+      record.teardown = result;
+    }
+  } catch (error) {
+    queueMicrotask(() => {
+      throw error;
+    });
+  } finally {
+    active_effect = parent;
+    for (const reads of record.reads.values()) {
+      for (const read of reads.values()) {
+        read.version = state_versions.get(read.value)?.version;
+      }
+    }
+    for (const source of record.sources.keys()) {
+      record.sources.set(source, source.version);
+    }
+  }
+  return record;
+}
+
+// Whether a binding or reactive object that the effect read changed since.
+// Bindings are compared with the values their instance last rendered, and
+// reactive objects by the number of writes made to them.
+function has_changed_reads(record) {
+  for (const [instance, reads] of record.reads) {
+    const values = instance.tracked?.() ?? {};
+    for (const [name, read] of reads) {
+      const value = values[name];
+      if (!Object.is(value, read.value) || state_versions.get(value)?.version !== read.version) {
+        return true;
+      }
+    }
+  }
+  for (const [source, version] of record.sources) {
+    if (source.version !== version) {
+      return true;
+    }
+  }
+  return false;
+}
+
+function teardown_effect(record) {
+  if (!record) {
+    return;
+  }
+  const children = record.children;
+  record.children = [];
+  for (const child of children) {
+    teardown_effect(child);
+  }
+  const teardown = record.teardown;
+  record.teardown = null;
+  try {
+    teardown?.();
+  } catch {}
+}
+
+function destroy_instance(instance) {
+  if (instance.destroyed) {
+    return;
+  }
+  instance.destroyed = true;
+  for (const record of instance.effects.values()) {
+    teardown_effect(record);
+  }
+  instance.effects.clear();
+  try {
+    instance.bind_this?.bind(null);
+  } catch {}
+}
+
+function schedule_update(instance) {
+  if (!instance.remount) {
+    return;
+  }
+  if (!scheduled_instances) {
+    scheduled_instances = new Set();
+    queueMicrotask(flush_updates);
+  }
+  scheduled_instances.add(instance);
+}
+
+// Renders again the components whose state differs from what they last
+// rendered. Writes that end up restoring the rendered value, like a
+// `bind:this` unbound and bound again while re-mounting, change nothing.
+function flush_updates() {
+  const instances = scheduled_instances;
+  scheduled_instances = null;
+  const remounts = new Map();
+  for (const instance of instances) {
+    if (!instance.destroyed && (instance.forced || has_unrendered_values(instance))) {
+      remounts.set(instance.remount, instance.anchor);
+    }
+  }
+  for (const [remount, anchor] of remounts) {
+    rerender(anchor, remount);
+  }
+}
+
+// Renders the component mounted at the anchor again, keeping what it
+// mounted and morphing its DOM.
+function rerender(anchor, remount) {
+  if (typeof remount !== "function") {
+    return;
+  }
+  const previous = rerendering_anchor;
+  rerendering_anchor = anchor;
+  try {
+    remount();
+  } finally {
+    rerendering_anchor = previous;
+  }
+}
+
+function is_proxyable(value) {
+  if (value === null || typeof value !== "object" || Object.isFrozen(value)) {
+    return false;
+  }
+  const prototype = Object.getPrototypeOf(value);
+  return Array.isArray(value) || prototype === Object.prototype || prototype === null;
+}
+
+function create_source() {
+  return { version: 0, readers: new Set() };
+}
+
+function object_source(object) {
+  let source = state_versions.get(object);
+  if (!source) {
+    source = create_source();
+    state_versions.set(object, source);
+  }
+  return source;
+}
+
+// A reactive object is read by the instance that renders, or by the effect
+// that runs, and by the instance that the effect belongs to.
+function observe_source(source) {
+  if (current_render_state) {
+    const instance = current_render_state.instance;
+    if (instance) {
+      source.readers.add(instance);
+    }
+    return;
+  }
+  if (!active_effect) {
+    return;
+  }
+  if (!active_effect.sources.has(source)) {
+    active_effect.sources.set(source, source.version);
+  }
+  if (active_effect.instance) {
+    source.readers.add(active_effect.instance);
+  }
+}
+
+// Renders again the instances that read the object. Writes made while
+// rendering are part of that render already.
+function notify(source) {
+  source.version++;
+  if (current_render_state) {
+    return;
+  }
+  for (const instance of source.readers) {
+    if (instance.destroyed) {
+      source.readers.delete(instance);
+      continue;
+    }
+    instance.forced = true;
+    schedule_update(instance);
+  }
+}
+
+function clone_snapshot(value, copies) {
+  value = proxy_targets.get(value) ?? value;
+  if (value === null || typeof value !== "object") {
+    return value;
+  }
+  if (copies.has(value)) {
+    return copies.get(value);
+  }
+  const prototype = Object.getPrototypeOf(value);
+  if (Array.isArray(value) || prototype === Object.prototype || prototype === null) {
+    const copy = Array.isArray(value) ? [] : Object.create(prototype);
+    copies.set(value, copy);
+    for (const key of Object.keys(value)) {
+      copy[key] = clone_snapshot(value[key], copies);
+    }
+    return copy;
+  }
+  if (typeof value.toJSON === "function") {
+    return clone_snapshot(value.toJSON(), copies);
+  }
+  try {
+    return structuredClone(value);
+  } catch {
+    return value;
+  }
+}
+
+function has_unrendered_values(instance) {
+  const rendered = instance.rendered;
+  if (!rendered || rendered.size !== instance.values.size) {
+    return true;
+  }
+  for (const [name, value] of instance.values) {
+    if (!rendered.has(name) || !Object.is(rendered.get(name), value)) {
+      return true;
+    }
+  }
+  return false;
+}
+
+function ensure_anchor_mount_state(anchor) {
+  const existing = anchor_mount_state.get(anchor);
+  if (existing) {
+    return existing;
+  }
+  const state = {
+    event_cleanups: [],
+    binding_cleanups: [],
+    action_cleanups: [],
+    attachments: new Map(),
+    transition_cleanups: [],
+    animation_cleanups: []
+  };
+  anchor_mount_state.set(anchor, state);
+  return state;
+}
+
+function clear_anchor_mount_state(anchor) {
+  const state = anchor_mount_state.get(anchor);
+  if (!state) {
+    return;
+  }
+  run_cleanup_list(state.event_cleanups);
+  run_cleanup_list(state.binding_cleanups);
+  run_cleanup_list(state.action_cleanups);
+  teardown_attachments(state.attachments);
+  run_cleanup_list(state.transition_cleanups);
+  run_cleanup_list(state.animation_cleanups);
+  anchor_mount_state.delete(anchor);
+}
+
+// The pending promises of a boundary are counted across the renders of the
+// component mounted at the anchor.
+function boundary_pending(anchor, index) {
+  if (!anchor) {
+    return { count: 0 };
+  }
+  let records = anchor_boundary_pending.get(anchor);
+  if (!records) {
+    records = new Map();
+    anchor_boundary_pending.set(anchor, records);
+  }
+  let record = records.get(index);
+  if (!record) {
+    record = { count: 0 };
+    records.set(index, record);
+  }
+  return record;
+}
+
+function clear_anchor_head_state(anchor) {
+  const state = anchor_head_state.get(anchor);
+  if (!state) {
+    return;
+  }
+  for (const node of state.nodes) {
+    if (node?.parentNode) {
+      node.parentNode.removeChild(node);
+    }
+  }
+  anchor_head_state.delete(anchor);
+}
+
+function run_cleanup_list(cleanups) {
+  if (!Array.isArray(cleanups) || cleanups.length === 0) {
+    return;
+  }
+  for (const cleanup of cleanups) {
+    if (typeof cleanup !== "function") {
+      continue;
+    }
+    try {
+      cleanup();
+    } catch {}
+  }
+}
+
+function ensure_anchor_region(anchor, owner_document) {
+  const existing = anchor_regions.get(anchor);
+  if (existing && existing.start?.parentNode && existing.end?.parentNode) {
+    return existing;
+  }
+
+  const parent = anchor.parentNode;
+  if (!parent) return null;
+
+  const start = owner_document.createComment("lux-start");
+  const end = owner_document.createComment("lux-end");
+  parent.insertBefore(start, anchor);
+  parent.insertBefore(end, anchor);
+
+  const region = { start, end };
+  anchor_regions.set(anchor, region);
+  return region;
+}
+
+// Turns the nodes from `start` up to `end` into `new_nodes`, reusing the
+// existing DOM where it matches. Keyed each items are matched by key and
+// moved rather than recreated, so they keep focus, input state and running
+// transitions.
+function morph_range(parent, start, end, new_nodes, blocks) {
+  const old_items = collect_morph_items(collect_range(start, end));
+  const new_items = collect_morph_items(new_nodes);
+
+  const instances = new Map();
+  for (const item of old_items) {
+    if (item.identity !== null && !instances.has(item.identity)) {
+      instances.set(item.identity, item);
+      record_item_rects(item);
+    }
+  }
+
+  let index = 0;
+  const reference = () => {
+    while (index < old_items.length && old_items[index].consumed) {
+      index += 1;
+    }
+    return index < old_items.length ? old_items[index].nodes[0] : end;
+  };
+
+  for (const item of new_items) {
+    const next = reference();
+    if (item.identity !== null) {
+      const existing = instances.get(item.identity);
+      if (!existing || existing.consumed) {
+        insert_nodes(parent, item.nodes, next);
+        continue;
+      }
+      existing.consumed = true;
+      if (existing.nodes[0] !== next) {
+        insert_nodes(parent, existing.nodes, next);
+      }
+      morph_range(
+        parent,
+        existing.nodes[0].nextSibling,
+        existing.nodes[existing.nodes.length - 1],
+        item.nodes.slice(1, -1),
+        blocks
+      );
+      continue;
+    }
+
+    const candidate = old_items[index];
+    if (
+      candidate &&
+      candidate.identity === null &&
+      can_morph_node(candidate.nodes[0], item.nodes[0])
+    ) {
+      candidate.consumed = true;
+      morph_node(candidate.nodes[0], item.nodes[0], blocks);
+      continue;
+    }
+    insert_nodes(parent, item.nodes, next);
+  }
+
+  for (const item of old_items) {
+    if (item.consumed) {
+      continue;
+    }
+    for (const node of item.nodes) {
+      if (node.parentNode === parent) {
+        remove_with_outros(parent, node, blocks);
+      }
+    }
+  }
+}
+
+function collect_range(start, end) {
+  const nodes = [];
+  for (let node = start; node && node !== end; node = node.nextSibling) {
+    // Nodes kept in place for their outro are no longer part of the markup.
+    if (!outroing_nodes.has(node)) {
+      nodes.push(node);
+    }
+  }
+  return nodes;
+}
+
+// Groups nodes into keyed each items, delimited by their marker comments,
+// and single unkeyed nodes.
+function collect_morph_items(nodes) {
+  const items = [];
+  for (let i = 0; i < nodes.length; i += 1) {
+    const node = nodes[i];
+    const identity = block_marker_identity(node);
+    if (identity === null) {
+      items.push({ identity: null, nodes: [node], consumed: false });
+      continue;
+    }
+
+    let depth = 1;
+    let j = i + 1;
+    for (; j < nodes.length; j += 1) {
+      if (block_marker_identity(nodes[j]) !== null) {
+        depth += 1;
+      } else if (nodes[j].nodeType === 8 && nodes[j].data === BLOCK_END_MARKER) {
+        depth -= 1;
+        if (depth === 0) {
+          break;
+        }
+      }
+    }
+    if (j >= nodes.length) {
+      items.push({ identity: null, nodes: [node], consumed: false });
+      continue;
+    }
+
+    items.push({ identity, nodes: nodes.slice(i, j + 1), consumed: false });
+    i = j;
+  }
+  return items;
+}
+
+function block_marker_identity(node) {
+  if (node?.nodeType !== 8 || !node.data.startsWith(BLOCK_MARKER)) {
+    return null;
+  }
+  return node.data.slice(BLOCK_MARKER.length);
+}
+
+// Marker comments must not contain `--`.
+function encode_block_identity(identity) {
+  return encodeURIComponent(identity).replace(/-/g, "%2D");
+}
+
+// Keys compare by identity, like `===`.
+function key_identity(key) {
+  if ((typeof key === "object" && key !== null) || typeof key === "function") {
+    let id = object_key_ids.get(key);
+    if (id === undefined) {
+      object_key_counter += 1;
+      id = object_key_counter;
+      object_key_ids.set(key, id);
+    }
+    return `o${id}`;
+  }
+  return `${typeof key}:${String(key)}`;
+}
+
+function record_item_rects(item) {
+  for (const node of item.nodes) {
+    if (node.nodeType === 1 && typeof node.getBoundingClientRect === "function") {
+      moved_element_rects.set(node, node.getBoundingClientRect());
+    }
   }
-  const state = {
-    event_cleanups: [],
-    binding_cleanups: [],
-    action_cleanups: [],
-    transition_cleanups: [],
-    animation_cleanups: []
-  };
-  anchor_mount_state.set(anchor, state);
-  return state;
 }
 
-function clear_anchor_mount_state(anchor) {
-  const state = anchor_mount_state.get(anchor);
-  if (!state) {
-    return;
+function insert_nodes(parent, nodes, reference) {
+  for (const node of nodes) {
+    parent.insertBefore(node, reference);
   }
-  run_cleanup_list(state.event_cleanups);
-  run_cleanup_list(state.binding_cleanups);
-  run_cleanup_list(state.action_cleanups);
-  run_cleanup_list(state.transition_cleanups);
-  run_cleanup_list(state.animation_cleanups);
-  anchor_mount_state.delete(anchor);
 }
 
-function clear_anchor_head_state(anchor) {
-  const state = anchor_head_state.get(anchor);
-  if (!state) {
-    return;
+function can_morph_node(old_node, new_node) {
+  if (!old_node || old_node.nodeType !== new_node.nodeType) {
+    return false;
   }
-  for (const node of state.nodes) {
-    if (node?.parentNode) {
-      node.parentNode.removeChild(node);
-    }
+  if (old_node.nodeType !== 1) {
+    return true;
   }
-  anchor_head_state.delete(anchor);
+  return old_node.nodeName === new_node.nodeName && old_node.namespaceURI === new_node.namespaceURI;
 }
 
-function run_cleanup_list(cleanups) {
-  if (!Array.isArray(cleanups) || cleanups.length === 0) {
+function morph_node(old_node, new_node, blocks) {
+  if (old_node.nodeType !== 1) {
+    if (old_node.nodeValue !== new_node.nodeValue) {
+      old_node.nodeValue = new_node.nodeValue;
+    }
     return;
   }
-  for (const cleanup of cleanups) {
-    if (typeof cleanup !== "function") {
+
+  const rendered =
+    rendered_attributes.get(old_node) ??
+    Array.from(old_node.attributes, (attribute) => attribute.name);
+  for (const name of rendered) {
+    if (old_node.hasAttribute(name) && !new_node.hasAttribute(name)) {
+      old_node.removeAttribute(name);
+      if (name === "checked" && "checked" in old_node) {
+        old_node.checked = false;
+      }
+    }
+  }
+  rendered_attributes.set(old_node, rendered_attributes.get(new_node) ?? []);
+  for (const attribute of Array.from(new_node.attributes)) {
+    if (old_node.getAttribute(attribute.name) === attribute.value) {
       continue;
     }
-    try {
-      cleanup();
-    } catch {}
+    if (attribute.name === "style" && old_node.hasAttribute(CSS_PROPS_ATTRIBUTE)) {
+      morph_css_props(old_node.style, new_node.style);
+      continue;
+    }
+    old_node.setAttribute(attribute.name, attribute.value);
+    // Properties only follow attributes that the template changed, so user
+    // input in untouched fields is kept.
+    if (attribute.name === "value" && "value" in old_node) {
+      old_node.value = attribute.value;
+    } else if (attribute.name === "checked" && "checked" in old_node) {
+      old_node.checked = true;
+    }
+  }
+
+  if (old_node.nodeName === "TEXTAREA" || old_node.nodeName === "TEMPLATE") {
+    return;
   }
+  morph_range(old_node, old_node.firstChild, null, Array.from(new_node.childNodes), blocks);
 }
 
-function ensure_anchor_region(anchor, owner_document) {
-  const existing = anchor_regions.get(anchor);
-  if (existing && existing.start?.parentNode && existing.end?.parentNode) {
-    return existing;
+function morph_css_props(old_style, new_style) {
+  const next = new Map();
+  for (let i = 0; i < new_style.length; i += 1) {
+    const name = new_style.item(i);
+    next.set(name, new_style.getPropertyValue(name));
+  }
+  for (let i = old_style.length - 1; i >= 0; i -= 1) {
+    const name = old_style.item(i);
+    if (!next.has(name)) {
+      old_style.removeProperty(name);
+    }
   }
+  for (const [name, value] of next) {
+    if (old_style.getPropertyValue(name) !== value) {
+      old_style.setProperty(name, value);
+    }
+  }
+}
 
-  const parent = anchor.parentNode;
-  if (!parent) return null;
+function create_fragment(owner_document, html, namespace) {
+  const namespace_uri = NAMESPACE_URIS[namespace];
+  if (!namespace_uri) {
+    const template = owner_document.createElement("template");
+    template.innerHTML = html;
+    record_rendered_attributes(template.content);
+    return template.content;
+  }
 
-  const start = owner_document.createComment("lux-start");
-  const end = owner_document.createComment("lux-end");
-  parent.insertBefore(start, anchor);
-  parent.insertBefore(end, anchor);
+  // Parsing inside a foreign root keeps `<circle>` and friends in their namespace.
+  const root = owner_document.createElementNS(namespace_uri, namespace === "svg" ? "svg" : "math");
+  root.innerHTML = html;
+  const fragment = owner_document.createDocumentFragment();
+  while (root.firstChild) {
+    fragment.appendChild(root.firstChild);
+  }
+  record_rendered_attributes(fragment);
+  return fragment;
+}
 
-  const region = { start, end };
-  anchor_regions.set(anchor, region);
-  return region;
+// A morph removes only the attributes that the template rendered, so those set
+// by actions and attachments are kept.
+function record_rendered_attributes(root) {
+  for (const element of root.querySelectorAll("*")) {
+    rendered_attributes.set(
+      element,
+      Array.from(element.attributes, (attribute) => attribute.name)
+    );
+  }
 }
 
 function clear_region(region) {
@@ -841,13 +2206,13 @@ function collect_region_roots(region) {
   return roots;
 }
 
-function bind_event_for_root(root, attr_name, attr_value, event, cleanups) {
+function bind_event_for_root(root, attr_name, attr_value, event, delegation_root, cleanups) {
   if (root.nodeType !== 1) {
     return;
   }
 
   if (root.getAttribute(attr_name) === attr_value) {
-    const cleanup = attach_event(root, attr_name, event);
+    const cleanup = attach_event(root, attr_name, event, delegation_root);
     if (typeof cleanup === "function") {
       cleanups.push(cleanup);
     }
@@ -856,42 +2221,79 @@ function bind_event_for_root(root, attr_name, attr_value, event, cleanups) {
   const selector = `[${attr_name}="${css_escape(attr_value)}"]`;
   const nodes = root.querySelectorAll(selector);
   for (const node of nodes) {
-    const cleanup = attach_event(node, attr_name, event);
+    const cleanup = attach_event(node, attr_name, event, delegation_root);
     if (typeof cleanup === "function") {
       cleanups.push(cleanup);
     }
   }
 }
 
-function mount_action_for_root(root, attr_name, attr_value, action, cleanups) {
+function for_each_marked_element(root, attr_name, attr_value, callback) {
   if (root.nodeType !== 1) {
     return;
   }
 
   if (root.getAttribute(attr_name) === attr_value) {
-    const cleanup = apply_action(root, attr_name, action);
-    if (typeof cleanup === "function") {
-      cleanups.push(cleanup);
-    }
+    callback(root);
   }
 
   const selector = `[${attr_name}="${css_escape(attr_value)}"]`;
-  const nodes = root.querySelectorAll(selector);
-  for (const node of nodes) {
-    const cleanup = apply_action(node, attr_name, action);
-    if (typeof cleanup === "function") {
-      cleanups.push(cleanup);
+  for (const node of root.querySelectorAll(selector)) {
+    callback(node);
+  }
+}
+
+// Attachments are matched with those of the previous mount by their element
+// and their order on it.
+function mount_attachment(element, attr_name, descriptor, previous, current, instance) {
+  remove_binding_marker(element, attr_name);
+  let records = current.get(element);
+  if (!records) {
+    records = [];
+    current.set(element, records);
+  }
+  const previous_records = previous.get(element);
+  const index = records.length;
+  const kept = previous_records?.[index] ?? null;
+  if (previous_records) {
+    previous_records[index] = null;
+  }
+  if (kept && !has_changed_reads(kept)) {
+    records.push(kept);
+    return;
+  }
+  teardown_effect(kept);
+  records.push(
+    run_effect_function(() => {
+      try {
+        const attachment = descriptor.attachment();
+        return typeof attachment === "function" ? attachment(element) : undefined;
+      } catch (error) {
+        if (!descriptor.boundary) {
+          throw error;
+        }
+        descriptor.boundary(error);
+      }
+    }, null, instance)
+  );
+}
+
+function teardown_attachments(attachments) {
+  for (const records of attachments.values()) {
+    for (const record of records) {
+      teardown_effect(record);
     }
   }
+  attachments.clear();
 }
 
-function mount_transition_for_root(root, attr_name, attr_value, transition, cleanups) {
+function mount_transition_for_root(root, attr_name, attr_value, transition, blocks, cleanups) {
   if (root.nodeType !== 1) {
     return;
   }
 
   if (root.getAttribute(attr_name) === attr_value) {
-    const cleanup = apply_transition(root, attr_name, transition);
+    const cleanup = apply_transition(root, attr_name, transition, blocks);
     if (typeof cleanup === "function") {
       cleanups.push(cleanup);
     }
@@ -900,7 +2302,7 @@ function mount_transition_for_root(root, attr_name, attr_value, transition, clea
   const selector = `[${attr_name}="${css_escape(attr_value)}"]`;
   const nodes = root.querySelectorAll(selector);
   for (const node of nodes) {
-    const cleanup = apply_transition(node, attr_name, transition);
+    const cleanup = apply_transition(node, attr_name, transition, blocks);
     if (typeof cleanup === "function") {
       cleanups.push(cleanup);
     }
@@ -1088,7 +2490,10 @@ function read_global_binding_value(target_name, target, binding_name) {
   }
 }
 
-function attach_event(element, attr_name, event) {
+function attach_event(element, attr_name, event, delegation_root) {
+  if (event.delegated && delegation_root) {
+    return attach_delegated_event(element, attr_name, event, delegation_root);
+  }
   const listener = build_event_listener(event, element);
   const options = build_event_listener_options(event.modifiers);
   element.addEventListener(event.name, listener, options);
@@ -1098,6 +2503,89 @@ function attach_event(element, attr_name, event) {
   };
 }
 
+// Delegated handlers are stored on their element and run by a single
+// listener on the mount root, which walks up from the event target. The
+// root listener is counted per event and removed with the last handler.
+function attach_delegated_event(element, attr_name, event, root) {
+  const key = DELEGATED_HANDLER_PREFIX + event.name;
+  const handler = event.handler;
+  element[key] = handler;
+  remove_binding_marker(element, attr_name);
+
+  let counts = delegated_root_events.get(root);
+  if (!counts) {
+    counts = new Map();
+    delegated_root_events.set(root, counts);
+  }
+  const count = counts.get(event.name) ?? 0;
+  counts.set(event.name, count + 1);
+  if (count === 0) {
+    root.addEventListener(event.name, handle_delegated_event, {
+      passive: event.modifiers.passive
+    });
+  }
+
+  return () => {
+    if (element[key] === handler) {
+      delete element[key];
+    }
+    const remaining = counts.get(event.name) - 1;
+    if (remaining > 0) {
+      counts.set(event.name, remaining);
+      return;
+    }
+    counts.delete(event.name);
+    root.removeEventListener(event.name, handle_delegated_event);
+  };
+}
+
+function handle_delegated_event(event) {
+  const root = this;
+  const path = typeof event.composedPath === "function" ? event.composedPath() : [];
+  if (path.length === 0) {
+    for (let node = event.target; node; node = node.parentNode) {
+      path.push(node);
+    }
+  }
+
+  // A nested mount root has already run the handlers below it.
+  const handled_root = event[DELEGATED_ROOT_KEY];
+  const handled_index = handled_root ? path.indexOf(handled_root) : -1;
+  const key = DELEGATED_HANDLER_PREFIX + event.type;
+
+  try {
+    for (let i = handled_index + 1; i < path.length; i += 1) {
+      const node = path[i];
+      if (node === root) {
+        break;
+      }
+      const handler = node[key];
+      if (typeof handler !== "function" || node.disabled) {
+        continue;
+      }
+      Object.defineProperty(event, "currentTarget", { configurable: true, get: () => node });
+      handler.call(node, event);
+      if (event.cancelBubble) {
+        break;
+      }
+    }
+  } finally {
+    event[DELEGATED_ROOT_KEY] = root;
+    Object.defineProperty(event, "currentTarget", { configurable: true, get: () => root });
+  }
+}
+
+function resolve_delegation_root(anchor) {
+  const region = anchor_regions.get(anchor);
+  if (region?.start?.parentNode) {
+    return region.start.parentNode;
+  }
+  if (anchor.nodeType === 1 || anchor.nodeType === 9 || anchor.nodeType === 11) {
+    return anchor;
+  }
+  return null;
+}
+
 function build_event_listener(event, element) {
   const modifiers = event.modifiers;
   const handler = event.handler;
@@ -1394,8 +2882,9 @@ function apply_action(element, attr_name, descriptor) {
   let result;
   try {
     result = descriptor.action(element, parameter);
-  } catch {
+  } catch (error) {
     remove_binding_marker(element, attr_name);
+    descriptor.boundary?.(error);
     return undefined;
   }
 
@@ -1412,63 +2901,186 @@ function apply_action(element, attr_name, descriptor) {
   return undefined;
 }
 
-function apply_transition(element, attr_name, descriptor) {
+function apply_transition(element, attr_name, descriptor, blocks) {
+  remove_binding_marker(element, attr_name);
   if (!descriptor || typeof descriptor.transition !== "function") {
-    remove_binding_marker(element, attr_name);
     return undefined;
   }
 
+  // An element reused by a re-mount keeps its transition, which picks up
+  // the parameters and block of the new render.
+  let records = live_transitions.get(element);
+  const existing = records?.get(descriptor.name);
+  if (existing) {
+    existing.descriptor = descriptor;
+    return existing.destroy;
+  }
+
+  const record = { element, descriptor, result: undefined, running: null, destroyed: false };
+  record.destroy = () => {
+    if (record.destroyed) {
+      return;
+    }
+    record.destroyed = true;
+    record.running?.();
+    record.running = null;
+    live_transitions.get(element)?.delete(record.descriptor.name);
+    if (record.result && typeof record.result.destroy === "function") {
+      record.result.destroy();
+    }
+  };
+  record.destroy.element = element;
+  if (!records) {
+    records = new Map();
+    live_transitions.set(element, records);
+  }
+  records.set(descriptor.name, record);
+
+  if (should_intro(descriptor, blocks)) {
+    run_transition(record, "in");
+  }
+  return record.destroy;
+}
+
+// Local transitions only play when their own block is created or destroyed,
+// not along with an enclosing block; global ones play whenever their element
+// is. Before a re-mount every block counts as created.
+function should_intro(descriptor, blocks) {
+  if (!descriptor.intro) {
+    return false;
+  }
+  if (descriptor.global || descriptor.path === "") {
+    return true;
+  }
+  const created = (path) => !blocks?.previous || (path !== "" && !blocks.previous.has(path));
+  return created(descriptor.path) && !created(parent_block_path(descriptor.path));
+}
+
+function should_outro(descriptor, blocks) {
+  if (!descriptor.outro) {
+    return false;
+  }
+  if (descriptor.global) {
+    return true;
+  }
+  const destroyed = (path) => path !== "" && !blocks.current.has(path);
+  return destroyed(descriptor.path) && !destroyed(parent_block_path(descriptor.path));
+}
+
+function parent_block_path(path) {
+  return path.slice(0, path.lastIndexOf("/"));
+}
+
+// Removes a node that is no longer rendered. When transitions inside it
+// outro, the node stays in place until they have all finished.
+function remove_with_outros(parent, node, blocks) {
+  const records = [];
+  for (const element of subtree_elements(node)) {
+    for (const record of live_transitions.get(element)?.values() ?? []) {
+      if (should_outro(record.descriptor, blocks)) {
+        records.push(record);
+      }
+    }
+  }
+  if (records.length === 0) {
+    parent.removeChild(node);
+    return;
+  }
+
+  outroing_nodes.add(node);
+  let pending = records.length;
+  const done = () => {
+    pending -= 1;
+    if (pending > 0) {
+      return;
+    }
+    outroing_nodes.delete(node);
+    node.parentNode?.removeChild(node);
+    for (const element of subtree_elements(node)) {
+      for (const record of Array.from(live_transitions.get(element)?.values() ?? [])) {
+        record.destroy();
+      }
+    }
+  };
+  for (const record of records) {
+    run_transition(record, "out", done);
+  }
+}
+
+function subtree_elements(node) {
+  if (node.nodeType !== 1) {
+    return [];
+  }
+  return [node, ...node.querySelectorAll("*")];
+}
+
+function run_transition(record, direction, on_end) {
+  const { element, descriptor } = record;
+  record.running?.();
+  record.running = null;
+
+  const intro = direction === "in";
   const parameter =
     typeof descriptor.parameter === "function"
       ? descriptor.parameter()
       : descriptor.parameter;
-
   const options = {
-    direction:
-      descriptor.intro && descriptor.outro
-        ? "both"
-        : descriptor.intro
-        ? "in"
-        : descriptor.outro
-        ? "out"
-        : "both"
+    direction: descriptor.intro && descriptor.outro ? "both" : direction
   };
 
   let result;
   try {
     result = descriptor.transition(element, parameter, options);
-  } catch {
-    remove_binding_marker(element, attr_name);
-    return undefined;
+  } catch (error) {
+    descriptor.boundary?.(error);
+    on_end?.();
+    return;
   }
+  record.result = result;
+
+  dispatch_transition_event(element, intro ? "introstart" : "outrostart");
+  const end = () => {
+    record.running = null;
+    dispatch_transition_event(element, intro ? "introend" : "outroend");
+    on_end?.();
+  };
 
-  if (descriptor.intro && result && typeof result.in === "function") {
+  const config = typeof result === "function" ? result({ direction }) : result;
+  if (config && (typeof config.in === "function" || typeof config.out === "function")) {
     try {
-      result.in();
-    } catch {}
+      (intro ? config.in : config.out)?.call(config);
+    } catch (error) {
+      descriptor.boundary?.(error);
+    }
+    end();
+    return;
   }
+  record.running = play_config(element, config, !intro, end);
+}
 
-  remove_binding_marker(element, attr_name);
-
-  return () => {
-    if (descriptor.outro && result && typeof result.out === "function") {
-      try {
-        result.out();
-      } catch {}
-    }
-    if (typeof result === "function") {
-      result();
-      return;
-    }
-    if (result && typeof result.destroy === "function") {
-      result.destroy();
-    }
-  };
+function dispatch_transition_event(element, type) {
+  if (typeof CustomEvent === "function" && typeof element.dispatchEvent === "function") {
+    element.dispatchEvent(new CustomEvent(type));
+  }
 }
 
+// Animations only run on keyed each items that a re-mount moved, as a FLIP
+// from the position recorded before the move.
 function apply_animation(element, attr_name, descriptor) {
-  if (!descriptor || typeof descriptor.animation !== "function") {
-    remove_binding_marker(element, attr_name);
+  remove_binding_marker(element, attr_name);
+  const from = moved_element_rects.get(element);
+  moved_element_rects.delete(element);
+  if (!from || !descriptor || typeof descriptor.animation !== "function") {
+    return undefined;
+  }
+
+  const to = element.getBoundingClientRect();
+  if (
+    from.left === to.left &&
+    from.top === to.top &&
+    from.width === to.width &&
+    from.height === to.height
+  ) {
     return undefined;
   }
 
@@ -1477,25 +3089,89 @@ function apply_animation(element, attr_name, descriptor) {
       ? descriptor.parameter()
       : descriptor.parameter;
 
-  let result;
+  let config;
   try {
-    result = descriptor.animation(element, parameter);
-  } catch {
-    remove_binding_marker(element, attr_name);
+    config = descriptor.animation(element, { from, to }, parameter);
+  } catch (error) {
+    descriptor.boundary?.(error);
     return undefined;
   }
+  return play_config(element, config, false) ?? undefined;
+}
 
-  remove_binding_marker(element, attr_name);
+// Plays a transition or animation config, from `t = 0` to `t = 1` or, for an
+// outro, from `t = 1` to `t = 0`. Returns a function that stops it, or null
+// when there is nothing to play, in which case `on_end` is called right away.
+function play_config(element, config, outro, on_end) {
+  const finish = () => on_end?.();
+  if (!config || typeof config !== "object") {
+    finish();
+    return null;
+  }
+  const delay = config.delay ?? 0;
+  const duration = config.duration ?? 300;
+  const easing = typeof config.easing === "function" ? config.easing : (t) => t;
+  const at = (progress) => (outro ? 1 - easing(progress) : easing(progress));
+
+  if (typeof config.css === "function" && typeof element.animate === "function") {
+    const frames = Math.max(2, Math.ceil(duration / (1000 / 60)));
+    const keyframes = [];
+    for (let i = 0; i <= frames; i += 1) {
+      const t = at(i / frames);
+      keyframes.push(css_to_keyframe(config.css(t, 1 - t)));
+    }
+    const animation = element.animate(keyframes, {
+      delay,
+      duration,
+      fill: outro ? "forwards" : "backwards"
+    });
+    animation.onfinish = finish;
+    return () => {
+      animation.onfinish = null;
+      animation.cancel();
+    };
+  }
 
-  return () => {
-    if (typeof result === "function") {
-      result();
-      return;
+  if (typeof config.tick === "function" && typeof requestAnimationFrame === "function") {
+    let frame = 0;
+    const start = performance.now() + delay;
+    config.tick(at(0), 1 - at(0));
+    const step = (now) => {
+      const progress = Math.min(Math.max(now - start, 0) / duration, 1);
+      const t = at(progress);
+      config.tick(t, 1 - t);
+      if (progress < 1) {
+        frame = requestAnimationFrame(step);
+      } else {
+        finish();
+      }
+    };
+    frame = requestAnimationFrame(step);
+    return () => cancelAnimationFrame(frame);
+  }
+
+  finish();
+  return null;
+}
+
+function css_to_keyframe(css) {
+  const keyframe = {};
+  for (const declaration of stringify(css).split(";")) {
+    const separator = declaration.indexOf(":");
+    if (separator === -1) {
+      continue;
     }
-    if (result && typeof result.destroy === "function") {
-      result.destroy();
+    const name = declaration.slice(0, separator).trim();
+    const value = declaration.slice(separator + 1).trim();
+    if (!name) {
+      continue;
     }
-  };
+    const property = name.startsWith("--")
+      ? name
+      : name.replace(/-([a-z])/g, (_, c) => c.toUpperCase());
+    keyframe[property] = value;
+  }
+  return keyframe;
 }
 
 function set_element_value(element, value) {
@@ -2001,6 +3677,63 @@ function get_group_binding_key(binding) {
   return `id:${String(binding?.id ?? "")}`;
 }
 
+function custom_element_attribute_name(prop_name, definition) {
+  return definition?.attribute ?? prop_name.toLowerCase();
+}
+
+function custom_element_prop_value(value, definition) {
+  switch (definition?.type) {
+    case "Boolean":
+      return value !== null && value !== "false";
+    case "Number":
+      return value == null ? value : Number(value);
+    case "Array":
+    case "Object":
+      if (value == null) {
+        return value;
+      }
+      try {
+        return JSON.parse(value);
+      } catch {
+        return value;
+      }
+    default:
+      return value;
+  }
+}
+
+function custom_element_attribute_value(value, definition) {
+  switch (definition?.type) {
+    case "Boolean":
+      return value ? "" : null;
+    case "Array":
+    case "Object":
+      return value == null ? null : JSON.stringify(value);
+    default:
+      return value == null ? null : stringify(value);
+  }
+}
+
+function custom_element_slot_props(host) {
+  const names = new Set();
+  for (const child of host.childNodes) {
+    if (child.nodeType === 1 && child.hasAttribute("slot")) {
+      names.add(child.getAttribute("slot"));
+    } else if (child.nodeType === 1 || (child.nodeType === 3 && child.data.trim())) {
+      names.add("default");
+    }
+  }
+
+  const slots = {};
+  for (const name of names) {
+    slots[name] = () =>
+      name === "default" ? "<slot></slot>" : `<slot name="${escape_attr(name)}"></slot>`;
+  }
+  return slots.default
+    ? { $$slots: slots, children: slots.default }
+    : { $$slots: slots };
+}
+
 function remove_binding_marker(element, attr_name) {
   if (!attr_name || typeof element?.removeAttribute !== "function") {
     return;
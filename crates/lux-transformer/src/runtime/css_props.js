@@ -0,0 +1,17 @@
+// Shared by the server and client runtimes, which append this file and
+// provide `escape_attr`.
+
+const CSS_PROPS_ATTRIBUTE = "data-lux-css-props";
+
+// The wrapper is marked so that a re-render sets the custom properties that
+// changed on it rather than replacing its whole style.
+export function css_props(html, props, is_svg = false) {
+  const styles = Object.entries(props ?? {})
+    .filter(([, value]) => value != null && value !== "")
+    .map(([name, value]) => `${name}: ${escape_attr(value)};`)
+    .join(" ");
+  if (is_svg) {
+    return `<g ${CSS_PROPS_ATTRIBUTE} style="${styles}">${html}</g>`;
+  }
+  return `<svelte-css-wrapper ${CSS_PROPS_ATTRIBUTE} style="display: contents; ${styles}">${html}</svelte-css-wrapper>`;
+}
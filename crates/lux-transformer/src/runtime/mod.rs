@@ -2,9 +2,9 @@ pub(crate) const SERVER_RUNTIME_SPECIFIER: &str = "lux/runtime/server";
 pub(crate) const CLIENT_RUNTIME_SPECIFIER: &str = "lux/runtime/client";
 
 pub(crate) fn server_runtime_source() -> &'static str {
-    include_str!("server.js")
+    concat!(include_str!("server.js"), include_str!("css_props.js"))
 }
 
 pub(crate) fn client_runtime_source() -> &'static str {
-    include_str!("client.js")
+    concat!(include_str!("client.js"), include_str!("css_props.js"))
 }
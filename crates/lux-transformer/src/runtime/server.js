@@ -285,12 +285,17 @@ export function render_component(component, props, render_state = null) {
     if (context) {
       options.context = context;
     }
+    const collect = (result) => {
+      if (render_state && result?.head) {
+        render_state.head += result.head;
+      }
+      return result?.body ?? result?.html ?? "";
+    };
     const result = svelteRender(component, options);
-    const body = result?.body ?? result?.html ?? "";
-    if (render_state && result?.head) {
-      render_state.head += result.head;
+    if (component.async) {
+      return Promise.resolve(result).then(collect);
     }
-    return body;
+    return collect(result);
   }
 
   if (component && typeof component.render === "function") {
@@ -307,6 +312,94 @@ export function render_component(component, props, render_state = null) {
   return "";
 }
 
+export function boundary(render_children, failed, onerror, pending) {
+  // A boundary cannot be reset while rendering on the server.
+  const reset = () => {};
+  const handle = (error) => {
+    if (typeof onerror === "function") {
+      onerror(error, reset);
+    }
+    if (typeof failed === "function") {
+      return failed(error, reset);
+    }
+    if (typeof onerror === "function") {
+      return "";
+    }
+    throw error;
+  };
+
+  let result;
+  try {
+    result = render_children();
+  } catch (error) {
+    return handle(error);
+  }
+  if (result && typeof result.then === "function") {
+    if (typeof pending === "function") {
+      result.catch(() => {});
+      return pending();
+    }
+    return result.then(undefined, handle);
+  }
+  return result;
+}
+
+export function create_stream_sink(controller) {
+  let buffer = "";
+  let head = "";
+  return {
+    push(chunk) {
+      buffer += chunk ?? "";
+    },
+    head(render) {
+      render({
+        push(chunk) {
+          head += chunk ?? "";
+        }
+      });
+    },
+    async flush() {
+      if (buffer) {
+        controller.enqueue(buffer);
+        buffer = "";
+      }
+    },
+    get head_html() {
+      return head;
+    }
+  };
+}
+
+export function render_stream(component, props = {}) {
+  let resolve_head;
+  const head = new Promise((resolve) => {
+    resolve_head = resolve;
+  });
+  const body = new ReadableStream({
+    async start(controller) {
+      const sink = create_stream_sink(controller);
+      try {
+        if (component && typeof component.stream === "function") {
+          await component.stream(sink, props);
+        } else {
+          const render_state = begin_render();
+          sink.push(await render_component(component, props, render_state));
+          if (render_state.head) {
+            sink.head((renderer) => renderer.push(render_state.head));
+          }
+        }
+        await sink.flush();
+        resolve_head(sink.head_html);
+        controller.close();
+      } catch (error) {
+        resolve_head("");
+        controller.error(error);
+      }
+    }
+  });
+  return { head, body };
+}
+
 export function event_attr() {
   return "";
 }
@@ -327,6 +420,14 @@ export function use_attr() {
   return "";
 }
 
+export function attach_attr() {
+  return "";
+}
+
+export function attachment_key() {
+  return Symbol("@attach");
+}
+
 export function transition_attr() {
   return "";
 }
@@ -0,0 +1,539 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lux_analyzer::analyze;
+use lux_parser::parse;
+use lux_test_support::{node_executable, workspace_root_from_manifest_dir};
+use lux_transformer::{TransformTarget, transform_for_target_with_filename};
+use oxc_allocator::Allocator;
+
+const CLIENT_DOM: &str = include_str!("fixtures/client_dom.mjs");
+
+#[test]
+fn client_runtime_mounts_and_updates_component() {
+    let output = run_client_components(
+        &[(
+            "App",
+            "<script>let count = $state(0);</script><button onclick={() => count++}>{count}</button>",
+        )],
+        r#"
+const target = mount_target();
+App(target, {});
+console.log(html(target));
+fire(target.querySelector("button"), "click");
+await tick();
+console.log(html(target));
+"#,
+    );
+
+    assert_eq!(output, "<button>0</button>\n<button>1</button>");
+}
+
+#[test]
+fn client_runtime_custom_element_renders_props_after_its_styles() {
+    let output = run_client_components(
+        &[(
+            "Label",
+            r#"<svelte:options customElement="my-label" />
+<script>
+    let { label } = $props();
+</script>
+
+<p>{label}</p>
+
+<style>
+    p { color: red; }
+</style>"#,
+        )],
+        r#"
+const element = document.createElement("my-label");
+element.setAttribute("label", "a");
+document.body.appendChild(element);
+await tick();
+const root = element.shadowRoot;
+console.log(html(root).replace(/<style>.*<\/style>/, "<style/>").replace(/ class="[^"]*"/, ""));
+element.setAttribute("label", "b");
+element.label = "c";
+await tick();
+console.log(html(root).replace(/<style>.*<\/style>/, "<style/>").replace(/ class="[^"]*"/, ""));
+console.log(root.querySelectorAll("style").length);
+"#,
+    );
+
+    assert_eq!(output, "<style/><p>a</p>\n<style/><p>c</p>\n1");
+}
+
+#[test]
+fn client_runtime_custom_element_without_shadow_root_styles_the_document_head() {
+    let output = run_client_components(
+        &[(
+            "Label",
+            r#"<svelte:options customElement={{ tag: "my-label", shadow: "none" }} />
+
+<p>label</p>
+
+<style>
+    p { color: red; }
+</style>"#,
+        )],
+        r#"
+document.body.appendChild(document.createElement("my-label"));
+document.body.appendChild(document.createElement("my-label"));
+await tick();
+const styles = document.head.querySelectorAll("style");
+console.log(styles.length, /color: ?red/.test(styles[0].textContent));
+console.log(document.body.querySelectorAll("p").length);
+"#,
+    );
+
+    assert_eq!(output, "1 true\n2");
+}
+
+#[test]
+fn client_runtime_updates_css_custom_properties_in_place() {
+    let output = run_client_components(
+        &[
+            ("Child", "<p>child</p>"),
+            (
+                "App",
+                r#"<script>
+    import Child from "./Child.svelte";
+    let color = $state("red");
+</script>
+<button onclick={() => (color = "blue")}>change</button>
+<Child --color={color} --size="2px" />"#,
+            ),
+        ],
+        r#"
+const target = mount_target();
+App(target, {});
+const wrapper = target.querySelector("svelte-css-wrapper");
+const child = target.querySelector("p");
+fire(target.querySelector("button"), "click");
+await tick();
+console.log(target.querySelector("svelte-css-wrapper") === wrapper, target.querySelector("p") === child);
+console.log(wrapper.getAttribute("style"));
+console.log((wrapper.style_writes ?? []).join(","));
+"#,
+    );
+
+    assert_eq!(
+        output,
+        "true true\ndisplay: contents; --color: blue; --size: 2px;\n--color=blue"
+    );
+}
+
+#[test]
+fn client_runtime_mounting_again_cleans_up_the_previous_mount() {
+    let output = run_client_components(
+        &[(
+            "App",
+            r#"<script>
+    let { label } = $props();
+    let count = $state(0);
+    $effect(() => {
+        log.push("mount");
+        return () => log.push("cleanup");
+    });
+</script>
+<button onclick={() => count++}>{label}: {count}</button>"#,
+        )],
+        r#"
+globalThis.log = [];
+const target = mount_target();
+App(target, { label: "a" });
+await tick();
+fire(target.querySelector("button"), "click");
+await tick();
+App(target, { label: "b" });
+await tick();
+console.log(html(target));
+console.log(log.join(","));
+"#,
+    );
+
+    assert_eq!(output, "<button>b: 0</button>\nmount,cleanup,mount");
+}
+
+#[test]
+fn client_runtime_effects_track_the_reads_they_make() {
+    let output = run_client_components(
+        &[(
+            "App",
+            r#"<script>
+    let count = $state(0);
+    let other = $state(0);
+    let items = $state([]);
+    function total() {
+        return count * 10 + items.length;
+    }
+    $effect(() => {
+        log.push(`total ${total()}`);
+    });
+    $effect(() => {
+        const count = "local";
+        log.push(`shadowed ${count}`);
+    });
+</script>
+<button onclick={() => count++}>{count}</button>
+<button onclick={() => other++}>{other}</button>
+<button onclick={() => items.push(1)}>push</button>"#,
+        )],
+        r#"
+globalThis.log = [];
+const target = mount_target();
+App(target, {});
+await tick();
+const [count, other, push] = target.querySelectorAll("button");
+fire(count, "click");
+await tick();
+fire(other, "click");
+await tick();
+fire(push, "click");
+await tick();
+console.log(log.join(","));
+"#,
+    );
+
+    assert_eq!(output, "total 0,shadowed local,total 10,total 11");
+}
+
+#[test]
+fn client_runtime_effect_tracking_is_true_in_effects_and_the_template() {
+    let output = run_client_components(
+        &[(
+            "App",
+            r#"<script>
+    const in_script = $effect.tracking();
+    $effect(() => {
+        log.push(`effect ${$effect.tracking()} ${$effect.pending()}`);
+    });
+</script>
+<p>{in_script} {$effect.tracking()} {$effect.pending()}</p>
+<button onclick={() => globalThis.log.push(`handler ${$effect.tracking()}`)}>go</button>"#,
+        )],
+        r#"
+globalThis.log = [];
+const target = mount_target();
+App(target, {});
+await tick();
+fire(target.querySelector("button"), "click");
+console.log(target.querySelector("p").textContent);
+console.log(log.join(","));
+"#,
+    );
+
+    assert_eq!(output, "false true 0\neffect true 0,handler false");
+}
+
+#[test]
+fn client_runtime_attachments_rerun_only_when_what_they_read_changes() {
+    let output = run_client_components(
+        &[
+            (
+                "Child",
+                "<script>let { children, ...rest } = $props();</script><span {...rest}>child</span>",
+            ),
+            (
+                "App",
+                r#"<script>
+    import Child from "./Child.svelte";
+    let color = $state("red");
+    let clicks = $state(0);
+    function paint(node) {
+        node.setAttribute("data-color", color);
+        log.push(`paint ${color}`);
+        return () => log.push(`unpaint ${color}`);
+    }
+    function tooltip(text) {
+        return (node) => {
+            log.push(`tooltip ${text}`);
+            return () => log.push(`untooltip ${text}`);
+        };
+    }
+</script>
+<button onclick={() => clicks++} {@attach paint}>{clicks}</button>
+<p {@attach tooltip(color)}></p>
+<Child {@attach paint} />
+<button onclick={() => (color = "blue")}>blue</button>"#,
+            ),
+        ],
+        r#"
+globalThis.log = [];
+const target = mount_target();
+const cleanup = App(target, {});
+await tick();
+console.log(log.splice(0).join(","));
+const [count, blue] = target.querySelectorAll("button");
+fire(count, "click");
+await tick();
+console.log(count.textContent, count.getAttribute("data-color"), `[${log.splice(0).join(",")}]`);
+fire(blue, "click");
+await tick();
+console.log(target.querySelector("span").getAttribute("data-color"), log.splice(0).join(","));
+cleanup();
+console.log(log.join(","));
+"#,
+    );
+
+    assert_eq!(
+        output,
+        "paint red,tooltip red,paint red\n\
+         1 red []\n\
+         blue unpaint red,paint blue,untooltip red,tooltip blue,unpaint red,paint blue\n\
+         unpaint blue,untooltip blue,unpaint blue"
+    );
+}
+
+#[test]
+fn client_runtime_writes_to_state_objects_render_only_their_readers() {
+    let output = run_client_components(
+        &[
+            (
+                "Shared",
+                r#"<script module>
+    export const shared = $state({ n: 0 });
+    class Counter {
+        #count = "private";
+        count = $state(0);
+        constructor(label) {
+            this.label = $state(label);
+        }
+        get secret() {
+            return this.#count;
+        }
+    }
+    export const counter = new Counter("a");
+    export function rendered(name) {
+        log.push(name);
+        return "";
+    }
+</script>"#,
+            ),
+            (
+                "Reader",
+                r#"<script>import { shared, rendered } from "./Shared.svelte";</script><p>{rendered("reader")}{shared.n}</p>"#,
+            ),
+            (
+                "Other",
+                r#"<script>
+    import { counter, rendered } from "./Shared.svelte";
+    $effect(() => {
+        log.push(`effect ${counter.label}`);
+    });
+</script>
+<p>{rendered("other")}{counter.count} {counter.label} {counter.secret}</p>"#,
+            ),
+        ],
+        r#"
+import { shared, counter } from "./Shared.js";
+globalThis.log = [];
+const reader = mount_target();
+const other = mount_target();
+Reader(reader, {});
+Other(other, {});
+await tick();
+console.log(log.splice(0).join(","));
+shared.n++;
+await tick();
+console.log(html(reader), log.splice(0).join(","));
+counter.count++;
+await tick();
+console.log(html(other), log.splice(0).join(","));
+counter.label = "b";
+await tick();
+console.log(html(other), log.splice(0).join(","));
+"#,
+    );
+
+    assert_eq!(
+        output,
+        "reader,other,effect a\n\
+         <p>1</p> reader\n\
+         <p>1 a private</p> other,effect a\n\
+         <p>1 b private</p> other,effect b"
+    );
+}
+
+#[test]
+fn client_runtime_custom_element_updates_props_without_remounting() {
+    let output = run_client_components(
+        &[(
+            "Counter",
+            r#"<svelte:options customElement="my-counter" />
+<script>
+    let { label } = $props();
+    let count = $state(0);
+    $effect(() => {
+        log.push("mount");
+        return () => log.push("unmount");
+    });
+</script>
+
+<button onclick={() => count++}>{label}: {count}</button>
+
+<style>
+    button { color: red; }
+</style>"#,
+        )],
+        r#"
+globalThis.log = [];
+const element = document.createElement("my-counter");
+element.setAttribute("label", "a");
+document.body.appendChild(element);
+await tick();
+const root = element.shadowRoot;
+const button = root.querySelector("button");
+fire(button, "click");
+await tick();
+element.setAttribute("label", "b");
+element.label = "c";
+await tick();
+console.log(html(root).replace(/<style>.*<\/style>/, "<style/>").replace(/ class="[^"]*"/, ""));
+console.log(root.querySelectorAll("style").length, root.querySelector("button") === button, log.join(","));
+document.body.removeChild(element);
+await tick();
+console.log(log.join(","));
+"#,
+    );
+
+    assert_eq!(
+        output,
+        "<style/><button>c: 1</button>\n1 true mount\nmount,unmount"
+    );
+}
+
+#[test]
+fn client_runtime_transition_errors_reach_the_boundary() {
+    let output = run_client_components(
+        &[(
+            "App",
+            r#"<script>
+    let show = $state(false);
+    function boom() {
+        return { in() { throw new Error("intro failed"); } };
+    }
+</script>
+<button onclick={() => (show = true)}>show</button>
+<svelte:boundary>
+    {#if show}<p in:boom>shown</p>{/if}
+    {#snippet failed(error)}<p>{error.message}</p>{/snippet}
+</svelte:boundary>"#,
+        )],
+        r#"
+const target = mount_target();
+App(target, {});
+fire(target.querySelector("button"), "click");
+await tick();
+await new Promise((resolve) => setTimeout(resolve, 0));
+console.log(html(target.querySelector("p")));
+"#,
+    );
+
+    assert_eq!(output, "intro failed");
+}
+
+#[test]
+fn client_runtime_removes_delegated_root_listeners_on_cleanup() {
+    let output = run_client_components(
+        &[(
+            "App",
+            "<script>let count = $state(0);</script><button onclick={() => count++}>{count}</button><button onclick={() => count--}>-</button>",
+        )],
+        r#"
+const target = mount_target();
+const cleanup = App(target, {});
+const listeners = () => target.listeners.map((entry) => entry.type).join(",");
+console.log(listeners());
+cleanup();
+console.log(`[${listeners()}]`);
+"#,
+    );
+
+    assert_eq!(output, "click\n[]");
+}
+
+/// Compiles the components for the client, mounts them in node with the
+/// DOM from `fixtures/client_dom.mjs` by running `script`, and returns what
+/// the script logged. The script can import the components by name.
+fn run_client_components(components: &[(&str, &str)], script: &str) -> String {
+    let output_dir = prepare_output_dir();
+    let mut imports =
+        String::from("import { fire, html, mount_target, tick } from \"./dom.mjs\";\n");
+    for (name, source) in components {
+        let allocator = Allocator::default();
+        let parsed = parse(source, &allocator, false);
+        assert!(parsed.errors.is_empty(), "parse should succeed for {name}");
+        let analysis = analyze(&parsed.root);
+        let filename = format!("{name}.svelte");
+        let result = transform_for_target_with_filename(
+            &parsed.root,
+            &analysis,
+            TransformTarget::Client,
+            Some(&filename),
+        );
+        write_runtime_modules(&output_dir, &result.runtime_modules);
+        write_file(
+            &output_dir.join(format!("{name}.js")),
+            &result.js.replace(".svelte\"", ".js\""),
+        );
+        imports.push_str(&format!("import {name} from \"./{name}.js\";\n"));
+    }
+    write_file(&output_dir.join("dom.mjs"), CLIENT_DOM);
+    // The DOM is set up before the components and the runtime load.
+    write_file(&output_dir.join("main.mjs"), &format!("{imports}{script}"));
+
+    let run = Command::new(node_executable())
+        .arg("main.mjs")
+        .current_dir(&output_dir)
+        .output()
+        .unwrap_or_else(|error| panic!("failed to run node: {error}"));
+    assert!(
+        run.status.success(),
+        "client script failed\nstdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&run.stdout),
+        String::from_utf8_lossy(&run.stderr),
+    );
+    let _ = fs::remove_dir_all(&output_dir);
+    String::from_utf8_lossy(&run.stdout).trim_end().to_string()
+}
+
+fn prepare_output_dir() -> PathBuf {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time after epoch");
+    let output_dir = workspace_root_from_manifest_dir(&manifest_dir)
+        .join("target")
+        .join("lux-client-runtime")
+        .join(format!("{}-{}", std::process::id(), now.as_nanos()));
+    let _ = fs::remove_dir_all(&output_dir);
+    fs::create_dir_all(&output_dir)
+        .unwrap_or_else(|error| panic!("failed to create {}: {error}", output_dir.display()));
+    output_dir
+}
+
+fn write_runtime_modules(output_dir: &Path, modules: &[lux_transformer::RuntimeModule]) {
+    let package_root = output_dir.join("node_modules").join("lux");
+    write_file(
+        &package_root.join("package.json"),
+        r#"{"name":"lux","type":"module","exports":{"./runtime/client":"./runtime/client.js"}}"#,
+    );
+    for module in modules {
+        let relative = module
+            .specifier
+            .strip_prefix("lux/")
+            .unwrap_or(&module.specifier);
+        write_file(&package_root.join(format!("{relative}.js")), &module.code);
+    }
+}
+
+fn write_file(path: &Path, contents: &str) {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    fs::write(path, contents)
+        .unwrap_or_else(|error| panic!("failed to write {}: {error}", path.display()));
+}
@@ -0,0 +1,433 @@
+// A small DOM for running client components in node. It parses the markup
+// that the client runtime produces, dispatches bubbling events, keeps
+// inline styles in sync with the `style` attribute and supports custom
+// elements with a shadow root.
+
+const VOID_ELEMENTS = new Set(["area", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "wbr"]);
+const registry = new Map();
+let constructing_tag = null;
+let owner_document = null;
+
+class Node {
+  constructor(type, name) {
+    this.nodeType = type;
+    this.nodeName = name;
+    this.childNodes = [];
+    this.parentNode = null;
+    this.ownerDocument = owner_document;
+    this.listeners = [];
+  }
+
+  get firstChild() {
+    return this.childNodes[0] ?? null;
+  }
+
+  get lastChild() {
+    return this.childNodes[this.childNodes.length - 1] ?? null;
+  }
+
+  get nextSibling() {
+    const siblings = this.parentNode?.childNodes ?? [];
+    return siblings[siblings.indexOf(this) + 1] ?? null;
+  }
+
+  get isConnected() {
+    let node = this;
+    while (node.parentNode || node.host) {
+      node = node.parentNode ?? node.host;
+    }
+    return node === document;
+  }
+
+  get nodeValue() {
+    return this.data ?? null;
+  }
+
+  set nodeValue(value) {
+    this.data = value;
+  }
+
+  get textContent() {
+    return this.nodeType === 3 ? this.data : this.childNodes.map((child) => child.textContent ?? "").join("");
+  }
+
+  set textContent(value) {
+    for (const child of [...this.childNodes]) {
+      this.removeChild(child);
+    }
+    this.appendChild(document.createTextNode(value));
+  }
+
+  insertBefore(node, reference) {
+    if (node.nodeType === 11 && !node.host) {
+      for (const child of [...node.childNodes]) {
+        this.insertBefore(child, reference);
+      }
+      return node;
+    }
+    if (node.parentNode) {
+      node.parentNode.removeChild(node);
+    }
+    const index = reference ? this.childNodes.indexOf(reference) : this.childNodes.length;
+    if (index < 0) {
+      throw new Error("reference node is not a child");
+    }
+    this.childNodes.splice(index, 0, node);
+    node.parentNode = this;
+    if (node.isConnected) {
+      connect(node);
+    }
+    return node;
+  }
+
+  appendChild(node) {
+    return this.insertBefore(node, null);
+  }
+
+  removeChild(node) {
+    const index = this.childNodes.indexOf(node);
+    if (index < 0) {
+      throw new Error("node is not a child");
+    }
+    const connected = node.isConnected;
+    this.childNodes.splice(index, 1);
+    node.parentNode = null;
+    if (connected) {
+      disconnect(node);
+    }
+    return node;
+  }
+
+  get innerHTML() {
+    return this.childNodes.map(serialize).join("");
+  }
+
+  set innerHTML(html) {
+    for (const child of [...this.childNodes]) {
+      this.removeChild(child);
+    }
+    parse(html, this);
+  }
+
+  addEventListener(type, listener, options) {
+    this.listeners.push({ type, listener, options });
+  }
+
+  removeEventListener(type, listener) {
+    this.listeners = this.listeners.filter(
+      (entry) => entry.type !== type || entry.listener !== listener
+    );
+  }
+
+  dispatchEvent(event) {
+    if (!event.target) {
+      Object.defineProperty(event, "target", { configurable: true, value: this });
+    }
+    for (let node = this; node && !event.cancelBubble; node = node.parentNode ?? node.host) {
+      Object.defineProperty(event, "currentTarget", { configurable: true, value: node });
+      for (const entry of [...node.listeners]) {
+        if (entry.type === event.type) {
+          entry.listener.call(node, event);
+        }
+      }
+    }
+    return true;
+  }
+
+  querySelectorAll(selector) {
+    const out = [];
+    const attribute = selector.match(/^\[([\w-]+)="(.*)"\]$/);
+    const walk = (node) => {
+      for (const child of node.childNodes) {
+        if (child.nodeType !== 1) {
+          continue;
+        }
+        if (
+          selector === "*" ||
+          child.nodeName.toLowerCase() === selector ||
+          (attribute && child.getAttribute(attribute[1]) === attribute[2].replace(/\\(.)/g, "$1"))
+        ) {
+          out.push(child);
+        }
+        walk(child);
+      }
+    };
+    walk(this);
+    return out;
+  }
+
+  querySelector(selector) {
+    return this.querySelectorAll(selector)[0] ?? null;
+  }
+}
+
+class Element extends Node {
+  constructor(name) {
+    super(1, name.toUpperCase());
+    this.localName = name.toLowerCase();
+    this.attrs = new Map();
+    this.namespaceURI = "http://www.w3.org/1999/xhtml";
+    this.shadowRoot = null;
+    if (this.localName === "template") {
+      this.content = new Node(11, "#document-fragment");
+    }
+  }
+
+  get attributes() {
+    return [...this.attrs].map(([name, value]) => ({ name, value }));
+  }
+
+  getAttribute(name) {
+    return this.attrs.get(name) ?? null;
+  }
+
+  hasAttribute(name) {
+    return this.attrs.has(name);
+  }
+
+  setAttribute(name, value) {
+    const previous = this.getAttribute(name);
+    this.attrs.set(name, String(value));
+    changed_attribute(this, name, previous, String(value));
+  }
+
+  removeAttribute(name) {
+    const previous = this.getAttribute(name);
+    this.attrs.delete(name);
+    changed_attribute(this, name, previous, null);
+  }
+
+  get style() {
+    return new StyleDeclaration(this);
+  }
+
+  get innerHTML() {
+    return super.innerHTML;
+  }
+
+  set innerHTML(html) {
+    const target = this.content ?? this;
+    for (const child of [...target.childNodes]) {
+      target.removeChild(child);
+    }
+    parse(html, target);
+  }
+
+  attachShadow() {
+    this.shadowRoot = new Node(11, "#shadow-root");
+    this.shadowRoot.host = this;
+    return this.shadowRoot;
+  }
+
+  getBoundingClientRect() {
+    return { left: 0, top: 0, width: 0, height: 0 };
+  }
+}
+
+// Inline styles read and write the `style` attribute, and record the
+// properties that were set on them.
+class StyleDeclaration {
+  constructor(element) {
+    this.element = element;
+  }
+
+  entries() {
+    return (this.element.getAttribute("style") ?? "")
+      .split(";")
+      .map((declaration) => declaration.trim())
+      .filter(Boolean)
+      .map((declaration) => {
+        const colon = declaration.indexOf(":");
+        return [declaration.slice(0, colon).trim(), declaration.slice(colon + 1).trim()];
+      });
+  }
+
+  write(entries) {
+    this.element.attrs.set(
+      "style",
+      entries.map(([name, value]) => `${name}: ${value};`).join(" ")
+    );
+  }
+
+  get length() {
+    return this.entries().length;
+  }
+
+  item(index) {
+    return this.entries()[index]?.[0] ?? "";
+  }
+
+  getPropertyValue(name) {
+    return this.entries().find(([key]) => key === name)?.[1] ?? "";
+  }
+
+  setProperty(name, value) {
+    (this.element.style_writes ??= []).push(`${name}=${value}`);
+    const entries = this.entries();
+    const entry = entries.find(([key]) => key === name);
+    if (entry) {
+      entry[1] = String(value);
+    } else {
+      entries.push([name, String(value)]);
+    }
+    this.write(entries);
+  }
+
+  removeProperty(name) {
+    (this.element.style_writes ??= []).push(`${name}=`);
+    this.write(this.entries().filter(([key]) => key !== name));
+  }
+}
+
+class HTMLElement extends Element {
+  constructor() {
+    super(constructing_tag ?? "unknown-element");
+  }
+}
+
+function connect(node) {
+  if (typeof node.connectedCallback === "function") {
+    node.connectedCallback();
+  }
+  for (const child of [...node.childNodes]) {
+    connect(child);
+  }
+}
+
+function disconnect(node) {
+  if (typeof node.disconnectedCallback === "function") {
+    node.disconnectedCallback();
+  }
+  for (const child of [...node.childNodes]) {
+    disconnect(child);
+  }
+}
+
+function changed_attribute(element, name, previous, next) {
+  const observed = element.constructor.observedAttributes ?? [];
+  if (typeof element.attributeChangedCallback === "function" && observed.includes(name)) {
+    element.attributeChangedCallback(name, previous, next);
+  }
+}
+
+function parse(html, parent) {
+  const pattern =
+    /<!--([\s\S]*?)-->|<\/([\w-]+)\s*>|<([\w-]+)((?:\s+[^\s"'>\/=]+(?:="[^"]*")?)*)\s*(\/?)>|([^<]+)/g;
+  let current = parent;
+  let match;
+  while ((match = pattern.exec(html))) {
+    const [, comment, closing, name, attributes, self_closing, text] = match;
+    if (comment !== undefined) {
+      current.appendChild(document.createComment(comment));
+    } else if (closing) {
+      current = current.parentNode ?? parent;
+    } else if (name) {
+      const element = document.createElement(name);
+      for (const attribute of attributes.matchAll(/([^\s"'>\/=]+)(?:="([^"]*)")?/g)) {
+        element.attrs.set(attribute[1], decode(attribute[2] ?? ""));
+      }
+      current.appendChild(element);
+      if (!self_closing && !VOID_ELEMENTS.has(name.toLowerCase())) {
+        current = element.content ?? element;
+      }
+    } else {
+      current.appendChild(document.createTextNode(decode(text)));
+    }
+  }
+}
+
+function decode(text) {
+  return text
+    .replace(/&quot;/g, '"')
+    .replace(/&#39;/g, "'")
+    .replace(/&lt;/g, "<")
+    .replace(/&gt;/g, ">")
+    .replace(/&amp;/g, "&");
+}
+
+function serialize(node) {
+  if (node.nodeType === 3) {
+    return node.data;
+  }
+  if (node.nodeType === 8) {
+    return `<!--${node.data}-->`;
+  }
+  const inner = node.childNodes.map(serialize).join("");
+  if (node.nodeType !== 1) {
+    return inner;
+  }
+  const attributes = node.attributes.map(({ name, value }) => ` ${name}="${value}"`).join("");
+  return `<${node.localName}${attributes}>${inner}</${node.localName}>`;
+}
+
+const document = new Node(9, "#document");
+owner_document = document;
+document.createElement = (name) => {
+  const definition = registry.get(name);
+  if (!definition) {
+    return new Element(name);
+  }
+  constructing_tag = name;
+  try {
+    return new definition();
+  } finally {
+    constructing_tag = null;
+  }
+};
+document.createTextNode = (data) => {
+  const node = new Node(3, "#text");
+  node.data = String(data);
+  return node;
+};
+document.createComment = (data) => {
+  const node = new Node(8, "#comment");
+  node.data = String(data);
+  return node;
+};
+document.createDocumentFragment = () => new Node(11, "#document-fragment");
+document.head = new Element("head");
+document.body = new Element("body");
+document.appendChild(document.head);
+document.appendChild(document.body);
+
+globalThis.document = document;
+globalThis.Node = Node;
+globalThis.HTMLElement = HTMLElement;
+globalThis.CSS = { escape: (value) => String(value).replace(/["\\]/g, "\\$&") };
+globalThis.customElements = {
+  define(name, definition) {
+    registry.set(name, definition);
+  },
+  get(name) {
+    return registry.get(name);
+  }
+};
+
+// Renders the markup inside `node` without the comments that delimit
+// blocks and mounted regions.
+export function html(node) {
+  return node.innerHTML.replace(/<!--[\s\S]*?-->/g, "");
+}
+
+export function fire(target, type) {
+  target.dispatchEvent({
+    type,
+    target,
+    cancelBubble: false,
+    stopPropagation() {
+      this.cancelBubble = true;
+    },
+    preventDefault() {}
+  });
+}
+
+// Waits for the scheduled renders and effects to run.
+export function tick() {
+  return new Promise((resolve) => setTimeout(resolve, 0));
+}
+
+export function mount_target() {
+  const target = document.createElement("div");
+  document.body.appendChild(target);
+  return target;
+}
@@ -1,6 +1,12 @@
+use std::cell::RefCell;
+
 use lux_analyzer::analyze;
 use lux_parser::parse;
-use lux_transformer::{TransformTarget, transform, transform_for_target, transform_with_filename};
+use lux_transformer::{
+    CssAssetKind, CssAssetReference, CssHash, CssHashCallback, CssHashInput, CssUrlResolver,
+    TransformOptions, TransformTarget, transform, transform_for_target, transform_with_filename,
+    transform_with_options,
+};
 use lux_utils::hash::hash;
 use oxc_allocator::Allocator;
 use oxc_parser::Parser;
@@ -54,6 +60,58 @@ fn transform_uses_filename_for_css_hash_when_available() {
     assert_eq!(result.css_scope.as_deref(), Some(expected_scope.as_str()));
 }
 
+#[test]
+fn transform_names_css_scope_with_css_hash_option() {
+    let source = "<style>h1 { color: red; }</style><h1>Hello</h1>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let filename = "src/lib/button/index.svelte";
+    let expected_hash = hash(filename);
+
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            filename: Some(filename),
+            css_hash: Some(CssHash::Template("[name]-[hash]")),
+            ..TransformOptions::default()
+        },
+    );
+    let expected_scope = format!("Button-{expected_hash}");
+    assert_eq!(result.css_hash.as_deref(), Some(expected_hash.as_str()));
+    assert_eq!(result.css_scope.as_deref(), Some(expected_scope.as_str()));
+    let css = result.css.expect("expected transformed css");
+    assert!(css.contains(&format!("h1.{expected_scope}")), "{css}");
+    assert!(
+        result
+            .js
+            .contains(&format!("class=\\\"{expected_scope}\\\"")),
+        "{}",
+        result.js
+    );
+
+    let name_scope = |input: &CssHashInput<'_>| {
+        assert_eq!(input.css, "h1 { color: red; }");
+        format!("{}_{}", input.name.to_lowercase(), input.default_hash)
+    };
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            filename: Some(filename),
+            css_hash: Some(CssHash::Callback(CssHashCallback(&name_scope))),
+            ..TransformOptions::default()
+        },
+    );
+    assert_eq!(
+        result.css_scope.as_deref(),
+        Some(format!("button_{expected_hash}").as_str())
+    );
+}
+
 #[test]
 fn transform_has_no_css_payload_without_style_blocks() {
     let source = "<h1>Hello</h1>";
@@ -106,6 +164,286 @@ fn transform_scopes_local_part_of_mixed_global_selector() {
     assert!(!css.contains(":global("));
 }
 
+#[test]
+fn transform_prunes_selectors_whose_combinators_do_not_match() {
+    let source = "<style>.a > .b { color: red; } .c > .b { color: blue; } .a + .c { color: green; } .b + .c { color: gray; }</style><div class=\"a\"><p class=\"b\"></p></div><p class=\"c\"></p>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform(&parsed.root, &analysis);
+
+    let css = result.css.expect("expected transformed css");
+    assert!(css.contains("color: red;"), "{css}");
+    assert!(!css.contains("color: blue;"), "{css}");
+    assert!(css.contains("color: green;"), "{css}");
+    assert!(!css.contains("color: gray;"), "{css}");
+}
+
+#[test]
+fn transform_scopes_and_prunes_pseudo_class_selector_arguments() {
+    let source = "<style>div:has(> p) { color: red; } div:has(.missing) { color: blue; } :is(.a, .missing) p { color: green; } p:not(.b) { color: gray; } :root:has(.b) { color: black; } p:is(.a .b) { color: white; }</style><div class=\"a\"><p class=\"b\"></p></div>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform(&parsed.root, &analysis);
+
+    let scope = result.css_scope.expect("expected css scope");
+    let css = result.css.expect("expected transformed css");
+    assert!(
+        css.contains(&format!(
+            "div.{scope}:has(> p:where(.{scope})) {{ color: red; }}"
+        )),
+        "{css}"
+    );
+    assert!(!css.contains("color: blue;"), "{css}");
+    assert!(
+        css.contains(&format!(
+            ":is(.a:where(.{scope})) p.{scope} {{ color: green; }}"
+        )),
+        "{css}"
+    );
+    assert!(
+        css.contains(&format!("p.{scope}:not(.b) {{ color: gray; }}")),
+        "{css}"
+    );
+    assert!(
+        css.contains(&format!(":root:has(.b.{scope}) {{ color: black; }}")),
+        "{css}"
+    );
+    assert!(
+        css.contains(&format!(
+            "p.{scope}:is(.a:where(.{scope}) .b:where(.{scope})) {{ color: white; }}"
+        )),
+        "{css}"
+    );
+    assert!(
+        result
+            .js
+            .contains(&format!("<p class=\\\"b {scope}\\\"></p>")),
+        "{}",
+        result.js
+    );
+}
+
+#[test]
+fn transform_keeps_selectors_matching_inside_blocks_and_loops() {
+    let source = "<style>.a { color: red; } .i + .i { color: blue; } .x .a { color: green; }</style>{#if ok}<p class=\"a\"></p>{/if}{#each items as item}<li class=\"i\">{item}</li>{/each}{#snippet row()}<span class=\"a\"></span>{/snippet}";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform(&parsed.root, &analysis);
+
+    let css = result.css.expect("expected transformed css");
+    assert!(css.contains("color: red;"), "{css}");
+    assert!(css.contains("color: blue;"), "{css}");
+    assert!(css.contains("color: green;"), "{css}");
+}
+
+#[test]
+fn transform_collects_and_rewrites_css_asset_references() {
+    let source = "<p>x</p><style>@import 'base.css'; p { background: url(img/bg.png) no-repeat; src: url(\"font.woff2\") format(\"woff2\"); } @media print { p { background: image-set(url('print.png') 1x); } }</style>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let resolve = |reference: &CssAssetReference| {
+        (reference.value != "print.png").then(|| format!("/assets/{}", reference.value))
+    };
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            css_url_resolver: Some(CssUrlResolver(&resolve)),
+            ..TransformOptions::default()
+        },
+    );
+
+    let assets = result
+        .css_assets
+        .iter()
+        .map(|reference| {
+            (
+                reference.kind,
+                reference.value.as_str(),
+                &source[reference.span.start as usize..reference.span.end as usize],
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        assets,
+        [
+            (CssAssetKind::Import, "base.css", "'base.css'"),
+            (CssAssetKind::Url, "img/bg.png", "url(img/bg.png)"),
+            (CssAssetKind::Url, "font.woff2", "url(\"font.woff2\")"),
+            (CssAssetKind::Url, "print.png", "url('print.png')"),
+        ]
+    );
+
+    let css = result.css.expect("expected transformed css");
+    assert!(css.contains("@import '/assets/base.css';"), "{css}");
+    assert!(
+        css.contains("background: url(\"/assets/img/bg.png\") no-repeat;"),
+        "{css}"
+    );
+    assert!(
+        css.contains("src: url(\"/assets/font.woff2\") format(\"woff2\");"),
+        "{css}"
+    );
+    assert!(css.contains("image-set(url('print.png') 1x)"), "{css}");
+}
+
+#[test]
+fn transform_collects_and_resolves_only_rendered_css_asset_references() {
+    let source = "<p>x</p><style>p { background: url(bg.png); } .missing { background: url(unused.png); }</style>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let calls = RefCell::new(Vec::new());
+    let resolve = |reference: &CssAssetReference| {
+        calls.borrow_mut().push(reference.value.clone());
+        Some(format!("/assets/{}", reference.value))
+    };
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            target: TransformTarget::Client,
+            css_url_resolver: Some(CssUrlResolver(&resolve)),
+            ..TransformOptions::default()
+        },
+    );
+
+    assert_eq!(calls.into_inner(), ["bg.png"]);
+    let assets = result
+        .css_assets
+        .iter()
+        .map(|reference| reference.value.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(assets, ["bg.png"]);
+    let css = result.css.expect("expected transformed css");
+    assert!(css.contains("url(\"/assets/bg.png\")"), "{css}");
+    assert!(
+        result.js.contains("url(\\\"/assets/bg.png\\\")"),
+        "{}",
+        result.js
+    );
+}
+
+#[test]
+fn transform_lowers_nested_css_rules_when_requested() {
+    let source = "<p class=\"a\"><span class=\"b\">x</span></p><style>.a { color: red; .b { color: blue; } &:hover { color: green; } > span { margin: 0; } @media (min-width: 10px) { color: gray; } padding: 0; }</style>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            lower_css_nesting: true,
+            ..TransformOptions::default()
+        },
+    );
+
+    let css = result.css.expect("expected transformed css");
+    let scope = result.css_scope.expect("expected css scope");
+    let expected = [
+        format!(".a.{scope} {{ color: red; }}"),
+        format!(".a.{scope} .b.{scope} {{ color: blue; }}"),
+        format!(".a.{scope}:hover {{ color: green; }}"),
+        format!(".a.{scope} > span.{scope} {{ margin: 0; }}"),
+        format!("@media (min-width: 10px){{ .a.{scope} {{ color: gray; }} }}"),
+        format!(".a.{scope} {{ padding: 0; }}"),
+    ]
+    .join("\n");
+    assert_eq!(css, expected);
+}
+
+#[test]
+fn transform_lowers_nesting_selectors_against_each_parent_selector() {
+    let source = "<div class=\"y\"><p class=\"a\"><span class=\"c\"><i class=\"b x\"></i></span></p></div><style>.a, .b { .c & { color: red; } &.x { color: blue; } .y & { color: green; } } .a .b { .y & { color: gray; } .x& { color: white; } }</style>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            lower_css_nesting: true,
+            ..TransformOptions::default()
+        },
+    );
+
+    let css = result.css.expect("expected transformed css");
+    let scope = result.css_scope.expect("expected css scope");
+    let expected = [
+        format!(".c.{scope} .a.{scope}, .c.{scope} .b.{scope} {{ color: red; }}"),
+        format!(".a.{scope}.x, .b.{scope}.x {{ color: blue; }}"),
+        format!(".y.{scope} .a.{scope}, .y.{scope} .b.{scope} {{ color: green; }}"),
+        format!(".y.{scope} :is(.a.{scope} .b:where(.{scope})) {{ color: gray; }}"),
+        format!(".x:is(.a.{scope} .b:where(.{scope})) {{ color: white; }}"),
+    ]
+    .join("\n");
+    assert_eq!(css, expected);
+}
+
+#[test]
+fn transform_minifies_css_when_requested() {
+    let source = "<p>x</p><style>\n  p {\n    margin: 0px 0.0em calc(0px + 1em);\n    color: #AABBCC;\n    font-family: \"A  B\" ,  sans-serif;\n    background: url( img.png );\n    --gap:  0px ;\n  }\n  p {}\n  @media (min-width:   10px) {\n    p { transition: opacity 0s; }\n  }\n</style>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            minify_css: true,
+            ..TransformOptions::default()
+        },
+    );
+
+    let css = result.css.expect("expected transformed css");
+    assert!(
+        css.contains("{margin:0 0 calc(0px + 1em);color:#abc;font-family:\"A  B\",sans-serif;background:url(img.png);--gap:0px;}@media (min-width: 10px){"),
+        "{css}"
+    );
+    assert!(css.contains("{transition:opacity 0s;}}"), "{css}");
+    assert!(!css.contains('\n'), "{css}");
+}
+
+#[test]
+fn transform_keeps_selectors_matching_dynamic_class_values() {
+    let source = "<script>let { on, rest } = $props();</script><style>.a { color: red; } .b { color: blue; } .c { color: green; } .d { color: gray; } i.missing { color: pink; } b.spread { color: navy; }</style><p class={on ? 'a' : 'b'}></p><span class={['c', on && { d: true }]}></span><b {...rest}></b>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform(&parsed.root, &analysis);
+
+    let css = result.css.expect("expected transformed css");
+    assert!(css.contains("color: red;"), "{css}");
+    assert!(css.contains("color: blue;"), "{css}");
+    assert!(css.contains("color: green;"), "{css}");
+    assert!(css.contains("color: gray;"), "{css}");
+    assert!(css.contains("color: navy;"), "{css}");
+    assert!(!css.contains("color: pink;"), "{css}");
+}
+
 #[test]
 fn transform_generates_expression_runtime_render() {
     let source = "<p>{name}</p>";
@@ -995,6 +1333,27 @@ fn transform_plain_event_attributes_are_omitted_on_non_load_error_elements() {
     assert!(!result.js.contains("alert(1)"));
 }
 
+#[test]
+fn transform_delegates_event_attributes_where_safe() {
+    let source = "<button onclick={go} ontouchstart={go} onclickcapture={go} onscroll={go}>x</button><svelte:element this={tag} onclick={go} />";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let client = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+    let js = client.js.replace(['\n', '\t'], "");
+    for expected in [
+        "__lux_event_attr(\"click\", function({ go }) {return go;}(_props), [], true)",
+        "__lux_event_attr(\"touchstart\", function({ go }) {return go;}(_props), [\"passive\"], true)",
+        "__lux_event_attr(\"click\", function({ go }) {return go;}(_props), [\"capture\"], false)",
+        "__lux_event_attr(\"scroll\", function({ go }) {return go;}(_props), [], false)",
+    ] {
+        assert!(js.contains(expected), "{expected}\n{}", client.js);
+    }
+    assert_eq!(js.matches(", [], true)").count(), 1, "{}", client.js);
+}
+
 #[test]
 fn transform_omits_bind_this_attribute_on_regular_element() {
     let source = "<div bind:this={el}></div>";
@@ -1125,6 +1484,148 @@ fn transform_svelte_component_bind_this_emits_assignment_side_effect() {
     assert!(result.js.contains("child = __lux_component"));
 }
 
+#[test]
+fn transform_wraps_component_css_custom_properties() {
+    let source = "<Child --color={color} label=\"x\" /><svg><Child --fill=\"blue\" /></svg>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    for target in [TransformTarget::Server, TransformTarget::Client] {
+        let result = transform_for_target(&parsed.root, &analysis, target);
+        assert!(
+            result.js.contains("css_props as __lux_css_props"),
+            "{}",
+            result.js
+        );
+        assert!(result.js.contains("\"--color\":"), "{}", result.js);
+        assert!(
+            result
+                .js
+                .contains("__lux_css_props(__lux_render_component("),
+            "{}",
+            result.js
+        );
+        assert!(result.js.contains("}, false)"), "{}", result.js);
+        assert!(result.js.contains("}, true)"), "{}", result.js);
+        assert!(
+            result
+                .js
+                .contains("const __lux_component_props = { label: [\"x\"].join(\"\") };"),
+            "{}",
+            result.js
+        );
+        assert_eq!(result.js.matches("\"--color\"").count(), 1, "{}", result.js);
+    }
+}
+
+#[test]
+fn transform_renders_svelte_boundary_through_boundary_runtime() {
+    let source = "<svelte:boundary onerror={report}><Child />{#snippet failed(error, reset)}<p>{error.message}</p>{/snippet}</svelte:boundary>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    for target in [TransformTarget::Server, TransformTarget::Client] {
+        let result = transform_for_target(&parsed.root, &analysis, target);
+        assert!(
+            result.js.contains("boundary as __lux_boundary"),
+            "{}",
+            result.js
+        );
+        assert!(
+            result.js.contains("_props.failed = function(error, reset)"),
+            "{}",
+            result.js
+        );
+        assert!(
+            result.js.contains("return __lux_boundary(function() {"),
+            "{}",
+            result.js
+        );
+        assert!(result.js.contains("return failed;"), "{}", result.js);
+        assert!(result.js.contains("return report;"), "{}", result.js);
+        assert!(result.js.contains("}(_props), null);"), "{}", result.js);
+    }
+
+    let client = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+    assert!(
+        client
+            .js
+            .contains("__lux_begin_render(__lux_mount_mode ? $$anchor : null, () => __lux_component($$anchor, $$props));"),
+        "{}",
+        client.js
+    );
+}
+
+#[test]
+fn transform_marks_keyed_each_items_on_client_target() {
+    let source = "<ul>{#each items as item (item.id)}<li animate:flip>{item.name}</li>{/each}</ul>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let client = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+    assert!(client.js.contains("block as __lux_block"), "{}", client.js);
+    assert!(
+        client.js.contains("return __lux_block(4, item.id, () => "),
+        "{}",
+        client.js
+    );
+    assert!(
+        !client
+            .js
+            .contains("__lux_cleanup_mount($$anchor), __lux_mount_html("),
+        "{}",
+        client.js
+    );
+
+    let server = transform_for_target(&parsed.root, &analysis, TransformTarget::Server);
+    assert!(!server.js.contains("__lux_block"), "{}", server.js);
+}
+
+#[test]
+fn transform_tracks_transition_blocks_and_scope_on_client_target() {
+    let source = "{#if show}<p transition:fade|global>a</p>{:else}<p in:fly out:fade>b</p>{/if}";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let client = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+    assert!(
+        client.js.contains("__lux_block(0, 0, () => "),
+        "{}",
+        client.js
+    );
+    assert!(
+        client.js.contains("__lux_block(0, 1, () => "),
+        "{}",
+        client.js
+    );
+    assert!(
+        client.js.contains("undefined, true, true, true)"),
+        "{}",
+        client.js
+    );
+    assert!(
+        client.js.contains("undefined, true, false, false)"),
+        "{}",
+        client.js
+    );
+    assert!(
+        client.js.contains("undefined, false, true, false)"),
+        "{}",
+        client.js
+    );
+
+    let server = transform_for_target(&parsed.root, &analysis, TransformTarget::Server);
+    assert!(!server.js.contains("__lux_block"), "{}", server.js);
+}
+
 #[test]
 fn transform_client_target_emits_mountable_default_export() {
     let source = "<p>{name}</p>";
@@ -1442,7 +1943,8 @@ fn transform_server_target_renders_checked_attribute_for_group_bindings() {
 
 #[test]
 fn transform_server_target_omits_value_attribute_for_file_input_binding() {
-    let source = "<script>let value = '/tmp/file';</script><input type=\"file\" bind:value={value}>";
+    let source =
+        "<script>let value = '/tmp/file';</script><input type=\"file\" bind:value={value}>";
     let allocator = Allocator::default();
     let parsed = parse(source, &allocator, false);
     assert!(parsed.errors.is_empty(), "parse should succeed");
@@ -1451,7 +1953,11 @@ fn transform_server_target_omits_value_attribute_for_file_input_binding() {
     let result = transform_for_target(&parsed.root, &analysis, TransformTarget::Server);
 
     assert!(!result.js.contains("__lux_attr(\"value\""), "{}", result.js);
-    assert!(result.js.contains("__lux_bind_attr(\"value\""), "{}", result.js);
+    assert!(
+        result.js.contains("__lux_bind_attr(\"value\""),
+        "{}",
+        result.js
+    );
 }
 
 #[test]
@@ -1465,13 +1971,15 @@ fn transform_server_target_imports_rest_props_helper_for_exported_props() {
     let result = transform_for_target(&parsed.root, &analysis, TransformTarget::Server);
 
     assert!(
-        result
-            .js
-            .contains("rest_props as __lux_rest_props"),
+        result.js.contains("rest_props as __lux_rest_props"),
+        "{}",
+        result.js
+    );
+    assert!(
+        result.js.contains("const $$restProps = __lux_rest_props"),
         "{}",
         result.js
     );
-    assert!(result.js.contains("const $$restProps = __lux_rest_props"), "{}", result.js);
 }
 
 #[test]
@@ -1653,7 +2161,7 @@ fn transform_client_target_emits_attach_runtime_hooks() {
     let analysis = analyze(&parsed.root);
     let result = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
 
-    assert!(result.js.contains("__lux_use_attr(\"attach\""));
+    assert!(result.js.contains("__lux_attach_attr(() => attach)"));
     assert!(
         result
             .js
@@ -1661,6 +2169,36 @@ fn transform_client_target_emits_attach_runtime_hooks() {
     );
 }
 
+#[test]
+fn transform_forwards_component_attachments_to_spread_targets() {
+    let source = "<script>import Child from './Child.svelte'; const attach = () => {};</script><Child {@attach attach} /><div {@attach attach} {@attach (node) => node}></div>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+
+    assert!(
+        result.js.contains("{ [__lux_attachment_key()]: attach }"),
+        "{}",
+        result.js
+    );
+    assert_eq!(
+        result.js.matches("__lux_attach_attr(").count(),
+        2,
+        "{}",
+        result.js
+    );
+
+    let server = transform_for_target(&parsed.root, &analysis, TransformTarget::Server);
+    assert!(
+        server.js.contains("attach_attr as __lux_attach_attr"),
+        "{}",
+        server.js
+    );
+}
+
 #[test]
 fn transform_client_target_emits_transition_runtime_hooks() {
     let source = "<script>const fade = () => ({ in() {}, out() {}, destroy() {} });</script><div transition:fade></div>";
@@ -1819,6 +2357,435 @@ fn transform_client_target_runtime_cleans_bind_this_with_null_on_teardown() {
     assert!(runtime.contains("return () => setter(null);"));
 }
 
+#[test]
+fn transform_client_target_persists_state_and_propagates_bound_prop_writes() {
+    let source = "<script>import Child from './Child.svelte'; let count = $state(0); let child; let { value = $bindable(5), label: text } = $props(); function rename(count) { count = 1; text = 'x'; }</script><button onclick={() => count++}>{value}</button><Child bind:value={count} bind:this={child} />";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let client = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+    let js = client.js.replace(['\n', '\t'], "");
+    for expected in [
+        "const _props = __lux_props(__lux_render_state, ",
+        "let count = __lux_state(__lux_render_state, \"count\", 0);",
+        "let { value = 5, label: text } = _props;",
+        "function rename(count) {count = 1;__lux_set(__lux_render_state, text = \"x\", { label: text });}",
+        "() => __lux_set(__lux_render_state, count++, { count })",
+        "set value($$value) {__lux_set(__lux_render_state, count = __lux_proxy($$value), { count });}",
+        "__lux_render_state, function(__lux_component) {child = __lux_component;})",
+    ] {
+        assert!(js.contains(expected), "missing `{expected}` in {js}");
+    }
+    assert!(
+        js.find("const __lux_render_state").unwrap() < js.find("let count").unwrap(),
+        "{js}"
+    );
+
+    let server = transform_for_target(&parsed.root, &analysis, TransformTarget::Server);
+    assert!(!server.js.contains("__lux_set"), "{}", server.js);
+    assert!(
+        server
+            .js
+            .contains("let { value = 5, label: text } = _props;"),
+        "{}",
+        server.js
+    );
+    assert!(
+        server.js.contains("child = __lux_component;"),
+        "{}",
+        server.js
+    );
+}
+
+#[test]
+fn transform_client_target_runs_effect_runes() {
+    let source = "<script>let count = $state(0); let doubled = $derived(count * 2); $effect(() => { console.log(doubled, $effect.tracking()); return () => {}; }); $effect.pre(() => { count = count; }); const stop = $effect.root(() => {}); const pending = $effect.pending();</script><button onclick={stop}>{count}</button>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    assert!(
+        analysis.diagnostics.is_empty(),
+        "{:?}",
+        analysis.diagnostics
+    );
+    let client = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+    let js = client.js.replace(['\n', '\t'], "");
+    for expected in [
+        "__lux_effect(__lux_render_state, () => {console.log(__lux_read(__lux_render_state, \"doubled\", doubled), __lux_effect_tracking());return () => {};});",
+        "__lux_effect_pre(__lux_render_state, () => {",
+        "__lux_read(__lux_render_state, \"count\", count)",
+        "__lux_track(__lux_render_state, () => ({count,doubled}));",
+        "const stop = __lux_effect_root(__lux_render_state, () => {});",
+        "const pending = __lux_effect_pending();",
+    ] {
+        assert!(js.contains(expected), "missing `{expected}` in {js}");
+    }
+    assert!(
+        client.runtime_modules[0]
+            .code
+            .contains("export function effect_root")
+    );
+    assert_js_parses_as_module(&client.js);
+
+    let server = transform_for_target(&parsed.root, &analysis, TransformTarget::Server);
+    assert!(!server.js.contains("__lux_effect("), "{}", server.js);
+    assert!(!server.js.contains("$effect"), "{}", server.js);
+}
+
+#[test]
+fn transform_client_target_proxies_deep_state() {
+    let source = "<script module>export class Todo { #done = 1; done = $state(false); text = $state.raw(''); tags; constructor(tags) { this.tags = $state(tags); } }</script><script>let count = $state(0); let todos = $state([]); let raw = $state.raw({ n: 1 }); function reset() { todos = []; raw = {}; } const copy = $state.snapshot(todos);</script><button onclick={reset}>{todos.length}</button>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let client = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+    let js = client.js.replace(['\n', '\t'], "");
+    for expected in [
+        "#done = 1;#done_1 = false;get done() {__lux_observe(this);return this.#done_1;}",
+        "set done(value) {this.#done_1 = __lux_proxy(value);__lux_invalidate(this);}",
+        "#text = \"\";",
+        "set text(value) {this.#text = value;__lux_invalidate(this);}",
+        "#tags;get tags() {__lux_observe(this);return this.#tags;}",
+        "constructor(tags) {this.#tags = __lux_proxy(tags);}",
+        "let count = __lux_state(__lux_render_state, \"count\", 0);",
+        "let todos = __lux_state(__lux_render_state, \"todos\", __lux_proxy([]));",
+        "let raw = __lux_state(__lux_render_state, \"raw\", { n: 1 });",
+        "__lux_set(__lux_render_state, todos = __lux_proxy([]), { todos });",
+        "__lux_set(__lux_render_state, raw = {}, { raw });",
+        "const copy = __lux_snapshot(todos);",
+    ] {
+        assert!(js.contains(expected), "missing `{expected}` in {js}");
+    }
+    // The plain declaration would hide the accessors of the constructor field.
+    assert!(!js.contains("}tags;"), "{js}");
+    assert!(
+        client.runtime_modules[0]
+            .code
+            .contains("export function proxy")
+    );
+    assert_js_parses_as_module(&client.js);
+
+    let server = transform_for_target(&parsed.root, &analysis, TransformTarget::Server);
+    assert!(!server.js.contains("__lux_proxy"), "{}", server.js);
+    assert!(server.js.contains("done = false;"), "{}", server.js);
+    assert!(server.js.contains("let todos = [];"), "{}", server.js);
+}
+
+#[test]
+fn transform_client_target_dev_mode_logs_inspect_runes() {
+    let source = "<script>let count = $state(0); $inspect(count, ...rest); $inspect(count).with(console.trace); $effect(function logger() { $inspect.trace(); console.log(count); }); const double = () => { $inspect.trace('double'); return count * 2; };</script><p>{count}</p>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    assert!(
+        analysis.diagnostics.is_empty(),
+        "{:?}",
+        analysis.diagnostics
+    );
+    let dev = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            target: TransformTarget::Client,
+            dev: true,
+            ..TransformOptions::default()
+        },
+    );
+    let js = dev.js.replace(['\n', '\t'], "");
+    for expected in [
+        "__lux_inspect(__lux_render_state, 31, [count, ...rest]);",
+        "__lux_inspect(__lux_render_state, 57, [count], console.trace);",
+        "__lux_inspect_trace(__lux_render_state, 122, \"logger\", () => ({ count }));",
+        "__lux_inspect_trace(__lux_render_state, 187, \"double\", () => ({ count }));",
+    ] {
+        assert!(js.contains(expected), "missing `{expected}` in {js}");
+    }
+    assert!(
+        dev.runtime_modules[0]
+            .code
+            .contains("export function inspect_trace")
+    );
+    assert_js_parses_as_module(&dev.js);
+
+    for target in [TransformTarget::Client, TransformTarget::Server] {
+        let result = transform_for_target(&parsed.root, &analysis, target);
+        assert!(!result.js.contains("__lux_inspect("), "{}", result.js);
+        assert!(!result.js.contains("__lux_inspect_trace("), "{}", result.js);
+        assert!(!result.js.contains(".with("), "{}", result.js);
+        assert!(!result.js.contains("$inspect"), "{}", result.js);
+    }
+}
+
+#[test]
+fn transform_client_target_wraps_custom_element_component() {
+    let source = "<svelte:options customElement={{ tag: \"my-counter\", shadow: \"none\", props: { count: { reflect: true, type: \"Number\" } } }} /><script>let { count = 0, label } = $props();</script><p>{label}: {count}</p>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+
+    assert!(
+        result
+            .js
+            .contains("export default __lux_create_custom_element(function __lux_component"),
+        "missing custom element wrapper:\n{}",
+        result.js
+    );
+    assert!(result.js.contains("tag: \"my-counter\""));
+    assert!(result.js.contains("names: [\"count\", \"label\"]"));
+    assert!(result.js.contains("shadow: \"none\""));
+    assert!(result.js.contains("reflect: true"));
+    assert!(result.js.contains("css: __lux_css"));
+    assert!(
+        result.runtime_modules[0]
+            .code
+            .contains("export function create_custom_element")
+    );
+    assert_js_parses_as_module(&result.js);
+}
+
+#[test]
+fn transform_client_target_custom_element_defaults_to_open_shadow_root() {
+    let source =
+        "<svelte:options customElement=\"my-element\" /><style>p { color: red; }</style><p>hi</p>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+
+    assert!(result.js.contains("tag: \"my-element\""));
+    assert!(result.js.contains("shadow: \"open\""));
+    assert_js_parses_as_module(&result.js);
+}
+
+#[test]
+fn transform_server_target_ignores_custom_element_options() {
+    let source = "<svelte:options customElement=\"my-element\" /><p>hi</p>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform(&parsed.root, &analysis);
+
+    assert!(!result.js.contains("__lux_create_custom_element"));
+}
+
+#[test]
+fn transform_renders_svg_children_with_foreign_serialization() {
+    let source = "<svg viewBox=\"0 0 10 10\"><use xlink:href=\"#icon\" /><path d=\"M0\"></path><foreignObject><div tabIndex=\"0\"></div><br></foreignObject></svg><div tabIndex=\"1\"></div>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform(&parsed.root, &analysis);
+
+    assert!(
+        result.js.contains(
+            "<svg viewBox=\\\"0 0 10 10\\\"><use xlink:href=\\\"#icon\\\"/><path d=\\\"M0\\\"/>"
+        ),
+        "svg output should keep attribute casing and self-close:\n{}",
+        result.js
+    );
+    assert!(
+        result
+            .js
+            .contains("<foreignObject><div tabindex=\\\"0\\\"></div><br></foreignObject>")
+    );
+    assert!(result.js.contains("<div tabindex=\\\"1\\\"></div>"));
+}
+
+#[test]
+fn transform_client_target_mounts_svg_namespace_component() {
+    let source = "<svelte:options namespace=\"svg\" /><script>let radius = 4;</script><circle r={radius} pathLength=\"10\" />";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_for_target(&parsed.root, &analysis, TransformTarget::Client);
+
+    assert!(
+        result.js.contains("__lux_attr(\"pathLength\""),
+        "{}",
+        result.js
+    );
+    assert!(result.js.contains("\"/>\""));
+    assert!(
+        result
+            .js
+            .contains("__lux_mount_html($$anchor, __lux_html, \"svg\")")
+    );
+    assert!(
+        result.runtime_modules[0]
+            .code
+            .contains("owner_document.createElementNS(namespace_uri")
+    );
+    assert_js_parses_as_module(&result.js);
+}
+
+#[test]
+fn transform_collapses_whitespace_between_nodes() {
+    let source = "\n<p>\n  hello\n</p>\n\n<pre>\n  keep   this\n</pre>\n<table>\n  <tr> <td> x </td> </tr>\n</table>\n";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform(&parsed.root, &analysis);
+
+    assert!(
+        result.js.contains(
+            "\"<p>hello</p> <pre>\\n  keep   this\\n</pre> <table><tr><td>x</td></tr></table>\""
+        ),
+        "unexpected whitespace handling:\n{}",
+        result.js
+    );
+}
+
+#[test]
+fn transform_keeps_whitespace_when_preserve_whitespace_is_set() {
+    let source = "<svelte:options preserveWhitespace={true} />\n<p>\n  hello\n</p>\n";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform(&parsed.root, &analysis);
+
+    assert!(
+        result.js.contains("\"\\n<p>\\n  hello\\n</p>\\n\""),
+        "whitespace should be preserved:\n{}",
+        result.js
+    );
+}
+
+#[test]
+fn transform_experimental_async_awaits_blocks_on_server() {
+    let source = "<script>let { load, items } = $props();</script>{#await load()}<p>loading</p>{:then value}<p>{value}</p>{/await}{#each items as item}<span>{item}</span>{/each}";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            experimental_async: true,
+            ..TransformOptions::default()
+        },
+    );
+
+    assert!(result.js.contains("$$renderer.child(async ($$renderer) =>"));
+    assert!(result.js.contains("const __lux_await_value = await load()"));
+    assert!(!result.js.contains("loading"));
+    assert!(
+        result
+            .js
+            .contains("await Promise.all(Array.from(items ?? []).map(async function(item)")
+    );
+    assert!(result.js.contains("async: true"));
+    assert!(result.runtime_modules[0].code.contains("component.async"));
+}
+
+#[test]
+fn transform_experimental_async_keeps_client_target_synchronous() {
+    let source = "{#await load()}<p>loading</p>{:then value}<p>{value}</p>{/await}";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            target: TransformTarget::Client,
+            experimental_async: true,
+            ..TransformOptions::default()
+        },
+    );
+
+    assert!(!result.js.contains("async function"));
+    assert!(result.js.contains("loading"));
+}
+
+#[test]
+fn transform_streaming_flushes_before_await_and_boundary() {
+    let source = "<script>let { load } = $props();</script><h1>title</h1>{#await load() then value}<p>{value}</p>{/await}<svelte:boundary><p>inside</p></svelte:boundary>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            streaming: true,
+            ..TransformOptions::default()
+        },
+    );
+
+    assert!(
+        result
+            .js
+            .contains("stream: async function __lux_stream($$renderer, _props = {})")
+    );
+    assert!(result.js.contains("$$renderer.push([\n\t\t\t\"<h1\""));
+    assert_eq!(result.js.matches("await $$renderer.flush();").count(), 2);
+    assert!(result.js.contains("const __lux_await_value = await load()"));
+    assert!(
+        result.runtime_modules[0]
+            .code
+            .contains("export function render_stream(")
+    );
+}
+
+#[test]
+fn transform_streaming_flushes_before_await_nested_in_elements() {
+    let source = "<script>let { load } = $props();</script><main><div class=\"list\">{#await load() then value}<p>{value}</p>{/await}</div><p>after</p></main>";
+    let allocator = Allocator::default();
+    let parsed = parse(source, &allocator, false);
+    assert!(parsed.errors.is_empty(), "parse should succeed");
+
+    let analysis = analyze(&parsed.root);
+    let result = transform_with_options(
+        &parsed.root,
+        &analysis,
+        &TransformOptions {
+            streaming: true,
+            ..TransformOptions::default()
+        },
+    );
+
+    let js = &result.js;
+    assert_eq!(js.matches("await $$renderer.flush();").count(), 1, "{js}");
+    let open = js.find("\"<div\",").expect("div should be streamed");
+    let flush = js.find("await $$renderer.flush();").unwrap();
+    let close = js
+        .find("$$renderer.push(\"</div>\");")
+        .expect("div should be closed");
+    let end = js
+        .find("$$renderer.push(\"</main>\");")
+        .expect("main should be closed");
+    assert!(open < flush && flush < close && close < end, "{js}");
+}
+
 fn assert_component_js_payload(js: &str) {
     assert!(
         js.contains("const __lux_template = "),
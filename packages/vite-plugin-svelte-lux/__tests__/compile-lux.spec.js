@@ -85,6 +85,7 @@ describe('createCompileSvelte with Lux', () => {
 				compilerOptions: {
 					runes: true,
 					customElement: true,
+					experimental: { async: true },
 					preserveWhitespace: true,
 					css: 'injected'
 				},
@@ -98,6 +99,7 @@ describe('createCompileSvelte with Lux', () => {
 			filename: '/some/File.svelte',
 			runes: true,
 			customElement: true,
+			experimental: { async: true },
 			preserveWhitespace: true,
 			css: 'injected'
 		});
@@ -121,4 +123,46 @@ describe('createCompileSvelte with Lux', () => {
 		expect(output.compiled.warnings[0].code).toBe('test_warning');
 		expect(getLuxRuntimeModule('lux/runtime/server')).toBe('export const ok = true;');
 	});
+
+	it('forwards Lux compiler options', async () => {
+		luxCompile.mockReturnValue({ js: 'export default {};', errors: [], warnings: [] });
+		const cssUrlResolver = (/** @type {{ value: string }} */ asset) => `/assets/${asset.value}`;
+		const cssHash = (/** @type {{ name: string }} */ { name }) => `lux-${name}`;
+
+		const compileSvelte = createCompileSvelte();
+		await compileSvelte(
+			{
+				cssId: 'svelte-xxxxx',
+				query: {},
+				raw: false,
+				ssr: true,
+				timestamp: Date.now(),
+				id: 'id',
+				filename: '/some/File.svelte',
+				normalizedFilename: 'some/File.svelte'
+			},
+			'<h1>ok</h1>',
+			{
+				compilerOptions: {
+					dev: true,
+					cssHash,
+					minifyCss: true,
+					lowerCssNesting: true,
+					cssUrlResolver
+				},
+				root: process.cwd()
+			}
+		);
+
+		expect(luxCompile).toHaveBeenCalledWith('<h1>ok</h1>', {
+			ts: false,
+			generate: 'server',
+			filename: '/some/File.svelte',
+			dev: true,
+			cssHash,
+			minifyCss: true,
+			lowerCssNesting: true,
+			cssUrlResolver
+		});
+	});
 });
@@ -16,6 +16,12 @@ const scriptLangRE =
 /** @type {Map<string, string>} */
 const luxRuntimeModules = new Map();
 
+/**
+ * compilerOptions that only the Lux compiler understands. They are forwarded to it
+ * and left out when compiling with svelte/compiler.
+ */
+const luxOnlyCompileOptions = ['minifyCss', 'lowerCssNesting', 'cssUrlResolver'];
+
 /**
  * @param {string} specifier
  */
@@ -127,13 +133,19 @@ export function createCompileSvelte() {
 						log.warn.once(
 							'lux client transform is not implemented yet; falling back to svelte/compiler for client output'
 						);
-						compiled = svelte.compile(finalCode, { ...finalCompileOptions, filename });
+						compiled = svelte.compile(finalCode, {
+							...toSvelteCompileOptions(finalCompileOptions),
+							filename
+						});
 					} else {
 						throw luxError;
 					}
 				}
 			} else {
-				compiled = svelte.compile(finalCode, { ...finalCompileOptions, filename });
+				compiled = svelte.compile(finalCode, {
+					...toSvelteCompileOptions(finalCompileOptions),
+					filename
+				});
 			}
 
 			// patch output with partial accept until svelte does it
@@ -227,6 +239,9 @@ function compileWithLux(code, filename, isTypeScript, ssr, compileOptions) {
 		};
 	});
 
+	// `result.cssAssets` is not registered with Vite: external CSS goes through Vite's CSS
+	// pipeline, which resolves its url() and @import references itself, and the references
+	// of injected CSS are rewritten with `compilerOptions.cssUrlResolver`.
 	const compiled = {
 		js: {
 			code: result.js,
@@ -263,14 +278,18 @@ function toLuxCompileOptions(compileOptions, isTypeScript, ssr) {
 	for (const key of [
 		'filename',
 		'rootDir',
+		'dev',
 		'runes',
 		'immutable',
 		'accessors',
 		'preserveWhitespace',
 		'customElement',
+		'experimental',
 		'outputFilename',
 		'cssOutputFilename',
-		'modernAst'
+		'cssHash',
+		'modernAst',
+		...luxOnlyCompileOptions
 	]) {
 		if (compileOptions[key] !== undefined) {
 			options[key] = compileOptions[key];
@@ -284,6 +303,18 @@ function toLuxCompileOptions(compileOptions, isTypeScript, ssr) {
 	return options;
 }
 
+/**
+ * @param {import('svelte/compiler').CompileOptions} compileOptions
+ * @returns {import('svelte/compiler').CompileOptions}
+ */
+function toSvelteCompileOptions(compileOptions) {
+	const options = /** @type {Record<string, unknown>} */ ({ ...compileOptions });
+	for (const key of luxOnlyCompileOptions) {
+		delete options[key];
+	}
+	return options;
+}
+
 /**
  * @param {string | null | undefined} raw
  */